@@ -0,0 +1,67 @@
+//! Acceleration structures for spatial queries on meshes.
+
+mod bvh;
+mod kdtree;
+mod spatial_hash;
+
+pub use bvh::Bvh;
+pub use kdtree::VertexKdTree;
+pub use spatial_hash::SpatialHash;
+
+/// Axis aligned bounding box.
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: glam::Vec3,
+    max: glam::Vec3,
+}
+
+impl Aabb {
+    fn empty() -> Self {
+        Aabb {
+            min: glam::Vec3::splat(f32::INFINITY),
+            max: glam::Vec3::splat(f32::NEG_INFINITY),
+        }
+    }
+
+    fn include(&mut self, p: glam::Vec3) {
+        self.min = self.min.min(p);
+        self.max = self.max.max(p);
+    }
+
+    /// Index of the axis along which the box is the longest.
+    fn longest_axis(&self) -> usize {
+        let d = self.max - self.min;
+        if d.x() >= d.y() && d.x() >= d.z() {
+            0
+        } else if d.y() >= d.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Intersect the ray with the box using the slab method, and return the
+    /// ray parameter at which the ray enters the box, if it hits it before
+    /// `tmax`.
+    fn ray_entry(&self, origin: glam::Vec3, inv_dir: glam::Vec3, tmax: f32) -> Option<f32> {
+        let t0 = (self.min - origin) * inv_dir;
+        let t1 = (self.max - origin) * inv_dir;
+        let tnear = t0.min(t1);
+        let tfar = t0.max(t1);
+        let tenter = tnear.x().max(tnear.y()).max(tnear.z()).max(0.0);
+        let texit = tfar.x().min(tfar.y()).min(tfar.z()).min(tmax);
+        if tenter <= texit {
+            Some(tenter)
+        } else {
+            None
+        }
+    }
+}
+
+fn component(v: glam::Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => v.x(),
+        1 => v.y(),
+        _ => v.z(),
+    }
+}
@@ -0,0 +1,215 @@
+use super::{component, Aabb};
+use crate::mesh::{Error, FaceHandle, Mesh};
+
+/// Maximum number of triangles in a leaf node.
+const LEAF_SIZE: usize = 4;
+
+struct Triangle {
+    face: FaceHandle,
+    verts: [glam::Vec3; 3],
+    centroid: glam::Vec3,
+}
+
+enum NodeKind {
+    /// Range of triangles in the leaf.
+    Leaf(usize, usize),
+    /// Indices of the child nodes.
+    Branch(usize, usize),
+}
+
+struct Node {
+    bounds: Aabb,
+    kind: NodeKind,
+}
+
+/// Bounding volume hierarchy over the faces of a mesh, for ray intersection
+/// queries. The faces are fanned into triangles from their first vertex.
+pub struct Bvh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    /// Build the hierarchy by recursively splitting the triangles at the
+    /// median of their centroids along the longest axis.
+    pub fn build(mesh: &Mesh) -> Result<Bvh, Error> {
+        let mut triangles = Vec::with_capacity(mesh.num_faces() * 2);
+        let mut fverts = Vec::new();
+        for f in mesh.faces() {
+            fverts.clear();
+            for v in mesh.fv_ccw_iter(f) {
+                fverts.push(mesh.point(v)?);
+            }
+            for i in 1..(fverts.len() - 1) {
+                let verts = [fverts[0], fverts[i], fverts[i + 1]];
+                triangles.push(Triangle {
+                    face: f,
+                    verts,
+                    centroid: (verts[0] + verts[1] + verts[2]) / 3.0,
+                });
+            }
+        }
+        let mut bvh = Bvh {
+            triangles,
+            nodes: Vec::new(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build_node(0, bvh.triangles.len());
+        }
+        Ok(bvh)
+    }
+
+    /// Build the node for the given range of triangles and return its index.
+    fn build_node(&mut self, begin: usize, end: usize) -> usize {
+        let mut bounds = Aabb::empty();
+        let mut cbounds = Aabb::empty();
+        for tri in &self.triangles[begin..end] {
+            for v in tri.verts {
+                bounds.include(v);
+            }
+            cbounds.include(tri.centroid);
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            kind: NodeKind::Leaf(begin, end),
+        });
+        if end - begin <= LEAF_SIZE {
+            return index;
+        }
+        let axis = cbounds.longest_axis();
+        let mid = (begin + end) / 2;
+        self.triangles[begin..end].select_nth_unstable_by(mid - begin, |a, b| {
+            component(a.centroid, axis).total_cmp(&component(b.centroid, axis))
+        });
+        let left = self.build_node(begin, mid);
+        let right = self.build_node(mid, end);
+        self.nodes[index].kind = NodeKind::Branch(left, right);
+        index
+    }
+
+    /// Find the first face hit by the ray. Returns the face and the ray
+    /// parameter of the hit, i.e. the hit point is `origin + dir * t`.
+    pub fn raycast(&self, origin: glam::Vec3, dir: glam::Vec3) -> Option<(FaceHandle, f32)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_dir = glam::Vec3::one() / dir;
+        let mut best: Option<(FaceHandle, f32)> = None;
+        let mut stack = vec![0usize];
+        while let Some(ni) = stack.pop() {
+            let node = &self.nodes[ni];
+            let tmax = best.map_or(f32::INFINITY, |(_, t)| t);
+            if node.bounds.ray_entry(origin, inv_dir, tmax).is_none() {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf(begin, end) => {
+                    for tri in &self.triangles[begin..end] {
+                        if let Some(t) = ray_triangle(origin, dir, &tri.verts) {
+                            if t < tmax && best.is_none_or(|(_, bt)| t < bt) {
+                                best = Some((tri.face, t));
+                            }
+                        }
+                    }
+                }
+                NodeKind::Branch(left, right) => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Intersect the ray with the triangle using the Möller–Trumbore algorithm,
+/// returning the ray parameter of the hit.
+fn ray_triangle(origin: glam::Vec3, dir: glam::Vec3, verts: &[glam::Vec3; 3]) -> Option<f32> {
+    const EPS: f32 = 1e-8;
+    let e1 = verts[1] - verts[0];
+    let e2 = verts[2] - verts[0];
+    let p = dir.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < EPS {
+        return None; // Parallel to the triangle.
+    }
+    let inv_det = 1.0 / det;
+    let s = origin - verts[0];
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = dir.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(q) * inv_det;
+    if t >= 0.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bvh;
+    use crate::mesh::{
+        test::{quad_box, quad_grid},
+        FaceHandle, Mesh,
+    };
+
+    #[test]
+    fn t_box_raycast() {
+        let mesh = quad_box();
+        let bvh = Bvh::build(&mesh).unwrap();
+        let (f, t) = bvh
+            .raycast(glam::vec3(0.5, 0.5, 5.0), glam::vec3(0.0, 0.0, -1.0))
+            .unwrap();
+        assert_eq!(f, FaceHandle(5));
+        assert!((t - 4.0).abs() < 1e-6);
+        let (f, t) = bvh
+            .raycast(glam::vec3(-2.0, 0.25, 0.75), glam::vec3(2.0, 0.0, 0.0))
+            .unwrap();
+        assert_eq!(f, FaceHandle(4));
+        assert!((t - 1.0).abs() < 1e-6);
+        // From the inside.
+        let (f, t) = bvh
+            .raycast(glam::vec3(0.5, 0.5, 0.5), glam::vec3(0.0, 1.0, 0.0))
+            .unwrap();
+        assert_eq!(f, FaceHandle(3));
+        assert!((t - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn t_box_raycast_miss() {
+        let bvh = Bvh::build(&quad_box()).unwrap();
+        assert!(bvh
+            .raycast(glam::vec3(5.0, 5.0, 5.0), glam::vec3(0.0, 0.0, 1.0))
+            .is_none());
+        assert!(bvh
+            .raycast(glam::vec3(0.5, 0.5, 5.0), glam::vec3(0.0, 0.0, 1.0))
+            .is_none());
+        assert!(Bvh::build(&Mesh::new())
+            .unwrap()
+            .raycast(glam::Vec3::zero(), glam::vec3(1.0, 0.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn t_grid_raycast_matches_closest_point() {
+        let mesh = quad_grid(16, 16);
+        let bvh = Bvh::build(&mesh).unwrap();
+        for i in 0..16 {
+            for j in 0..16 {
+                let origin = glam::vec3(i as f32 + 0.3, j as f32 + 0.6, 3.0);
+                let (f, t) = bvh.raycast(origin, glam::vec3(0.0, 0.0, -1.0)).unwrap();
+                let (cf, _, _) = mesh.closest_point(origin).unwrap().unwrap();
+                assert_eq!(f, cf);
+                assert!((t - 3.0).abs() < 1e-6);
+            }
+        }
+    }
+}
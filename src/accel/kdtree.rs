@@ -0,0 +1,172 @@
+use super::{component, Aabb};
+use crate::mesh::{Error, Mesh, VertexHandle};
+
+/// Kd-tree over the vertices of a mesh, for nearest vertex queries. The
+/// positions are read once when the tree is built, so the tree doesn't see
+/// any later edits to the mesh.
+///
+/// The tree is stored implicitly: the node of a range of entries is the
+/// entry in the middle of the range, and the entries before and after it
+/// make up the left and right subtrees.
+pub struct VertexKdTree {
+    entries: Vec<(glam::Vec3, VertexHandle)>,
+    /// Split axis of the node at the same index in `entries`.
+    axes: Vec<u8>,
+}
+
+impl VertexKdTree {
+    pub fn build(mesh: &Mesh) -> Result<VertexKdTree, Error> {
+        let entries = mesh
+            .vertices()
+            .map(|v| mesh.point(v).map(|p| (p, v)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut tree = VertexKdTree {
+            axes: vec![0; entries.len()],
+            entries,
+        };
+        tree.build_range(0, tree.entries.len());
+        Ok(tree)
+    }
+
+    fn build_range(&mut self, begin: usize, end: usize) {
+        if end - begin < 2 {
+            return;
+        }
+        let mut bounds = Aabb::empty();
+        for (p, _) in &self.entries[begin..end] {
+            bounds.include(*p);
+        }
+        let axis = bounds.longest_axis();
+        let mid = (begin + end) / 2;
+        self.entries[begin..end].select_nth_unstable_by(mid - begin, |a, b| {
+            component(a.0, axis).total_cmp(&component(b.0, axis))
+        });
+        self.axes[mid] = axis as u8;
+        self.build_range(begin, mid);
+        self.build_range(mid + 1, end);
+    }
+
+    /// The vertex closest to `p`, or `None` if the tree is empty.
+    pub fn nearest(&self, p: glam::Vec3) -> Option<VertexHandle> {
+        let mut best: Option<(VertexHandle, f32)> = None;
+        self.nearest_in_range(p, 0, self.entries.len(), &mut best);
+        best.map(|(v, _)| v)
+    }
+
+    fn nearest_in_range(
+        &self,
+        p: glam::Vec3,
+        begin: usize,
+        end: usize,
+        best: &mut Option<(VertexHandle, f32)>,
+    ) {
+        if begin >= end {
+            return;
+        }
+        let mid = (begin + end) / 2;
+        let (pos, v) = self.entries[mid];
+        let dsq = (pos - p).length_squared();
+        if best.is_none_or(|(_, bdsq)| dsq < bdsq) {
+            *best = Some((v, dsq));
+        }
+        let axis = self.axes[mid] as usize;
+        let delta = component(p, axis) - component(pos, axis);
+        let (near, far) = if delta < 0.0 {
+            ((begin, mid), (mid + 1, end))
+        } else {
+            ((mid + 1, end), (begin, mid))
+        };
+        self.nearest_in_range(p, near.0, near.1, best);
+        // Only visit the other side if it can contain something closer.
+        if best.is_none_or(|(_, bdsq)| delta * delta < bdsq) {
+            self.nearest_in_range(p, far.0, far.1, best);
+        }
+    }
+
+    /// All vertices within the distance `r` of `p`, in no particular order.
+    pub fn within_radius(&self, p: glam::Vec3, r: f32) -> Vec<VertexHandle> {
+        let mut out = Vec::new();
+        self.within_radius_in_range(p, r * r, 0, self.entries.len(), &mut out);
+        out
+    }
+
+    fn within_radius_in_range(
+        &self,
+        p: glam::Vec3,
+        rsq: f32,
+        begin: usize,
+        end: usize,
+        out: &mut Vec<VertexHandle>,
+    ) {
+        if begin >= end {
+            return;
+        }
+        let mid = (begin + end) / 2;
+        let (pos, v) = self.entries[mid];
+        if (pos - p).length_squared() <= rsq {
+            out.push(v);
+        }
+        let axis = self.axes[mid] as usize;
+        let delta = component(p, axis) - component(pos, axis);
+        if delta < 0.0 || delta * delta <= rsq {
+            self.within_radius_in_range(p, rsq, begin, mid, out);
+        }
+        if delta >= 0.0 || delta * delta <= rsq {
+            self.within_radius_in_range(p, rsq, mid + 1, end, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::VertexKdTree;
+    use crate::mesh::{test::quad_grid, Mesh, VertexHandle};
+
+    #[test]
+    fn t_grid_nearest() {
+        let mesh = quad_grid(10, 10);
+        let tree = VertexKdTree::build(&mesh).unwrap();
+        let nearest = |p| tree.nearest(p).map(u32::from);
+        assert_eq!(nearest(glam::vec3(-3.0, -2.0, 1.0)), Some(0));
+        assert_eq!(nearest(glam::vec3(12.0, -1.0, 0.0)), Some(10));
+        assert_eq!(nearest(glam::vec3(10.4, 10.3, 0.5)), Some(120));
+        assert_eq!(nearest(glam::vec3(-0.2, 10.1, 0.0)), Some(110));
+        // Compare against brute force.
+        for i in 0..25 {
+            let q = glam::vec3((i * 7 % 23) as f32 * 0.47, (i * 5 % 19) as f32 * 0.61, 0.3);
+            let expected = mesh
+                .vertices()
+                .min_by(|a, b| {
+                    let da = (mesh.point(*a).unwrap() - q).length_squared();
+                    let db = (mesh.point(*b).unwrap() - q).length_squared();
+                    da.total_cmp(&db)
+                })
+                .unwrap();
+            let found = tree.nearest(q).unwrap();
+            let dfound = (mesh.point(found).unwrap() - q).length_squared();
+            let dexpected = (mesh.point(expected).unwrap() - q).length_squared();
+            assert_eq!(dfound, dexpected);
+        }
+        assert!(VertexKdTree::build(&Mesh::new())
+            .unwrap()
+            .nearest(glam::Vec3::zero())
+            .is_none());
+    }
+
+    #[test]
+    fn t_grid_within_radius() {
+        let mesh = quad_grid(10, 10);
+        let tree = VertexKdTree::build(&mesh).unwrap();
+        // The vertex itself and its four neighbors.
+        let mut found = tree.within_radius(glam::vec3(5.0, 5.0, 0.0), 1.01);
+        found.sort();
+        assert_eq!(found, [49, 59, 60, 61, 71].map(VertexHandle));
+        // Add the diagonal neighbors.
+        assert_eq!(tree.within_radius(glam::vec3(5.0, 5.0, 0.0), 1.5).len(), 9);
+        // Corner.
+        assert_eq!(tree.within_radius(glam::vec3(0.0, 0.0, 0.0), 1.01).len(), 3);
+        assert!(tree
+            .within_radius(glam::vec3(0.5, 0.5, 3.0), 1.0)
+            .is_empty());
+    }
+}
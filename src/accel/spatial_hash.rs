@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+/// Uniform grid of cubic cells, hashed by their integer coordinates, that
+/// buckets handles by position. Only occupied cells take up memory, so the
+/// grid is unbounded. Useful for finding points within one cell length of a
+/// query, such as when welding coincident vertices.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i64, i64, i64), Vec<u32>>,
+}
+
+impl SpatialHash {
+    /// Create an empty grid with cells `cell_size` wide. This panics if
+    /// `cell_size` is not positive, because the cells would not partition
+    /// space.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Cell size must be positive");
+        SpatialHash {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell_of(&self, pos: glam::Vec3) -> (i64, i64, i64) {
+        let c = pos / self.cell_size;
+        (
+            c.x().floor() as i64,
+            c.y().floor() as i64,
+            c.z().floor() as i64,
+        )
+    }
+
+    /// Add the handle to the cell containing `pos`.
+    pub fn insert(&mut self, pos: glam::Vec3, handle: u32) {
+        let cell = self.cell_of(pos);
+        self.cells.entry(cell).or_default().push(handle);
+    }
+
+    /// Iterate over the handles in the cell containing `pos` and the 26 cells
+    /// around it. This includes every handle inserted within one cell length
+    /// of `pos`, along with some that are further away.
+    pub fn query_cell_neighbors(&self, pos: glam::Vec3) -> impl Iterator<Item = u32> + '_ {
+        let (cx, cy, cz) = self.cell_of(pos);
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+            .filter_map(move |(dx, dy, dz)| self.cells.get(&(cx + dx, cy + dy, cz + dz)))
+            .flatten()
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SpatialHash;
+
+    #[test]
+    fn t_spatial_hash_neighbors() {
+        let mut grid = SpatialHash::new(1.0);
+        // A cluster around the origin, and a few points far away.
+        let cluster = [
+            glam::vec3(0.1, 0.1, 0.1),
+            glam::vec3(-0.4, 0.2, 0.0),
+            glam::vec3(0.9, -0.9, 0.5),
+            glam::vec3(-0.99, -0.99, -0.99),
+        ];
+        let far = [
+            glam::vec3(5.0, 0.0, 0.0),
+            glam::vec3(0.0, -3.5, 0.0),
+            glam::vec3(2.5, 2.5, 2.5),
+        ];
+        for (i, p) in cluster.iter().chain(far.iter()).enumerate() {
+            grid.insert(*p, i as u32);
+        }
+        let mut found: Vec<u32> = grid.query_cell_neighbors(glam::Vec3::zero()).collect();
+        found.sort();
+        assert_eq!(found, vec![0, 1, 2, 3]);
+        assert!(grid.query_cell_neighbors(glam::vec3(5.2, 0.1, 0.0)).eq([4]));
+        assert_eq!(
+            grid.query_cell_neighbors(glam::vec3(-10.0, 0.0, 0.0))
+                .count(),
+            0
+        );
+        // Everything within one cell length is found.
+        let q = glam::vec3(1.7, 1.7, 1.7);
+        let found: Vec<u32> = grid.query_cell_neighbors(q).collect();
+        for (i, p) in cluster.iter().chain(far.iter()).enumerate() {
+            if (*p - q).length() < 1.0 {
+                assert!(found.contains(&(i as u32)));
+            }
+        }
+        assert!(found.contains(&6));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cell size must be positive")]
+    fn t_spatial_hash_zero_cell_size() {
+        SpatialHash::new(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cell size must be positive")]
+    fn t_spatial_hash_nan_cell_size() {
+        SpatialHash::new(f32::NAN);
+    }
+}
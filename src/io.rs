@@ -0,0 +1,52 @@
+mod obj;
+mod off;
+mod ply;
+mod stl;
+
+pub use ply::{PlyColumn, TPlyScalar};
+
+use crate::mesh::Error;
+use std::io::BufRead;
+
+/// Iterate over the lines of the reader that have content, along with their
+/// 1-based line numbers. Blank lines and comments starting with `#` are
+/// skipped.
+fn content_lines<R: BufRead>(reader: R) -> impl Iterator<Item = Result<(usize, String), Error>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| match line {
+            Ok(line) => {
+                let content = match line.find('#') {
+                    Some(pos) => &line[..pos],
+                    None => &line,
+                }
+                .trim();
+                if content.is_empty() {
+                    None
+                } else {
+                    Some(Ok((i + 1, content.to_string())))
+                }
+            }
+            Err(err) => Some(Err(Error::Io(err))),
+        })
+}
+
+/// Most elements reserved up front from the counts in a file header. The
+/// counts come from the file and can't be trusted, so a bogus count fails
+/// when the elements run out instead of allocating a huge mesh. The mesh
+/// still grows past this as more elements are read.
+const MAX_RESERVED: usize = 1 << 20;
+
+/// Parse a whitespace separated token, reporting `what` was expected if it
+/// fails.
+fn parse_token<T: std::str::FromStr>(
+    token: Option<&str>,
+    line: usize,
+    what: &str,
+) -> Result<T, Error> {
+    let token = token.ok_or_else(|| Error::ParseFailed(line, format!("expected {what}")))?;
+    token
+        .parse()
+        .map_err(|_| Error::ParseFailed(line, format!("expected {what}, found '{token}'")))
+}
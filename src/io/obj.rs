@@ -0,0 +1,227 @@
+use super::{content_lines, parse_token};
+use crate::mesh::{Error, Mesh, VertexHandle};
+use std::io::BufRead;
+
+/// Resolve a 1-based OBJ index, where negative indices count back from the
+/// most recently defined element.
+fn resolve_index(token: &str, count: usize, lnum: usize, what: &str) -> Result<u32, Error> {
+    let i: i64 = parse_token(Some(token), lnum, what)?;
+    let resolved = if i < 0 { count as i64 + i } else { i - 1 };
+    if i == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(Error::ParseFailed(
+            lnum,
+            format!("{what} {i} is out of range, there are {count}"),
+        ));
+    }
+    Ok(resolved as u32)
+}
+
+impl Mesh {
+    /// Load a mesh from the Wavefront OBJ format. Vertex positions (`v`),
+    /// texture coordinates (`vt`) and faces (`f`) are read, and everything
+    /// else is ignored. The texture coordinates of a face corner are stored
+    /// on the halfedge of the face that points to the corner, see
+    /// `Mesh::halfedge_uv`, so that vertices on seams keep a separate
+    /// coordinate for each face. Vertices written as `v x y z r g b` get the
+    /// color `(r, g, b)`, see `Mesh::vertex_color`.
+    pub fn load_obj<R: BufRead>(reader: R) -> Result<Mesh, Error> {
+        let mut mesh = Mesh::new();
+        let mut uvs: Vec<glam::Vec2> = Vec::new();
+        let mut fverts: Vec<VertexHandle> = Vec::new();
+        let mut fuvs: Vec<u32> = Vec::new();
+        let mut halfedges = Vec::new();
+        for line in content_lines(reader) {
+            let (lnum, line) = line?;
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let x = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+                    let y = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+                    let z = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+                    let v = mesh.add_vertex(glam::vec3(x, y, z))?;
+                    // A color is an optional extension. A single extra value
+                    // is the weight of the vertex, which is ignored.
+                    let rest: Vec<&str> = tokens.collect();
+                    if let [r, g, b, ..] = rest[..] {
+                        let color = |t: &str| parse_token(Some(t), lnum, "a vertex color");
+                        mesh.set_vertex_color(v, glam::vec3(color(r)?, color(g)?, color(b)?))?;
+                    }
+                }
+                Some("vt") => {
+                    let u = parse_token(tokens.next(), lnum, "a texture coordinate")?;
+                    // The second coordinate is optional.
+                    let v = match tokens.next() {
+                        Some(t) => parse_token(Some(t), lnum, "a texture coordinate")?,
+                        None => 0.0,
+                    };
+                    uvs.push(glam::vec2(u, v));
+                }
+                Some("f") => {
+                    fverts.clear();
+                    fuvs.clear();
+                    for corner in tokens {
+                        // Each corner is `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+                        let mut parts = corner.split('/');
+                        let v = parts.next().unwrap_or("");
+                        fverts.push(VertexHandle(resolve_index(
+                            v,
+                            mesh.num_vertices(),
+                            lnum,
+                            "vertex",
+                        )?));
+                        match parts.next() {
+                            Some(vt) if !vt.is_empty() => {
+                                fuvs.push(resolve_index(vt, uvs.len(), lnum, "texture coordinate")?)
+                            }
+                            _ => {}
+                        }
+                    }
+                    if !fuvs.is_empty() && fuvs.len() != fverts.len() {
+                        return Err(Error::ParseFailed(
+                            lnum,
+                            "either all or none of the corners of a face must have texture coordinates"
+                                .to_string(),
+                        ));
+                    }
+                    mesh.add_face_with_halfedges(&fverts, &mut halfedges)
+                        .map_err(|e| {
+                            Error::ParseFailed(lnum, format!("unable to add face: {e}"))
+                        })?;
+                    // The i-th halfedge points to the corner after the i-th.
+                    for (i, h) in halfedges.iter().enumerate() {
+                        if let Some(vt) = fuvs.get((i + 1) % fverts.len()) {
+                            mesh.set_halfedge_uv(*h, uvs[*vt as usize])?;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{Error, HalfedgeHandle, Mesh, VertexHandle};
+
+    /// A unit cube, unwrapped into a cross. The vertices around the edges of
+    /// the cross are on the seams, and have different coordinates in the
+    /// faces on either side.
+    const CUBE: &str = "# Unit cube.
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+vt 0.25 0.0
+vt 0.5 0.0
+vt 0.0 0.25
+vt 0.25 0.25
+vt 0.5 0.25
+vt 0.75 0.25
+vt 1.0 0.25
+vt 0.0 0.5
+vt 0.25 0.5
+vt 0.5 0.5
+vt 0.75 0.5
+vt 1.0 0.5
+vt 0.25 0.75
+vt 0.5 0.75
+vt 0.25 1.0
+vt 0.5 1.0
+f 1/4 4/9 3/10 2/5
+f 1/4 2/5 6/2 5/1
+f 2/5 3/10 7/11 6/6
+f 3/10 4/9 8/13 7/14
+f 4/9 1/4 5/3 8/8
+f 5/16 6/14 7/13 8/15
+";
+
+    #[test]
+    fn t_load_obj_uvs() {
+        let mesh = Mesh::load_obj(CUBE.as_bytes()).expect("Unable to load");
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_faces(), 6);
+        assert!((mesh.volume().unwrap() - 1.0).abs() < 1e-6);
+        // The halfedge pointing to a corner carries the uv of that corner.
+        let h = mesh
+            .voh_ccw_iter(VertexHandle(0))
+            .find(|h| mesh.to_vertex(*h) == VertexHandle(3))
+            .unwrap();
+        assert_eq!(mesh.halfedge_uv(h).unwrap(), glam::vec2(0.25, 0.5));
+        // Vertex 4 is on a seam: it has different uvs in its faces.
+        let uvs: Vec<glam::Vec2> = mesh
+            .vih_ccw_iter(VertexHandle(4))
+            .filter(|h| !mesh.is_boundary_halfedge(*h))
+            .map(|h| mesh.halfedge_uv(h).unwrap())
+            .collect();
+        assert_eq!(uvs.len(), 3);
+        assert!(uvs.contains(&glam::vec2(0.25, 0.0)));
+        assert!(uvs.contains(&glam::vec2(0.0, 0.25)));
+        assert!(uvs.contains(&glam::vec2(0.5, 1.0)));
+        // Vertex 0 is not: it has the same uv in all three faces.
+        assert!(mesh
+            .vih_ccw_iter(VertexHandle(0))
+            .all(|h| mesh.halfedge_uv(h).unwrap() == glam::vec2(0.25, 0.25)));
+    }
+
+    #[test]
+    fn t_load_obj_without_uvs() {
+        let mesh = Mesh::load_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n".as_bytes())
+            .expect("Unable to load");
+        assert_eq!(mesh.num_faces(), 1);
+        assert!(mesh.fv_ccw_iter_u32(0).eq([0, 1, 2]));
+        assert!(matches!(
+            mesh.halfedge_uv(HalfedgeHandle(0)),
+            Err(Error::PropertyDoesNotExist)
+        ));
+    }
+
+    #[test]
+    fn t_load_obj_vertex_colors() {
+        let obj = "v 0 0 0 1 0 0\nv 1 0 0 0 1 0\nv 0 1 0 0 0 0.5\nf 1 2 3\n";
+        let mesh = Mesh::load_obj(obj.as_bytes()).expect("Unable to load");
+        assert_eq!(mesh.point_u32(1).unwrap(), glam::vec3(1.0, 0.0, 0.0));
+        assert_eq!(
+            mesh.vertex_color(VertexHandle(0)).unwrap(),
+            glam::vec3(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            mesh.vertex_color(VertexHandle(1)).unwrap(),
+            glam::vec3(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            mesh.vertex_color(VertexHandle(2)).unwrap(),
+            glam::vec3(0.0, 0.0, 0.5)
+        );
+        let mesh = Mesh::load_obj(CUBE.as_bytes()).expect("Unable to load");
+        assert!(matches!(
+            mesh.vertex_color(VertexHandle(0)),
+            Err(Error::PropertyDoesNotExist)
+        ));
+        // A weight is not a color.
+        let mesh = Mesh::load_obj("v 0 0 0 1\n".as_bytes()).expect("Unable to load");
+        assert!(mesh.vertex_color(VertexHandle(0)).is_err());
+        assert!(matches!(
+            Mesh::load_obj("v 0 0 0 1 0 x\n".as_bytes()),
+            Err(Error::ParseFailed(1, _))
+        ));
+    }
+
+    #[test]
+    fn t_load_obj_bad_index() {
+        assert!(matches!(
+            Mesh::load_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n".as_bytes()),
+            Err(Error::ParseFailed(4, _))
+        ));
+        assert!(matches!(
+            Mesh::load_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nf 1/1 2/2 3\n".as_bytes()),
+            Err(Error::ParseFailed(5, _))
+        ));
+    }
+}
@@ -0,0 +1,220 @@
+use super::{content_lines, parse_token, MAX_RESERVED};
+use crate::mesh::{Error, Mesh};
+use std::io::{BufRead, Write};
+
+impl Mesh {
+    /// Load a mesh from the Object File Format (OFF). The header is followed
+    /// by a line with the vertex, face and edge counts, then one line per
+    /// vertex and one line per face. Any extra values after the vertex
+    /// indices of a face, such as colors, are ignored.
+    pub fn load_off<R: BufRead>(reader: R) -> Result<Mesh, Error> {
+        let mut lines = content_lines(reader);
+        let mut last_line = 0usize;
+        let mut next_line = |what: &str| -> Result<(usize, String), Error> {
+            match lines.next() {
+                Some(Ok((lnum, line))) => {
+                    last_line = lnum;
+                    Ok((lnum, line))
+                }
+                Some(Err(err)) => Err(err),
+                None => Err(Error::ParseFailed(
+                    last_line,
+                    format!("unexpected end of file, expected {what}"),
+                )),
+            }
+        };
+        let (lnum, header) = next_line("the OFF header")?;
+        let counts = match header.strip_prefix("OFF") {
+            Some(rest) if rest.trim().is_empty() => next_line("the element counts")?,
+            // Some files put the counts on the same line as the header.
+            Some(rest) if rest.starts_with(char::is_whitespace) => (lnum, rest.trim().to_string()),
+            _ => {
+                return Err(Error::ParseFailed(
+                    lnum,
+                    format!("expected the OFF header, found '{header}'"),
+                ))
+            }
+        };
+        let (nverts, nfaces) = {
+            let (lnum, line) = counts;
+            let mut tokens = line.split_whitespace();
+            let nverts: usize = parse_token(tokens.next(), lnum, "the number of vertices")?;
+            let nfaces: usize = parse_token(tokens.next(), lnum, "the number of faces")?;
+            let _nedges: usize = parse_token(tokens.next(), lnum, "the number of edges")?;
+            (nverts, nfaces)
+        };
+        let (rverts, rfaces) = (nverts.min(MAX_RESERVED), nfaces.min(MAX_RESERVED));
+        let mut mesh = Mesh::with_capacity(rverts, rverts + rfaces, rfaces);
+        for _ in 0..nverts {
+            let (lnum, line) = next_line("a vertex")?;
+            let mut tokens = line.split_whitespace();
+            let x = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+            let y = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+            let z = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+            mesh.add_vertex_u32(glam::vec3(x, y, z))?;
+        }
+        let mut fverts: Vec<u32> = Vec::new();
+        for fi in 0..nfaces {
+            let (lnum, line) = next_line("a face")?;
+            let mut tokens = line.split_whitespace();
+            let n: usize = parse_token(tokens.next(), lnum, "the number of face vertices")?;
+            fverts.clear();
+            for _ in 0..n {
+                let v: u32 = parse_token(tokens.next(), lnum, "a vertex index")?;
+                if v as usize >= nverts {
+                    return Err(Error::ParseFailed(
+                        lnum,
+                        format!("face {fi} refers to vertex {v}, which does not exist"),
+                    ));
+                }
+                fverts.push(v);
+            }
+            mesh.add_face_u32(&fverts)
+                .map_err(|e| Error::ParseFailed(lnum, format!("unable to add face {fi}: {e}")))?;
+        }
+        Ok(mesh)
+    }
+
+    /// Write the mesh in the Object File Format (OFF). Deleted elements are
+    /// skipped, and the vertices are renumbered accordingly.
+    pub fn write_off<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let (indices, nverts) = self.compact_vertex_indices();
+        writeln!(writer, "OFF")?;
+        writeln!(
+            writer,
+            "{} {} {}",
+            nverts,
+            self.faces_u32().count(),
+            self.edges_u32().count()
+        )?;
+        for v in self.vertices_u32() {
+            let p = self.point_u32(v)?;
+            writeln!(writer, "{} {} {}", p.x(), p.y(), p.z())?;
+        }
+        for f in self.faces_u32() {
+            write!(writer, "{}", self.fv_ccw_iter_u32(f).count())?;
+            for v in self.fv_ccw_iter_u32(f) {
+                write!(writer, " {}", indices[v as usize])?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{
+        test::{quad_box, quad_grid},
+        Error, Mesh,
+    };
+
+    const TETRAHEDRON: &str = "OFF
+# A tetrahedron.
+4 4 6
+0 0 0
+1 0 0
+0 1 0
+0 0 1
+
+3 0 2 1
+3 0 1 3
+3 1 2 3
+3 2 0 3
+";
+
+    #[test]
+    fn t_load_off() {
+        let mesh = Mesh::load_off(TETRAHEDRON.as_bytes()).expect("Unable to load");
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_edges(), 6);
+        assert_eq!(mesh.num_faces(), 4);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.point_u32(3).unwrap(), glam::vec3(0.0, 0.0, 1.0));
+        assert!(mesh.fv_ccw_iter_u32(2).eq([1, 2, 3]));
+    }
+
+    #[test]
+    fn t_off_roundtrip() {
+        let mesh = quad_box();
+        let mut buf = Vec::new();
+        mesh.write_off(&mut buf).expect("Unable to write");
+        let copy = Mesh::load_off(buf.as_slice()).expect("Unable to load");
+        assert_eq!(copy.num_vertices(), mesh.num_vertices());
+        assert_eq!(copy.num_edges(), mesh.num_edges());
+        assert_eq!(copy.num_faces(), mesh.num_faces());
+        for v in 0..(mesh.num_vertices() as u32) {
+            assert_eq!(copy.point_u32(v).unwrap(), mesh.point_u32(v).unwrap());
+        }
+        for f in 0..(mesh.num_faces() as u32) {
+            assert!(copy.fv_ccw_iter_u32(f).eq(mesh.fv_ccw_iter_u32(f)));
+        }
+    }
+
+    #[test]
+    fn t_write_off_skips_deleted() {
+        let mut mesh = quad_grid(2, 1);
+        mesh.delete_face_u32(0, true).unwrap();
+        let mut buf = Vec::new();
+        mesh.write_off(&mut buf).unwrap();
+        let copy = Mesh::load_off(buf.as_slice()).unwrap();
+        copy.validate().unwrap();
+        assert_eq!(
+            (copy.num_vertices(), copy.num_edges(), copy.num_faces()),
+            (4, 4, 1)
+        );
+        for (i, v) in [1, 2, 4, 5].into_iter().enumerate() {
+            assert_eq!(
+                copy.point_u32(i as u32).unwrap(),
+                mesh.point_u32(v).unwrap()
+            );
+        }
+        assert!(copy.fv_ccw_iter_u32(0).eq([0, 1, 3, 2]));
+    }
+
+    #[test]
+    fn t_load_off_malformed_counts() {
+        let err = Mesh::load_off("OFF\n4 four 6\n".as_bytes()).err().unwrap();
+        match err {
+            Error::ParseFailed(line, reason) => {
+                assert_eq!(line, 2);
+                assert!(reason.contains("number of faces"));
+                assert!(reason.contains("four"));
+            }
+            _ => panic!("Unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn t_load_off_huge_counts() {
+        // The counts are not reserved as they are.
+        assert!(matches!(
+            Mesh::load_off("OFF\n18446744073709551615 1 0\n0 0 0\n".as_bytes()),
+            Err(Error::ParseFailed(3, _))
+        ));
+    }
+
+    #[test]
+    fn t_load_off_missing_header() {
+        assert!(matches!(
+            Mesh::load_off("4 4 6\n".as_bytes()),
+            Err(Error::ParseFailed(1, _))
+        ));
+    }
+
+    #[test]
+    fn t_load_off_truncated() {
+        assert!(matches!(
+            Mesh::load_off("OFF\n3 1 0\n0 0 0\n1 0 0\n".as_bytes()),
+            Err(Error::ParseFailed(4, _))
+        ));
+    }
+
+    #[test]
+    fn t_load_off_bad_vertex_index() {
+        let err = Mesh::load_off("OFF 3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 7\n".as_bytes())
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::ParseFailed(5, _)));
+    }
+}
@@ -0,0 +1,547 @@
+use super::{content_lines, parse_token, MAX_RESERVED};
+use crate::mesh::{Error, Mesh, Property, TPropData};
+use std::io::{BufRead, Write};
+
+/// Property data that can be written as a scalar property in a PLY file.
+pub trait TPlyScalar: TPropData + std::fmt::Display {
+    /// Name of the type in the PLY header.
+    const PLY_TYPE: &'static str;
+}
+
+impl TPlyScalar for i8 {
+    const PLY_TYPE: &'static str = "char";
+}
+
+impl TPlyScalar for u8 {
+    const PLY_TYPE: &'static str = "uchar";
+}
+
+impl TPlyScalar for i16 {
+    const PLY_TYPE: &'static str = "short";
+}
+
+impl TPlyScalar for u16 {
+    const PLY_TYPE: &'static str = "ushort";
+}
+
+impl TPlyScalar for i32 {
+    const PLY_TYPE: &'static str = "int";
+}
+
+impl TPlyScalar for u32 {
+    const PLY_TYPE: &'static str = "uint";
+}
+
+impl TPlyScalar for f32 {
+    const PLY_TYPE: &'static str = "float";
+}
+
+impl TPlyScalar for f64 {
+    const PLY_TYPE: &'static str = "double";
+}
+
+/// A property that can be written as a column of an element in a PLY file.
+/// This is implemented for properties of all `TPlyScalar` types, so that
+/// properties of different types can be written together.
+pub trait PlyColumn {
+    /// Name of the type of the column in the PLY header.
+    fn ply_type(&self) -> &'static str;
+
+    /// Write the value of the `i`-th element.
+    fn write_value(&self, i: u32, writer: &mut dyn Write) -> Result<(), Error>;
+}
+
+impl<T: TPlyScalar> PlyColumn for Property<T> {
+    fn ply_type(&self) -> &'static str {
+        T::PLY_TYPE
+    }
+
+    fn write_value(&self, i: u32, writer: &mut dyn Write) -> Result<(), Error> {
+        write!(writer, "{}", self.get(i)?)?;
+        Ok(())
+    }
+}
+
+/// A property declared in the header of a PLY file. Scalar properties keep
+/// their type, lists only their name.
+enum PlyProperty {
+    Scalar(String, String),
+    List(String),
+}
+
+impl PlyProperty {
+    fn name(&self) -> &str {
+        match self {
+            PlyProperty::Scalar(name, _) | PlyProperty::List(name) => name,
+        }
+    }
+}
+
+/// An element declared in the header of a PLY file.
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+impl PlyElement {
+    fn scalar_index(&self, name: &str) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|p| matches!(p, PlyProperty::Scalar(n, _) if n == name))
+    }
+
+    /// Indices of the `red`, `green` and `blue` properties, and the factor
+    /// that scales their values to the range 0 to 1. Floating point colors
+    /// are assumed to be in that range already, and integer colors to be in
+    /// the range 0 to 255.
+    fn color_indices(&self) -> Option<([usize; 3], f32)> {
+        let indices = [
+            self.scalar_index("red")?,
+            self.scalar_index("green")?,
+            self.scalar_index("blue")?,
+        ];
+        let scale = match &self.properties[indices[0]] {
+            PlyProperty::Scalar(_, ty)
+                if matches!(ty.as_str(), "float" | "double" | "float32" | "float64") =>
+            {
+                1.0
+            }
+            _ => 1.0 / 255.0,
+        };
+        Some((indices, scale))
+    }
+
+    fn list_index(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|p| matches!(p, PlyProperty::List(n) if names.contains(&n.as_str())))
+    }
+
+    /// Split a line of this element into the tokens of each property. Scalar
+    /// properties get one token, and list properties get all their items.
+    fn split<'a>(&self, line: &'a str, lnum: usize) -> Result<Vec<Vec<&'a str>>, Error> {
+        let mut tokens = line.split_whitespace();
+        self.properties
+            .iter()
+            .map(|prop| match prop {
+                PlyProperty::Scalar(name, _) => tokens
+                    .next()
+                    .map(|t| vec![t])
+                    .ok_or_else(|| Error::ParseFailed(lnum, format!("expected '{name}'"))),
+                PlyProperty::List(name) => {
+                    let n: usize =
+                        parse_token(tokens.next(), lnum, &format!("the length of '{name}'"))?;
+                    let items: Vec<_> = tokens.by_ref().take(n).collect();
+                    if items.len() == n {
+                        Ok(items)
+                    } else {
+                        Err(Error::ParseFailed(
+                            lnum,
+                            format!("expected {n} items in '{}'", prop.name()),
+                        ))
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parse the header of a PLY file, returning the declared elements in the
+/// order they appear in the file.
+fn parse_header(
+    lines: &mut impl Iterator<Item = Result<(usize, String), Error>>,
+) -> Result<Vec<PlyElement>, Error> {
+    let mut elements: Vec<PlyElement> = Vec::new();
+    let mut last_line = 0usize;
+    let mut seen_magic = false;
+    loop {
+        let (lnum, line) = match lines.next() {
+            Some(line) => line?,
+            None => {
+                return Err(Error::ParseFailed(
+                    last_line,
+                    "unexpected end of file in the PLY header".to_string(),
+                ))
+            }
+        };
+        last_line = lnum;
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next().unwrap_or_default();
+        if !seen_magic {
+            if keyword != "ply" {
+                return Err(Error::ParseFailed(
+                    lnum,
+                    format!("expected 'ply', found '{line}'"),
+                ));
+            }
+            seen_magic = true;
+            continue;
+        }
+        match keyword {
+            "format" => {
+                let format = tokens.next().unwrap_or_default();
+                if format != "ascii" {
+                    return Err(Error::ParseFailed(
+                        lnum,
+                        format!("only the ascii PLY format is supported, found '{format}'"),
+                    ));
+                }
+            }
+            "comment" | "obj_info" => {}
+            "element" => {
+                let name = tokens
+                    .next()
+                    .ok_or_else(|| Error::ParseFailed(lnum, "expected an element name".into()))?;
+                let count = parse_token(tokens.next(), lnum, "the number of elements")?;
+                elements.push(PlyElement {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            "property" => {
+                let element = elements.last_mut().ok_or_else(|| {
+                    Error::ParseFailed(lnum, "property declared before any element".into())
+                })?;
+                let prop = match tokens.next() {
+                    // list <count type> <item type> <name>
+                    Some("list") => tokens.nth(2).map(|n| PlyProperty::List(n.to_string())),
+                    // <type> <name>
+                    Some(ty) => tokens
+                        .next()
+                        .map(|n| PlyProperty::Scalar(n.to_string(), ty.to_string())),
+                    None => None,
+                }
+                .ok_or_else(|| Error::ParseFailed(lnum, "malformed property".into()))?;
+                element.properties.push(prop);
+            }
+            "end_header" => return Ok(elements),
+            _ => {
+                return Err(Error::ParseFailed(
+                    lnum,
+                    format!("unexpected '{keyword}' in the PLY header"),
+                ))
+            }
+        }
+    }
+}
+
+impl Mesh {
+    /// Load a mesh from an ascii PLY file. The vertex positions are read from
+    /// the `x`, `y` and `z` properties of the `vertex` element, and the faces
+    /// from the `vertex_indices` list of the `face` element. If the vertices
+    /// have `red`, `green` and `blue` properties, they are read as the vertex
+    /// colors, see `Mesh::vertex_color`. Integer colors are divided by 255.
+    /// Other properties and elements are skipped.
+    pub fn load_ply_ascii<R: BufRead>(reader: R) -> Result<Mesh, Error> {
+        let mut lines = content_lines(reader);
+        let elements = parse_header(&mut lines)?;
+        let count = |name: &str| {
+            elements
+                .iter()
+                .find(|e| e.name == name)
+                .map(|e| e.count)
+                .unwrap_or(0)
+        };
+        let nverts = count("vertex");
+        let nfaces = count("face");
+        let (rverts, rfaces) = (nverts.min(MAX_RESERVED), nfaces.min(MAX_RESERVED));
+        let mut mesh = Mesh::with_capacity(rverts, rverts + rfaces, rfaces);
+        let mut fverts: Vec<u32> = Vec::new();
+        let mut last_line = 0usize;
+        for element in elements.iter() {
+            let xyz = match element.name.as_str() {
+                "vertex" => {
+                    let index = |name: &str| {
+                        element.scalar_index(name).ok_or_else(|| {
+                            Error::ParseFailed(
+                                last_line,
+                                format!("the vertex element has no '{name}' property"),
+                            )
+                        })
+                    };
+                    Some([index("x")?, index("y")?, index("z")?])
+                }
+                _ => None,
+            };
+            let rgb = match element.name.as_str() {
+                "vertex" => element.color_indices(),
+                _ => None,
+            };
+            let vindices = match element.name.as_str() {
+                "face" => Some(
+                    element
+                        .list_index(&["vertex_indices", "vertex_index"])
+                        .ok_or_else(|| {
+                            Error::ParseFailed(
+                                last_line,
+                                "the face element has no 'vertex_indices' property".into(),
+                            )
+                        })?,
+                ),
+                _ => None,
+            };
+            for ei in 0..element.count {
+                let (lnum, line) = match lines.next() {
+                    Some(line) => line?,
+                    None => {
+                        return Err(Error::ParseFailed(
+                            last_line,
+                            format!("unexpected end of file, expected {}", element.name),
+                        ))
+                    }
+                };
+                last_line = lnum;
+                let values = element.split(&line, lnum)?;
+                if let Some([x, y, z]) = xyz {
+                    let coord =
+                        |i: usize| parse_token(values[i].first().copied(), lnum, "a coordinate");
+                    let v = mesh.add_vertex(glam::vec3(coord(x)?, coord(y)?, coord(z)?))?;
+                    if let Some(([r, g, b], scale)) = rgb {
+                        let channel = |i: usize| {
+                            parse_token::<f32>(values[i].first().copied(), lnum, "a color")
+                                .map(|c| c * scale)
+                        };
+                        mesh.set_vertex_color(
+                            v,
+                            glam::vec3(channel(r)?, channel(g)?, channel(b)?),
+                        )?;
+                    }
+                }
+                if let Some(vi) = vindices {
+                    fverts.clear();
+                    for token in values[vi].iter() {
+                        let v: u32 = parse_token(Some(token), lnum, "a vertex index")?;
+                        if v as usize >= mesh.num_vertices() {
+                            return Err(Error::ParseFailed(
+                                lnum,
+                                format!("face {ei} refers to vertex {v}, which does not exist"),
+                            ));
+                        }
+                        fverts.push(v);
+                    }
+                    mesh.add_face_u32(&fverts).map_err(|e| {
+                        Error::ParseFailed(lnum, format!("unable to add face {ei}: {e}"))
+                    })?;
+                }
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// Write the mesh as an ascii PLY file. Each of `face_props` is written as
+    /// a scalar property of the `face` element with the given name, after
+    /// the vertex indices. Colors are conventionally written as `uchar`
+    /// properties named `red`, `green` and `blue`. Deleted elements are
+    /// skipped, and the vertices are renumbered accordingly.
+    pub fn write_ply_ascii<W: Write>(
+        &self,
+        mut writer: W,
+        face_props: &[(&str, &dyn PlyColumn)],
+    ) -> Result<(), Error> {
+        let (indices, nverts) = self.compact_vertex_indices();
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format ascii 1.0")?;
+        writeln!(writer, "element vertex {nverts}")?;
+        for axis in ["x", "y", "z"] {
+            writeln!(writer, "property float {axis}")?;
+        }
+        writeln!(writer, "element face {}", self.faces_u32().count())?;
+        // Faces can have more than 255 vertices, so the count is an int.
+        writeln!(writer, "property list int int vertex_indices")?;
+        for (name, prop) in face_props {
+            writeln!(writer, "property {} {name}", prop.ply_type())?;
+        }
+        writeln!(writer, "end_header")?;
+        for v in self.vertices_u32() {
+            let p = self.point_u32(v)?;
+            writeln!(writer, "{} {} {}", p.x(), p.y(), p.z())?;
+        }
+        for f in self.faces_u32() {
+            write!(writer, "{}", self.fv_ccw_iter_u32(f).count())?;
+            for v in self.fv_ccw_iter_u32(f) {
+                write!(writer, " {}", indices[v as usize])?;
+            }
+            for (_, prop) in face_props {
+                write!(writer, " ")?;
+                prop.write_value(f, &mut writer)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{content_lines, parse_header, PlyProperty};
+    use crate::mesh::{test::quad_box, Error, Mesh, VertexHandle};
+
+    const TETRAHEDRON: &str = "ply
+format ascii 1.0
+comment A tetrahedron, with an extra vertex property before the position.
+element vertex 4
+property float confidence
+property float x
+property float y
+property float z
+element face 4
+property list uchar int vertex_indices
+end_header
+0.5 0 0 0
+0.5 1 0 0
+0.5 0 1 0
+0.5 0 0 1
+3 0 2 1
+3 0 1 3
+3 1 2 3
+3 2 0 3
+";
+
+    #[test]
+    fn t_load_ply_tetrahedron() {
+        let mesh = Mesh::load_ply_ascii(TETRAHEDRON.as_bytes()).expect("Unable to load");
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_edges(), 6);
+        assert_eq!(mesh.num_faces(), 4);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.genus(), Some(0));
+        assert_eq!(mesh.point_u32(1).unwrap(), glam::vec3(1.0, 0.0, 0.0));
+        assert!(mesh.fv_ccw_iter_u32(3).eq([2, 0, 3]));
+    }
+
+    #[test]
+    fn t_load_ply_vertex_colors() {
+        let ply = TETRAHEDRON
+            .replace(
+                "property float z\n",
+                "property float z\nproperty uchar red\nproperty uchar green\nproperty uchar blue\n",
+            )
+            .replace("0.5 0 0 0\n", "0.5 0 0 0 255 0 0\n")
+            .replace("0.5 1 0 0\n", "0.5 1 0 0 0 255 0\n")
+            .replace("0.5 0 1 0\n", "0.5 0 1 0 0 0 255\n")
+            .replace("0.5 0 0 1\n", "0.5 0 0 1 51 102 0\n");
+        let mesh = Mesh::load_ply_ascii(ply.as_bytes()).expect("Unable to load");
+        mesh.validate().unwrap();
+        assert_eq!(mesh.point_u32(3).unwrap(), glam::vec3(0.0, 0.0, 1.0));
+        let colors: Vec<_> = mesh
+            .vertices()
+            .map(|v| mesh.vertex_color(v).unwrap())
+            .collect();
+        assert_eq!(
+            colors[..3],
+            [
+                glam::vec3(1.0, 0.0, 0.0),
+                glam::vec3(0.0, 1.0, 0.0),
+                glam::vec3(0.0, 0.0, 1.0)
+            ]
+        );
+        assert!((colors[3] - glam::vec3(0.2, 0.4, 0.0)).length() < 1e-6);
+        // Floating point colors are read as they are.
+        let ply = ply
+            .replace("uchar", "float")
+            .replace("0.5 0 0 1 51 102 0\n", "0.5 0 0 1 0.25 0.5 1\n");
+        let mesh = Mesh::load_ply_ascii(ply.as_bytes()).expect("Unable to load");
+        assert_eq!(
+            mesh.vertex_color(VertexHandle(3)).unwrap(),
+            glam::vec3(0.25, 0.5, 1.0)
+        );
+        // Without colors, there is no color property.
+        let mesh = Mesh::load_ply_ascii(TETRAHEDRON.as_bytes()).expect("Unable to load");
+        assert!(matches!(
+            mesh.vertex_color(VertexHandle(0)),
+            Err(Error::PropertyDoesNotExist)
+        ));
+    }
+
+    #[test]
+    fn t_load_ply_binary() {
+        let ply = "ply\nformat binary_little_endian 1.0\nend_header\n";
+        assert!(matches!(
+            Mesh::load_ply_ascii(ply.as_bytes()),
+            Err(Error::ParseFailed(2, _))
+        ));
+    }
+
+    #[test]
+    fn t_load_ply_huge_counts() {
+        // The counts are not reserved as they are, so this runs out of lines.
+        let ply = TETRAHEDRON.replace(
+            "element vertex 4\n",
+            "element vertex 18446744073709551615\n",
+        );
+        assert!(matches!(
+            Mesh::load_ply_ascii(ply.as_bytes()),
+            Err(Error::ParseFailed(19, _))
+        ));
+    }
+
+    #[test]
+    fn t_load_ply_complex_edge() {
+        let ply = TETRAHEDRON.replace("3 0 1 3", "3 0 2 3");
+        match Mesh::load_ply_ascii(ply.as_bytes()) {
+            Err(Error::ParseFailed(line, reason)) => {
+                assert_eq!(line, 17);
+                assert!(reason.contains("face 1"));
+            }
+            _ => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn t_write_ply_face_properties() {
+        let mut mesh = quad_box();
+        let mut red = mesh.create_face_prop::<u8>();
+        let mut green = mesh.create_face_prop::<u8>();
+        let mut blue = mesh.create_face_prop::<u8>();
+        let mut material = mesh.create_face_prop::<u32>();
+        for f in 0..(mesh.num_faces() as u32) {
+            red.set(f, 40 * f as u8).unwrap();
+            green.set(f, 255).unwrap();
+            blue.set(f, 0).unwrap();
+            material.set(f, 10 + f).unwrap();
+        }
+        let mut buf = Vec::new();
+        mesh.write_ply_ascii(
+            &mut buf,
+            &[
+                ("red", &red),
+                ("green", &green),
+                ("blue", &blue),
+                ("material", &material),
+            ],
+        )
+        .expect("Unable to write");
+        let mut lines = content_lines(buf.as_slice());
+        let elements = parse_header(&mut lines).unwrap();
+        assert_eq!(elements.len(), 2);
+        let face = &elements[1];
+        assert_eq!(face.name, "face");
+        assert_eq!(face.count, 6);
+        assert!(matches!(&face.properties[0], PlyProperty::List(n) if n == "vertex_indices"));
+        let names: Vec<_> = face.properties[1..].iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["red", "green", "blue", "material"]);
+        let text = String::from_utf8(buf.clone()).unwrap();
+        assert!(text.contains("property list int int vertex_indices\n"));
+        assert!(text.contains("property uchar red\n"));
+        assert!(text.contains("property uint material\n"));
+        let flines: Vec<_> = lines.skip(8).map(|l| l.unwrap().1).collect();
+        assert_eq!(flines.len(), 6);
+        for (f, line) in flines.iter().enumerate() {
+            let values = face.split(line, 0).unwrap();
+            let f = f as u32;
+            assert_eq!(values[1], [(40 * f).to_string()]);
+            assert_eq!(values[2], ["255"]);
+            assert_eq!(values[3], ["0"]);
+            assert_eq!(values[4], [(10 + f).to_string()]);
+        }
+        // The extra properties are skipped when loading.
+        let copy = Mesh::load_ply_ascii(buf.as_slice()).expect("Unable to load");
+        copy.validate().unwrap();
+        assert_eq!(copy.num_faces(), 6);
+        for f in 0..(mesh.num_faces() as u32) {
+            assert!(copy.fv_ccw_iter_u32(f).eq(mesh.fv_ccw_iter_u32(f)));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use super::{content_lines, parse_token};
+use crate::mesh::{Error, Mesh};
+use std::io::BufRead;
+
+impl Mesh {
+    /// Load a mesh from an ascii STL file. STL stores every triangle with its
+    /// own copy of its vertices, so vertices within `tolerance` of each other
+    /// are welded to recover the shared topology. The facet normals in the
+    /// file are ignored in favor of the winding of the vertices.
+    pub fn load_stl_ascii<R: BufRead>(reader: R, tolerance: f32) -> Result<Mesh, Error> {
+        let mut lines = content_lines(reader);
+        let mut last_line = 0usize;
+        let mut next_line = |what: &str| -> Result<(usize, String), Error> {
+            match lines.next() {
+                Some(Ok((lnum, line))) => {
+                    last_line = lnum;
+                    Ok((lnum, line))
+                }
+                Some(Err(err)) => Err(err),
+                None => Err(Error::ParseFailed(
+                    last_line,
+                    format!("unexpected end of file, expected {what}"),
+                )),
+            }
+        };
+        // Check the line starts with the keyword, and return the rest of it.
+        fn expect<'a>(line: &'a str, lnum: usize, keyword: &str) -> Result<&'a str, Error> {
+            let mut tokens = line.splitn(2, char::is_whitespace);
+            match tokens.next() {
+                Some(first) if first == keyword => Ok(tokens.next().unwrap_or("")),
+                _ => Err(Error::ParseFailed(
+                    lnum,
+                    format!("expected '{keyword}', found '{line}'"),
+                )),
+            }
+        }
+        let (lnum, header) = next_line("the solid header")?;
+        expect(&header, lnum, "solid")?;
+        let mut mesh = Mesh::new();
+        loop {
+            let (lnum, line) = next_line("a facet or 'endsolid'")?;
+            if expect(&line, lnum, "endsolid").is_ok() {
+                break;
+            }
+            expect(&line, lnum, "facet")?;
+            let (lnum, line) = next_line("'outer loop'")?;
+            if expect(&line, lnum, "outer")?.trim() != "loop" {
+                return Err(Error::ParseFailed(
+                    lnum,
+                    format!("expected 'outer loop', found '{line}'"),
+                ));
+            }
+            let mut verts = [0u32; 3];
+            for v in verts.iter_mut() {
+                let (lnum, line) = next_line("a vertex")?;
+                let mut tokens = expect(&line, lnum, "vertex")?.split_whitespace();
+                let x = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+                let y = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+                let z = parse_token(tokens.next(), lnum, "a vertex coordinate")?;
+                *v = mesh.add_vertex_u32(glam::vec3(x, y, z))?;
+            }
+            let (lnum, line) = next_line("'endloop'")?;
+            expect(&line, lnum, "endloop")?;
+            let (lnum, line) = next_line("'endfacet'")?;
+            expect(&line, lnum, "endfacet")?;
+            mesh.add_tri_face_u32(verts[0], verts[1], verts[2])?;
+        }
+        mesh.weld_vertices(tolerance)?;
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{Error, Mesh};
+
+    const TWO_TRIANGLES: &str = "solid square
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 1 0.000001
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid square
+";
+
+    #[test]
+    fn t_load_stl_ascii() {
+        let mesh = Mesh::load_stl_ascii(TWO_TRIANGLES.as_bytes(), 1e-4).expect("Unable to load");
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_edges(), 5);
+        assert_eq!(mesh.num_faces(), 2);
+        let interior: Vec<u32> = (0..(mesh.num_edges() as u32))
+            .filter(|e| {
+                !mesh.is_boundary_halfedge_u32(e << 1)
+                    && !mesh.is_boundary_halfedge_u32((e << 1) | 1)
+            })
+            .collect();
+        assert_eq!(interior.len(), 1);
+        let h = interior[0] << 1;
+        let mut ends = [mesh.from_vertex_u32(h), mesh.to_vertex_u32(h)];
+        ends.sort();
+        assert_eq!(ends, [0, 2]);
+    }
+
+    #[test]
+    fn t_load_stl_ascii_without_welding() {
+        // Without welding, the triangles are disconnected.
+        let mesh = Mesh::load_stl_ascii(TWO_TRIANGLES.as_bytes(), 0.0).expect("Unable to load");
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 6);
+        assert_eq!(mesh.num_edges(), 6);
+        assert_eq!(mesh.num_connected_components(), 2);
+    }
+
+    #[test]
+    fn t_load_stl_ascii_malformed() {
+        let err = Mesh::load_stl_ascii(
+            "solid x\nfacet normal 0 0 1\nouter loop\nvertex 0 0\n".as_bytes(),
+            1e-4,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, Error::ParseFailed(4, _)));
+        assert!(matches!(
+            Mesh::load_stl_ascii("facet normal 0 0 1\n".as_bytes(), 1e-4),
+            Err(Error::ParseFailed(1, _))
+        ));
+        assert!(matches!(
+            Mesh::load_stl_ascii("solid x\nfacet normal 0 0 1\n".as_bytes(), 1e-4),
+            Err(Error::ParseFailed(2, _))
+        ));
+    }
+}
@@ -0,0 +1,574 @@
+use crate::mesh::{HalfedgeHandle, Mesh};
+
+/// Iterator over the outgoing halfedges of a vertex. The `CCW` parameter
+/// decides whether the halfedges are visited in counter-clockwise or clockwise
+/// order.
+pub struct OutgoingHalfedgeIter<'a, const CCW: bool> {
+    mesh: &'a Mesh,
+    hstart: Option<u32>,
+    hcurrent: Option<u32>,
+}
+
+impl<'a, const CCW: bool> OutgoingHalfedgeIter<'a, CCW> {
+    fn new(mesh: &'a Mesh, v: u32) -> Self {
+        let h = mesh.vertex_halfedge_u32(v);
+        OutgoingHalfedgeIter {
+            mesh,
+            hstart: h,
+            hcurrent: h,
+        }
+    }
+}
+
+impl<const CCW: bool> Iterator for OutgoingHalfedgeIter<'_, CCW> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.hcurrent {
+            Some(current) => {
+                let next = if CCW {
+                    self.mesh.ccw_rotated_halfedge_u32(current)
+                } else {
+                    self.mesh.cw_rotated_halfedge_u32(current)
+                };
+                self.hcurrent = if Some(next) == self.hstart {
+                    None
+                } else {
+                    Some(next)
+                };
+                Some(current)
+            }
+            None => None,
+        }
+    }
+}
+
+/// Circulator over the outgoing halfedges of a vertex, starting at a given
+/// halfedge. Unlike the other iterators, it can look at the next halfedge
+/// without advancing, and can be reset to start over. The `CCW` parameter
+/// decides whether the halfedges are visited in counter-clockwise or clockwise
+/// order.
+pub struct HalfedgeCirculator<'a, const CCW: bool> {
+    mesh: &'a Mesh,
+    hstart: u32,
+    hcurrent: Option<u32>,
+}
+
+impl<'a, const CCW: bool> HalfedgeCirculator<'a, CCW> {
+    /// Circulate around the vertex that `h` starts from, beginning with `h`.
+    pub fn new(mesh: &'a Mesh, h: HalfedgeHandle) -> Self {
+        HalfedgeCirculator {
+            mesh,
+            hstart: h.0,
+            hcurrent: Some(h.0),
+        }
+    }
+
+    /// The halfedge that will be returned by the next call to `next`, without
+    /// advancing the circulator.
+    pub fn peek(&self) -> Option<HalfedgeHandle> {
+        self.hcurrent.map(HalfedgeHandle)
+    }
+
+    /// Go back to the halfedge the circulator started at.
+    pub fn reset(&mut self) {
+        self.hcurrent = Some(self.hstart);
+    }
+}
+
+impl<const CCW: bool> Iterator for HalfedgeCirculator<'_, CCW> {
+    type Item = HalfedgeHandle;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.hcurrent?;
+        let next = if CCW {
+            self.mesh.ccw_rotated_halfedge_u32(current)
+        } else {
+            self.mesh.cw_rotated_halfedge_u32(current)
+        };
+        self.hcurrent = if next == self.hstart {
+            None
+        } else {
+            Some(next)
+        };
+        Some(HalfedgeHandle(current))
+    }
+}
+
+pub(crate) fn voh_ccw_iter(mesh: &Mesh, v: u32) -> OutgoingHalfedgeIter<'_, true> {
+    OutgoingHalfedgeIter::new(mesh, v)
+}
+
+pub(crate) fn voh_cw_iter(mesh: &Mesh, v: u32) -> OutgoingHalfedgeIter<'_, false> {
+    OutgoingHalfedgeIter::new(mesh, v)
+}
+
+pub(crate) fn vih_ccw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_ccw_iter(mesh, v).map(|h| mesh.opposite_halfedge_u32(h))
+}
+
+pub(crate) fn vih_cw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_cw_iter(mesh, v).map(|h| mesh.opposite_halfedge_u32(h))
+}
+
+pub(crate) fn vv_ccw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_ccw_iter(mesh, v).map(|h| mesh.to_vertex_u32(h))
+}
+
+pub(crate) fn vv_cw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_cw_iter(mesh, v).map(|h| mesh.to_vertex_u32(h))
+}
+
+pub(crate) fn vf_ccw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_ccw_iter(mesh, v).filter_map(|h| mesh.halfedge_face_u32(h))
+}
+
+pub(crate) fn vf_cw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_cw_iter(mesh, v).filter_map(|h| mesh.halfedge_face_u32(h))
+}
+
+pub(crate) fn ve_ccw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_ccw_iter(mesh, v).map(|h| h >> 1)
+}
+
+pub(crate) fn ve_cw_iter(mesh: &Mesh, v: u32) -> impl Iterator<Item = u32> + '_ {
+    voh_cw_iter(mesh, v).map(|h| h >> 1)
+}
+
+/// Iterator over the halfedges of a face. The `CCW` parameter decides whether
+/// the halfedges are visited in counter-clockwise or clockwise order.
+pub struct FaceHalfedgeIter<'a, const CCW: bool> {
+    mesh: &'a Mesh,
+    // The halfedges that are yet to be visited, from the front and the back,
+    // both inclusive.
+    range: Option<(u32, u32)>,
+}
+
+impl<'a, const CCW: bool> FaceHalfedgeIter<'a, CCW> {
+    fn new(mesh: &'a Mesh, f: u32) -> Self {
+        Self::from_halfedge(mesh, mesh.face_halfedge_u32(f))
+    }
+
+    /// Walk the loop of halfedges starting at `h`. The halfedge doesn't need
+    /// to belong to a face, so this also works for boundary loops.
+    fn from_halfedge(mesh: &'a Mesh, h: u32) -> Self {
+        let last = if CCW {
+            mesh.prev_halfedge_u32(h)
+        } else {
+            mesh.next_halfedge_u32(h)
+        };
+        FaceHalfedgeIter {
+            mesh,
+            range: Some((h, last)),
+        }
+    }
+}
+
+impl<const CCW: bool> Iterator for FaceHalfedgeIter<'_, CCW> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (front, back) = self.range?;
+        self.range = if front == back {
+            None
+        } else if CCW {
+            Some((self.mesh.next_halfedge_u32(front), back))
+        } else {
+            Some((self.mesh.prev_halfedge_u32(front), back))
+        };
+        Some(front)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The degree of the face is not known without walking the loop.
+        match self.range {
+            Some(_) => (1, None),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl<const CCW: bool> DoubleEndedIterator for FaceHalfedgeIter<'_, CCW> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (front, back) = self.range?;
+        self.range = if front == back {
+            None
+        } else if CCW {
+            Some((front, self.mesh.prev_halfedge_u32(back)))
+        } else {
+            Some((front, self.mesh.next_halfedge_u32(back)))
+        };
+        Some(back)
+    }
+}
+
+pub(crate) fn fh_ccw_iter(mesh: &Mesh, f: u32) -> FaceHalfedgeIter<'_, true> {
+    FaceHalfedgeIter::new(mesh, f)
+}
+
+pub(crate) fn fh_cw_iter(mesh: &Mesh, f: u32) -> FaceHalfedgeIter<'_, false> {
+    FaceHalfedgeIter::new(mesh, f)
+}
+
+pub(crate) fn fv_ccw_iter(mesh: &Mesh, f: u32) -> impl Iterator<Item = u32> + '_ {
+    fh_ccw_iter(mesh, f).map(|h| mesh.to_vertex_u32(h))
+}
+
+pub(crate) fn fv_cw_iter(mesh: &Mesh, f: u32) -> impl Iterator<Item = u32> + '_ {
+    fh_cw_iter(mesh, f).map(|h| mesh.to_vertex_u32(h))
+}
+
+pub(crate) fn ff_ccw_iter(mesh: &Mesh, f: u32) -> impl Iterator<Item = u32> + '_ {
+    fh_ccw_iter(mesh, f).filter_map(|h| mesh.halfedge_face_u32(mesh.opposite_halfedge_u32(h)))
+}
+
+pub(crate) fn ff_cw_iter(mesh: &Mesh, f: u32) -> impl Iterator<Item = u32> + '_ {
+    fh_cw_iter(mesh, f).filter_map(|h| mesh.halfedge_face_u32(mesh.opposite_halfedge_u32(h)))
+}
+
+pub(crate) fn boundary_loop_iter(mesh: &Mesh, h: u32) -> FaceHalfedgeIter<'_, true> {
+    FaceHalfedgeIter::from_halfedge(mesh, h)
+}
+
+pub(crate) fn fe_ccw_iter(mesh: &Mesh, f: u32) -> impl Iterator<Item = u32> + '_ {
+    fh_ccw_iter(mesh, f).map(|h| h >> 1)
+}
+
+pub(crate) fn fe_cw_iter(mesh: &Mesh, f: u32) -> impl Iterator<Item = u32> + '_ {
+    fh_cw_iter(mesh, f).map(|h| h >> 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::HalfedgeCirculator;
+    use crate::mesh::{
+        test::{quad_box, quad_grid},
+        Mesh,
+    };
+
+    #[test]
+    fn t_box_voh_ccw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let hs: Vec<_> = mesh.voh_ccw_iter_u32(v).collect();
+            assert_eq!(hs.len(), 3);
+            for h in hs {
+                assert_eq!(mesh.to_vertex_u32(mesh.opposite_halfedge_u32(h)), v);
+            }
+        }
+    }
+
+    #[test]
+    fn t_box_voh_cw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let mut ccw: Vec<_> = mesh.voh_ccw_iter_u32(v).collect();
+            let cw: Vec<_> = mesh.voh_cw_iter_u32(v).collect();
+            // Same start, opposite direction.
+            ccw[1..].reverse();
+            assert_eq!(ccw, cw);
+        }
+    }
+
+    #[test]
+    fn t_box_halfedge_circulator() {
+        let mesh = quad_box();
+        for v in mesh.vertices() {
+            let h = mesh.vertex_halfedge(v).unwrap();
+            let ccw: Vec<_> = mesh.voh_ccw_iter(v).collect();
+            let mut circ = HalfedgeCirculator::<true>::new(&mesh, h);
+            for expected in &ccw {
+                assert_eq!(circ.peek(), Some(*expected));
+                assert_eq!(circ.peek(), Some(*expected));
+                assert_eq!(circ.next(), Some(*expected));
+            }
+            assert_eq!(circ.peek(), None);
+            assert_eq!(circ.next(), None);
+            circ.reset();
+            assert_eq!(circ.peek(), Some(h));
+            assert_eq!(circ.by_ref().collect::<Vec<_>>(), ccw);
+            // Clockwise, starting from a different halfedge.
+            let cw: Vec<_> = mesh.voh_cw_iter(v).collect();
+            let mut circ = HalfedgeCirculator::<false>::new(&mesh, cw[1]);
+            assert_eq!(circ.next(), Some(cw[1]));
+            circ.reset();
+            let visited: Vec<_> = circ.collect();
+            assert_eq!(visited, vec![cw[1], cw[2], cw[0]]);
+        }
+    }
+
+    #[test]
+    fn t_box_vih_ccw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let hs: Vec<_> = mesh.vih_ccw_iter_u32(v).collect();
+            assert_eq!(hs.len(), 3);
+            for h in hs {
+                assert_eq!(mesh.to_vertex_u32(h), v);
+            }
+        }
+    }
+
+    #[test]
+    fn t_box_vih_cw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let hs: Vec<_> = mesh.vih_cw_iter_u32(v).collect();
+            assert_eq!(hs.len(), 3);
+            for h in hs {
+                assert_eq!(mesh.to_vertex_u32(h), v);
+            }
+        }
+    }
+
+    #[test]
+    fn t_box_ve_ccw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let mut es: Vec<_> = mesh.ve_ccw_iter_u32(v).collect();
+            assert_eq!(es.len(), 3);
+            es.sort();
+            es.dedup();
+            assert_eq!(es.len(), 3);
+        }
+    }
+
+    #[test]
+    fn t_box_ve_cw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let mut es: Vec<_> = mesh.ve_cw_iter_u32(v).collect();
+            assert_eq!(es.len(), 3);
+            es.sort();
+            es.dedup();
+            assert_eq!(es.len(), 3);
+        }
+    }
+
+    #[test]
+    fn t_box_fh_ccw_iter() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let hs: Vec<_> = mesh.fh_ccw_iter_u32(f).collect();
+            assert_eq!(hs.len(), 4);
+            for (i, h) in hs.iter().enumerate() {
+                assert_eq!(mesh.next_halfedge_u32(*h), hs[(i + 1) % hs.len()]);
+            }
+        }
+    }
+
+    #[test]
+    fn t_box_fh_cw_iter() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let hs: Vec<_> = mesh.fh_cw_iter_u32(f).collect();
+            assert_eq!(hs.len(), 4);
+            for (i, h) in hs.iter().enumerate() {
+                assert_eq!(mesh.prev_halfedge_u32(*h), hs[(i + 1) % hs.len()]);
+            }
+        }
+    }
+
+    #[test]
+    fn t_box_fe_ccw_iter() {
+        let mesh = quad_box();
+        let nfaces = mesh.num_faces() as u32;
+        let edges: Vec<Vec<u32>> = (0..nfaces)
+            .map(|f| {
+                let mut es: Vec<_> = mesh.fe_ccw_iter_u32(f).collect();
+                es.sort();
+                es.dedup();
+                es
+            })
+            .collect();
+        for es in &edges {
+            assert_eq!(es.len(), 4);
+        }
+        for f in 0..nfaces {
+            // Adjacent faces share exactly one edge.
+            for h in mesh.fh_ccw_iter_u32(f) {
+                let g = mesh
+                    .halfedge_face_u32(mesh.opposite_halfedge_u32(h))
+                    .unwrap();
+                let nshared = edges[f as usize]
+                    .iter()
+                    .filter(|e| edges[g as usize].contains(e))
+                    .count();
+                assert_eq!(nshared, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn t_box_fe_cw_iter() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let mut ccw: Vec<_> = mesh.fe_ccw_iter_u32(f).collect();
+            let cw: Vec<_> = mesh.fe_cw_iter_u32(f).collect();
+            ccw[1..].reverse();
+            assert_eq!(ccw, cw);
+        }
+    }
+
+    #[test]
+    fn t_grid_boundary_loop_iter() {
+        let mesh = quad_grid(3, 4);
+        let h = (0..(mesh.num_halfedges() as u32))
+            .find(|h| mesh.is_boundary_halfedge_u32(*h))
+            .unwrap();
+        let hs: Vec<_> = mesh.boundary_loop_iter_u32(h).collect();
+        assert_eq!(hs.len(), 2 * (3 + 4));
+        for (i, h) in hs.iter().enumerate() {
+            assert!(mesh.is_boundary_halfedge_u32(*h));
+            assert_eq!(mesh.next_halfedge_u32(*h), hs[(i + 1) % hs.len()]);
+        }
+    }
+
+    #[test]
+    fn t_triangle_boundary_loop_iter() {
+        let mut mesh = Mesh::new();
+        for pos in [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+        ] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        let h = mesh.opposite_halfedge_u32(mesh.face_halfedge_u32(0));
+        let hs: Vec<_> = mesh.boundary_loop_iter_u32(h).collect();
+        assert_eq!(hs.len(), 3);
+        assert!(hs.iter().all(|h| mesh.is_boundary_halfedge_u32(*h)));
+    }
+
+    #[test]
+    fn t_box_fh_rev_iter() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let mut fwd: Vec<_> = mesh.fh_ccw_iter_u32(f).collect();
+            let rev: Vec<_> = mesh.fh_ccw_iter_u32(f).rev().collect();
+            fwd.reverse();
+            assert_eq!(fwd, rev);
+            let mut fwd: Vec<_> = mesh.fh_cw_iter_u32(f).collect();
+            let rev: Vec<_> = mesh.fh_cw_iter_u32(f).rev().collect();
+            fwd.reverse();
+            assert_eq!(fwd, rev);
+            // Reversing the ccw iterator visits the same halfedges as the cw
+            // iterator, except the cw iterator starts one step earlier.
+            let mut cw: Vec<_> = mesh.fh_cw_iter_u32(f).collect();
+            cw.rotate_left(1);
+            let rev: Vec<_> = mesh.fh_ccw_iter_u32(f).rev().collect();
+            assert_eq!(cw, rev);
+        }
+    }
+
+    #[test]
+    fn t_box_fh_iter_both_ends() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let hs: Vec<_> = mesh.fh_ccw_iter_u32(f).collect();
+            let mut iter = mesh.fh_ccw_iter_u32(f);
+            assert_eq!(iter.next(), Some(hs[0]));
+            assert_eq!(iter.next_back(), Some(hs[3]));
+            assert_eq!(iter.next(), Some(hs[1]));
+            assert_eq!(iter.next_back(), Some(hs[2]));
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+    }
+
+    #[test]
+    fn t_box_fv_ccw_iter() {
+        let mesh = quad_box();
+        let fvs: Vec<Vec<u32>> = (0..(mesh.num_faces() as u32))
+            .map(|f| mesh.fv_ccw_iter_u32(f).collect())
+            .collect();
+        assert_eq!(
+            fvs,
+            vec![
+                vec![0, 3, 2, 1],
+                vec![0, 1, 5, 4],
+                vec![1, 2, 6, 5],
+                vec![2, 3, 7, 6],
+                vec![3, 0, 4, 7],
+                vec![4, 5, 6, 7],
+            ]
+        );
+    }
+
+    #[test]
+    fn t_box_fv_cw_iter() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let mut ccw: Vec<_> = mesh.fv_ccw_iter_u32(f).collect();
+            let cw: Vec<_> = mesh.fv_cw_iter_u32(f).collect();
+            ccw[1..].reverse();
+            assert_eq!(ccw, cw);
+        }
+    }
+
+    #[test]
+    fn t_box_ff_ccw_iter() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let mut fs: Vec<_> = mesh.ff_ccw_iter_u32(f).collect();
+            assert_eq!(fs.len(), 4);
+            assert!(!fs.contains(&f));
+            fs.sort();
+            fs.dedup();
+            assert_eq!(fs.len(), 4);
+        }
+    }
+
+    #[test]
+    fn t_grid_ff_cw_iter() {
+        let mesh = quad_grid(3, 3);
+        // Corner, edge and center faces.
+        assert_eq!(mesh.ff_cw_iter_u32(0).count(), 2);
+        assert_eq!(mesh.ff_cw_iter_u32(1).count(), 3);
+        assert_eq!(mesh.ff_cw_iter_u32(4).count(), 4);
+    }
+
+    #[test]
+    fn t_box_vf_ccw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let fs: Vec<_> = mesh.vf_ccw_iter_u32(v).collect();
+            assert_eq!(fs.len(), 3);
+            for f in fs {
+                assert!(mesh.fv_ccw_iter_u32(f).any(|fv| fv == v));
+            }
+        }
+    }
+
+    #[test]
+    fn t_grid_vf_cw_iter() {
+        let mesh = quad_grid(2, 2);
+        // Corner, edge and center vertices.
+        assert_eq!(mesh.vf_cw_iter_u32(0).count(), 1);
+        assert_eq!(mesh.vf_cw_iter_u32(1).count(), 2);
+        assert_eq!(mesh.vf_cw_iter_u32(4).count(), 4);
+    }
+
+    #[test]
+    fn t_box_vv_ccw_iter() {
+        let mesh = quad_box();
+        let mut nbs: Vec<_> = mesh.vv_ccw_iter_u32(0).collect();
+        nbs.sort();
+        assert_eq!(nbs, vec![1, 3, 4]);
+        let mut nbs: Vec<_> = mesh.vv_ccw_iter_u32(6).collect();
+        nbs.sort();
+        assert_eq!(nbs, vec![2, 5, 7]);
+    }
+
+    #[test]
+    fn t_box_vv_cw_iter() {
+        let mesh = quad_box();
+        for v in 0..(mesh.num_vertices() as u32) {
+            let mut ccw: Vec<_> = mesh.vv_ccw_iter_u32(v).collect();
+            let cw: Vec<_> = mesh.vv_cw_iter_u32(v).collect();
+            ccw[1..].reverse();
+            assert_eq!(ccw, cw);
+        }
+    }
+}
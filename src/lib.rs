@@ -1,4 +1,9 @@
+pub mod accel;
+pub mod io;
+pub mod iterator;
 pub mod mesh;
+#[cfg(feature = "serde")]
+mod serialize;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
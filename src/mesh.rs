@@ -1,13 +1,146 @@
-use std::sync::{Arc, RwLock, Weak};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, RwLock, Weak},
+};
 
-enum Error {
+use crate::{accel::SpatialHash, iterator};
+
+mod curvature;
+mod decimate;
+mod delaunay;
+mod edit;
+mod flat;
+mod handle;
+mod quality;
+mod remesh;
+mod status;
+mod subdivide;
+
+pub use edit::GcRemap;
+pub use flat::FlatMesh;
+pub use handle::{EdgeHandle, FaceHandle, HalfedgeHandle, VertexHandle};
+pub use quality::QualityReport;
+pub use status::Status;
+
+#[derive(Debug)]
+pub enum Error {
     ReadPropertyFailed,
     WriteToPropertyFailed,
     PropertyDoesNotExist,
+    ComplexVertex(u32),
+    ComplexEdge(u32),
+    PatchRelinkingFailed,
+    InvalidOppositeHalfedge(u32),
+    InvalidHalfedgeLink(u32),
+    DisconnectedHalfedges(u32),
+    InvalidFaceLoop(u32),
+    InvalidVertexHalfedge(u32),
+    DegenerateFace,
+    OutOfBoundsAccess,
+    NotBoundaryHalfedge(u32),
+    BoundaryEdge(u32),
+    NotBoundaryEdge(u32),
+    /// The expected and the actual number of values.
+    LengthMismatch(usize, usize),
+    NotATriangle(u32),
+    Io(std::io::Error),
+    /// Parsing failed at the given (1-based) line number.
+    ParseFailed(usize, String),
+    /// Adding the element would overflow the `u32` handles.
+    CapacityExceeded,
+    /// The given order is not a permutation of the elements.
+    InvalidPermutation,
+    /// The properties of two meshes don't have the same types, in the same
+    /// order.
+    PropertyMismatch,
+    /// A length that must be positive and finite, but isn't.
+    InvalidLength(f32),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ReadPropertyFailed => write!(f, "unable to read from a property"),
+            Error::WriteToPropertyFailed => write!(f, "unable to write to a property"),
+            Error::PropertyDoesNotExist => write!(f, "the property does not exist"),
+            Error::ComplexVertex(v) => write!(f, "vertex {v} would become non-manifold"),
+            Error::ComplexEdge(e) => write!(f, "edge {e} would become non-manifold"),
+            Error::PatchRelinkingFailed => {
+                write!(f, "unable to relink the patches around the new face")
+            }
+            Error::InvalidOppositeHalfedge(h) => {
+                write!(f, "halfedge {h} is not the opposite of its opposite")
+            }
+            Error::InvalidHalfedgeLink(h) => {
+                write!(
+                    f,
+                    "next and previous links of halfedge {h} are inconsistent"
+                )
+            }
+            Error::DisconnectedHalfedges(h) => write!(
+                f,
+                "halfedge {h} does not end at the vertex where its next halfedge starts"
+            ),
+            Error::InvalidFaceLoop(fi) => {
+                write!(f, "the halfedges of face {fi} do not form a closed loop")
+            }
+            Error::InvalidVertexHalfedge(v) => {
+                write!(
+                    f,
+                    "the outgoing halfedge of vertex {v} does not start at it"
+                )
+            }
+            Error::DegenerateFace => write!(
+                f,
+                "a face needs at least three vertices, without repetition"
+            ),
+            Error::OutOfBoundsAccess => write!(f, "the element handle is out of bounds"),
+            Error::NotBoundaryHalfedge(h) => write!(f, "halfedge {h} is not on the boundary"),
+            Error::BoundaryEdge(e) => write!(f, "edge {e} is on the boundary"),
+            Error::NotBoundaryEdge(e) => write!(f, "edge {e} is not on the boundary"),
+            Error::LengthMismatch(expected, found) => {
+                write!(f, "expected {expected} values, found {found}")
+            }
+            Error::NotATriangle(fi) => write!(f, "face {fi} is not a triangle"),
+            Error::Io(err) => write!(f, "io error: {err}"),
+            Error::ParseFailed(line, reason) => write!(f, "line {line}: {reason}"),
+            Error::CapacityExceeded => {
+                write!(f, "the mesh can't hold any more elements of this kind")
+            }
+            Error::InvalidPermutation => {
+                write!(f, "the order does not contain every element exactly once")
+            }
+            Error::PropertyMismatch => {
+                write!(f, "the properties of the two meshes do not match")
+            }
+            Error::InvalidLength(len) => {
+                write!(
+                    f,
+                    "{len} is not a valid length, it must be positive and finite"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
 }
 
 struct Vertex {
     halfedge: Option<u32>,
+    deleted: bool,
 }
 
 struct Halfedge {
@@ -19,10 +152,33 @@ struct Halfedge {
 
 struct Edge {
     halfedges: [Halfedge; 2],
+    deleted: bool,
 }
 
 struct Face {
     halfedge: u32,
+    deleted: bool,
+}
+
+/// Scratch space reused across calls to `add_face`, to avoid allocating every
+/// time a face is added.
+#[derive(Default)]
+struct TopolCache {
+    /// Indices of the vertices of the face, when they are given as handles.
+    vertices: Vec<u32>,
+    halfedges: Vec<u32>,
+    is_new: Vec<bool>,
+    needs_adjust: Vec<bool>,
+    next_cache: Vec<(u32, u32)>,
+}
+
+impl TopolCache {
+    fn clear(&mut self) {
+        self.halfedges.clear();
+        self.is_new.clear();
+        self.needs_adjust.clear();
+        self.next_cache.clear();
+    }
 }
 
 pub struct Mesh {
@@ -30,7 +186,25 @@ pub struct Mesh {
     edges: Vec<Edge>,
     faces: Vec<Face>,
     points: Property<glam::Vec3>,
+    vnormals: Option<Property<glam::Vec3>>,
+    vcolors: Option<Property<glam::Vec3>>,
+    huvs: Option<Property<glam::Vec2>>,
+    hnormals: Option<Property<glam::Vec3>>,
+    creases: Option<Property<f32>>,
+    vstatus: Option<Property<Status>>,
+    estatus: Option<Property<Status>>,
+    fstatus: Option<Property<Status>>,
     vprops: PropertyContainer,
+    hprops: PropertyContainer,
+    eprops: PropertyContainer,
+    fprops: PropertyContainer,
+    cache: TopolCache,
+}
+
+impl Default for Mesh {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mesh {
@@ -43,7 +217,19 @@ impl Mesh {
             edges: Vec::new(),
             faces: Vec::new(),
             points,
+            vnormals: None,
+            vcolors: None,
+            huvs: None,
+            hnormals: None,
+            creases: None,
+            vstatus: None,
+            estatus: None,
+            fstatus: None,
             vprops,
+            hprops: PropertyContainer::new(),
+            eprops: PropertyContainer::new(),
+            fprops: PropertyContainer::new(),
+            cache: TopolCache::default(),
         }
     }
 
@@ -56,260 +242,5181 @@ impl Mesh {
             edges: Vec::with_capacity(nedges),
             faces: Vec::with_capacity(nfaces),
             points,
+            vnormals: None,
+            vcolors: None,
+            huvs: None,
+            hnormals: None,
+            creases: None,
+            vstatus: None,
+            estatus: None,
+            fstatus: None,
             vprops,
+            hprops: PropertyContainer::new(),
+            eprops: PropertyContainer::new(),
+            fprops: PropertyContainer::new(),
+            cache: TopolCache::default(),
         }
     }
 
-    pub fn halfedge(&self, h: u32) -> &Halfedge {
-        &self.edges[(h << 1) as usize].halfedges[(h & 1) as usize]
+    /// Reserve memory for at least the given number of additional vertices,
+    /// edges and faces, along with their properties.
+    pub fn reserve(&mut self, nverts: usize, nedges: usize, nfaces: usize) -> Result<(), Error> {
+        self.vertices.reserve(nverts);
+        self.edges.reserve(nedges);
+        self.faces.reserve(nfaces);
+        self.vprops.reserve(nverts)?;
+        self.hprops.reserve(nedges * 2)?;
+        self.eprops.reserve(nedges)?;
+        self.fprops.reserve(nfaces)?;
+        Ok(())
     }
 
-    pub fn is_boundary_halfedge(&self, h: u32) -> bool {
-        self.halfedge(h).face.is_none()
+    /// Remove all vertices, edges and faces, and the values of all their
+    /// properties, while keeping the allocated memory. Existing property
+    /// handles, including the points, remain valid and usable.
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.vertices.clear();
+        self.edges.clear();
+        self.faces.clear();
+        self.vprops.clear()?;
+        self.hprops.clear()?;
+        self.eprops.clear()?;
+        self.fprops.clear()?;
+        Ok(())
     }
 
-    pub fn is_boundary_vertex(&self, v: u32) -> bool {
-        match self.vertices[v as usize].halfedge {
-            Some(h) => self.is_boundary_halfedge(h),
-            None => true,
-        }
+    /// Create a new vertex property, with default values for the existing
+    /// vertices.
+    pub fn create_vertex_prop<T: TPropData>(&mut self) -> Property<T> {
+        self.create_vertex_prop_with_default(T::default())
     }
 
-    pub const fn opposite_halfedge(&self, h: u32) -> u32 {
-        h ^ 1
+    /// Same as `create_vertex_prop`, but the existing vertices, and the ones
+    /// added later, start with `default` instead of `T::default()`.
+    pub fn create_vertex_prop_with_default<T: TPropData>(&mut self, default: T) -> Property<T> {
+        let prop = Property::<T>::with_default(self.num_vertices(), default);
+        self.vprops.push_property(prop.generic_ref());
+        prop
     }
 
-    pub fn cw_rotated_halfedge(&self, h: u32) -> u32 {
-        self.halfedge(self.opposite_halfedge(h)).next
+    /// Create a new halfedge property, with default values for the existing
+    /// halfedges.
+    pub fn create_halfedge_prop<T: TPropData>(&mut self) -> Property<T> {
+        self.create_halfedge_prop_with_default(T::default())
     }
 
-    pub fn ccw_rotated_halfedge(&self, h: u32) -> u32 {
-        self.opposite_halfedge(self.halfedge(h).prev)
+    /// Same as `create_halfedge_prop`, but the existing halfedges, and the ones
+    /// added later, start with `default` instead of `T::default()`.
+    pub fn create_halfedge_prop_with_default<T: TPropData>(&mut self, default: T) -> Property<T> {
+        let prop = Property::<T>::with_default(self.num_halfedges(), default);
+        self.hprops.push_property(prop.generic_ref());
+        prop
     }
 
-    pub fn add_vertex(&mut self, pos: glam::Vec3) -> Result<u32, Error> {
-        let vi = self.vertices.len() as u32;
-        self.vprops.push_value()?;
-        self.points.set(vi, pos)?;
-        return Ok(vi);
+    /// Create a new edge property, with default values for the existing
+    /// edges.
+    pub fn create_edge_prop<T: TPropData>(&mut self) -> Property<T> {
+        self.create_edge_prop_with_default(T::default())
     }
 
-    pub fn add_face(&mut self, verts: &[u32]) -> u32 {
-        todo!("Not Implemented");
+    /// Same as `create_edge_prop`, but the existing edges, and the ones
+    /// added later, start with `default` instead of `T::default()`.
+    pub fn create_edge_prop_with_default<T: TPropData>(&mut self, default: T) -> Property<T> {
+        let prop = Property::<T>::with_default(self.num_edges(), default);
+        self.eprops.push_property(prop.generic_ref());
+        prop
     }
 
-    pub fn add_tri_face(&mut self, v0: u32, v1: u32, v2: u32) -> u32 {
-        self.add_face(&[v0, v1, v2])
+    /// Create a new face property, with default values for the existing
+    /// faces.
+    pub fn create_face_prop<T: TPropData>(&mut self) -> Property<T> {
+        self.create_face_prop_with_default(T::default())
     }
 
-    pub fn add_quad_face(&mut self, v0: u32, v1: u32, v2: u32, v3: u32) -> u32 {
-        self.add_face(&[v0, v1, v2, v3])
+    /// Same as `create_face_prop`, but the existing faces, and the ones
+    /// added later, start with `default` instead of `T::default()`.
+    pub fn create_face_prop_with_default<T: TPropData>(&mut self, default: T) -> Property<T> {
+        let prop = Property::<T>::with_default(self.num_faces(), default);
+        self.fprops.push_property(prop.generic_ref());
+        prop
     }
-}
 
-struct PropertyContainer {
-    props: Vec<Box<dyn GenericProperty>>,
-}
+    /// Read the value of a vertex property, after checking that the vertex
+    /// exists.
+    pub fn get_vertex_prop<T: TPropData>(
+        &self,
+        prop: &Property<T>,
+        v: VertexHandle,
+    ) -> Result<T, Error> {
+        self.check_vertex(v.0)?;
+        prop.get(v.0)
+    }
 
-impl PropertyContainer {
-    fn new() -> Self {
-        PropertyContainer { props: Vec::new() }
+    /// Write the value of a vertex property, after checking that the vertex
+    /// exists.
+    pub fn set_vertex_prop<T: TPropData>(
+        &self,
+        prop: &mut Property<T>,
+        v: VertexHandle,
+        val: T,
+    ) -> Result<(), Error> {
+        self.check_vertex(v.0)?;
+        prop.set(v.0, val)
     }
 
-    fn push_property(&mut self, prop: Box<dyn GenericProperty>) {
-        self.props.push(prop);
+    /// Blend the values of a vertex property at the corners of the triangle
+    /// `f`, using the barycentric coordinates `bary`. The coordinates are in
+    /// the order of `fv_ccw_iter`.
+    pub fn interpolate_vertex_prop<T: TInterpolate>(
+        &self,
+        f: FaceHandle,
+        bary: glam::Vec3,
+        prop: &Property<T>,
+    ) -> Result<T, Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        let data = prop.data.read().map_err(|_| Error::ReadPropertyFailed)?;
+        let mut verts = self.fv_ccw_iter_u32(f);
+        let (Some(a), Some(b), Some(c), None) =
+            (verts.next(), verts.next(), verts.next(), verts.next())
+        else {
+            return Err(Error::NotATriangle(f));
+        };
+        let value = |v: u32| {
+            data.get(v as usize)
+                .copied()
+                .ok_or(Error::OutOfBoundsAccess)
+        };
+        Ok(value(a)? * bary.x() + value(b)? * bary.y() + value(c)? * bary.z())
     }
 
-    fn reserve(&mut self, n: usize) -> Result<(), Error> {
-        for prop in self.props.iter_mut() {
-            prop.reserve(n)?;
+    /// Copy the values of all vertex properties, including the positions,
+    /// from the vertices of `other`, where `mapping[v]` is the vertex of
+    /// `other` to copy into the vertex `v` of this mesh. Vertices mapped to
+    /// `None` are left alone. The properties of the two meshes are matched in
+    /// the order they were created, so both meshes must have properties of
+    /// the same types created in the same order, e.g. when one mesh is
+    /// derived from the other. `Err(Error::PropertyMismatch)` is returned if
+    /// they don't, and nothing is copied. The built-in properties, such as
+    /// the vertex normals, colors and status, are not part of this order.
+    /// They are copied only if both meshes have them.
+    pub fn copy_vertex_properties_from(
+        &mut self,
+        other: &Mesh,
+        mapping: &[Option<VertexHandle>],
+    ) -> Result<(), Error> {
+        if mapping.len() != self.num_vertices() {
+            return Err(Error::LengthMismatch(self.num_vertices(), mapping.len()));
+        }
+        if mapping
+            .iter()
+            .flatten()
+            .any(|v| v.index() >= other.num_vertices())
+        {
+            return Err(Error::OutOfBoundsAccess);
         }
-        return Ok(());
+        let mapping: Vec<Option<u32>> = mapping.iter().map(|v| v.map(|v| v.0)).collect();
+        let mapping = mapping.as_slice();
+        let skip = self.builtin_vertex_props();
+        let src_skip = other.builtin_vertex_props();
+        self.vprops
+            .copy_rows_from(&other.vprops, mapping, &skip, &src_skip)?;
+        // The built-in properties are created lazily, so they are only copied
+        // when both meshes have them.
+        if let (Some(dst), Some(src)) = (&self.vnormals, &other.vnormals) {
+            dst.generic_ref()
+                .copy_rows_from(src.generic_ref().as_ref(), mapping)?;
+        }
+        if let (Some(dst), Some(src)) = (&self.vcolors, &other.vcolors) {
+            dst.generic_ref()
+                .copy_rows_from(src.generic_ref().as_ref(), mapping)?;
+        }
+        if let (Some(dst), Some(src)) = (&self.vstatus, &other.vstatus) {
+            dst.generic_ref()
+                .copy_rows_from(src.generic_ref().as_ref(), mapping)?;
+        }
+        Ok(())
     }
 
-    fn resize(&mut self, n: usize) -> Result<(), Error> {
-        for prop in self.props.iter_mut() {
-            prop.resize(n)?;
+    /// The lazily created built-in vertex properties, which are left out when
+    /// matching the vertex properties of two meshes by their creation order.
+    fn builtin_vertex_props(&self) -> Vec<*const ()> {
+        [
+            self.vnormals.as_ref().map(|p| p.data_ptr()),
+            self.vcolors.as_ref().map(|p| p.data_ptr()),
+            self.vstatus.as_ref().map(|p| p.data_ptr()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Copy the values of all edge properties from `src` to `dst`.
+    pub fn copy_edge_properties(&mut self, src: EdgeHandle, dst: EdgeHandle) -> Result<(), Error> {
+        self.copy_edge_properties_u32(src.0, dst.0)
+    }
+
+    /// Same as `copy_edge_properties`, with plain indices.
+    pub(crate) fn copy_edge_properties_u32(&mut self, src: u32, dst: u32) -> Result<(), Error> {
+        self.eprops.copy(src as usize, dst as usize)
+    }
+
+    /// Copy the values of all face properties from `src` to `dst`.
+    pub fn copy_face_properties(&mut self, src: FaceHandle, dst: FaceHandle) -> Result<(), Error> {
+        self.copy_face_properties_u32(src.0, dst.0)
+    }
+
+    /// Same as `copy_face_properties`, with plain indices.
+    pub(crate) fn copy_face_properties_u32(&mut self, src: u32, dst: u32) -> Result<(), Error> {
+        self.fprops.copy(src as usize, dst as usize)
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn num_halfedges(&self) -> usize {
+        self.edges.len() * 2
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Iterate over the vertices that are not deleted.
+    pub fn vertices(&self) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.vertices_u32().map(VertexHandle)
+    }
+
+    /// Iterate over the edges that are not deleted.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeHandle> + '_ {
+        self.edges_u32().map(EdgeHandle)
+    }
+
+    /// Iterate over the faces that are not deleted.
+    pub fn faces(&self) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.faces_u32().map(FaceHandle)
+    }
+
+    /// Same as `vertices`, with plain indices.
+    pub(crate) fn vertices_u32(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..(self.num_vertices() as u32)).filter(|v| !self.is_vertex_deleted_u32(*v))
+    }
+
+    /// Same as `edges`, with plain indices.
+    pub(crate) fn edges_u32(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..(self.num_edges() as u32)).filter(|e| !self.is_edge_deleted_u32(*e))
+    }
+
+    /// Same as `faces`, with plain indices.
+    pub(crate) fn faces_u32(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..(self.num_faces() as u32)).filter(|f| !self.is_face_deleted_u32(*f))
+    }
+
+    /// Index of every vertex among the vertices that are not deleted, e.g. to
+    /// write the mesh without its deleted elements, along with the number of
+    /// such vertices. Deleted vertices map to `u32::MAX`.
+    pub(crate) fn compact_vertex_indices(&self) -> (Vec<u32>, usize) {
+        let mut count = 0usize;
+        let indices = (0..(self.num_vertices() as u32))
+            .map(|v| {
+                if self.is_vertex_deleted_u32(v) {
+                    u32::MAX
+                } else {
+                    count += 1;
+                    (count - 1) as u32
+                }
+            })
+            .collect();
+        (indices, count)
+    }
+
+    /// Iterate over the vertices in parallel. Unlike `vertices`, this covers
+    /// the whole range of vertex handles, including deleted vertices, so that
+    /// the iterator stays indexed. Properties can be read from within the
+    /// closures, but each read takes a lock on the property; for hot loops,
+    /// read the positions once with `points_snapshot` instead.
+    #[cfg(feature = "rayon")]
+    pub fn par_vertices(&self) -> impl rayon::iter::IndexedParallelIterator<Item = VertexHandle> {
+        use rayon::prelude::*;
+        (0..(self.num_vertices() as u32))
+            .into_par_iter()
+            .map(VertexHandle)
+    }
+
+    /// Iterate over the faces in parallel. See `par_vertices`.
+    #[cfg(feature = "rayon")]
+    pub fn par_faces(&self) -> impl rayon::iter::IndexedParallelIterator<Item = FaceHandle> {
+        use rayon::prelude::*;
+        (0..(self.num_faces() as u32))
+            .into_par_iter()
+            .map(FaceHandle)
+    }
+
+    fn halfedge(&self, h: u32) -> &Halfedge {
+        &self.edges[(h >> 1) as usize].halfedges[(h & 1) as usize]
+    }
+
+    fn halfedge_mut(&mut self, h: u32) -> &mut Halfedge {
+        &mut self.edges[(h >> 1) as usize].halfedges[(h & 1) as usize]
+    }
+
+    pub fn vertex_halfedge(&self, v: VertexHandle) -> Option<HalfedgeHandle> {
+        self.vertex_halfedge_u32(v.0).map(HalfedgeHandle)
+    }
+
+    pub(crate) fn vertex_halfedge_u32(&self, v: u32) -> Option<u32> {
+        self.vertices[v as usize].halfedge
+    }
+
+    pub fn face_halfedge(&self, f: FaceHandle) -> HalfedgeHandle {
+        HalfedgeHandle(self.face_halfedge_u32(f.0))
+    }
+
+    pub(crate) fn face_halfedge_u32(&self, f: u32) -> u32 {
+        self.faces[f as usize].halfedge
+    }
+
+    pub fn halfedge_face(&self, h: HalfedgeHandle) -> Option<FaceHandle> {
+        self.halfedge_face_u32(h.0).map(FaceHandle)
+    }
+
+    pub(crate) fn halfedge_face_u32(&self, h: u32) -> Option<u32> {
+        self.halfedge(h).face
+    }
+
+    /// Faces on either side of the edge, in the order of its two halfedges.
+    /// Boundary edges have `None` on one side.
+    pub fn edge_faces(&self, e: EdgeHandle) -> (Option<FaceHandle>, Option<FaceHandle>) {
+        let (a, b) = self.edge_faces_u32(e.0);
+        (a.map(FaceHandle), b.map(FaceHandle))
+    }
+
+    /// Same as `edge_faces`, with plain indices.
+    pub(crate) fn edge_faces_u32(&self, e: u32) -> (Option<u32>, Option<u32>) {
+        let h = e << 1;
+        (self.halfedge_face_u32(h), self.halfedge_face_u32(h | 1))
+    }
+
+    pub fn to_vertex(&self, h: HalfedgeHandle) -> VertexHandle {
+        VertexHandle(self.to_vertex_u32(h.0))
+    }
+
+    pub(crate) fn to_vertex_u32(&self, h: u32) -> u32 {
+        self.halfedge(h).vertex
+    }
+
+    pub fn from_vertex(&self, h: HalfedgeHandle) -> VertexHandle {
+        VertexHandle(self.from_vertex_u32(h.0))
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) fn from_vertex_u32(&self, h: u32) -> u32 {
+        self.to_vertex_u32(self.opposite_halfedge_u32(h))
+    }
+
+    pub fn next_halfedge(&self, h: HalfedgeHandle) -> HalfedgeHandle {
+        HalfedgeHandle(self.next_halfedge_u32(h.0))
+    }
+
+    pub(crate) fn next_halfedge_u32(&self, h: u32) -> u32 {
+        self.halfedge(h).next
+    }
+
+    pub fn prev_halfedge(&self, h: HalfedgeHandle) -> HalfedgeHandle {
+        HalfedgeHandle(self.prev_halfedge_u32(h.0))
+    }
+
+    pub(crate) fn prev_halfedge_u32(&self, h: u32) -> u32 {
+        self.halfedge(h).prev
+    }
+
+    pub fn is_boundary_halfedge(&self, h: HalfedgeHandle) -> bool {
+        self.is_boundary_halfedge_u32(h.0)
+    }
+
+    pub(crate) fn is_boundary_halfedge_u32(&self, h: u32) -> bool {
+        self.halfedge_face_u32(h).is_none()
+    }
+
+    /// Check whether either halfedge of the edge is on the boundary.
+    pub fn is_boundary_edge(&self, e: EdgeHandle) -> bool {
+        self.is_boundary_edge_u32(e.0)
+    }
+
+    /// Same as `is_boundary_edge`, with plain indices.
+    pub(crate) fn is_boundary_edge_u32(&self, e: u32) -> bool {
+        self.is_boundary_halfedge_u32(e << 1) || self.is_boundary_halfedge_u32((e << 1) | 1)
+    }
+
+    pub fn is_boundary_vertex(&self, v: VertexHandle) -> bool {
+        self.is_boundary_vertex_u32(v.0)
+    }
+
+    pub(crate) fn is_boundary_vertex_u32(&self, v: u32) -> bool {
+        match self.vertices[v as usize].halfedge {
+            Some(h) => self.is_boundary_halfedge_u32(h),
+            None => true,
         }
-        return Ok(());
     }
 
-    fn clear(&mut self) -> Result<(), Error> {
-        for prop in self.props.iter_mut() {
-            prop.clear()?;
+    /// Point the outgoing halfedge of every vertex on the boundary to a
+    /// boundary halfedge, which is what `is_boundary_vertex` relies on. The
+    /// editing functions keep this up to date, so this is only needed to
+    /// repair a mesh after its connectivity was changed by other means.
+    pub fn recompute_boundary_status(&mut self) {
+        for v in 0..(self.num_vertices() as u32) {
+            if !self.is_vertex_deleted_u32(v) {
+                self.adjust_outgoing_halfedge(v);
+            }
         }
-        return Ok(());
     }
 
-    fn push_value(&mut self) -> Result<(), Error> {
-        for prop in self.props.iter_mut() {
-            prop.push()?;
+    fn check_vertex(&self, v: u32) -> Result<(), Error> {
+        if (v as usize) < self.num_vertices() {
+            Ok(())
+        } else {
+            Err(Error::OutOfBoundsAccess)
         }
-        return Ok(());
     }
 
-    fn swap(&mut self, i: usize, j: usize) -> Result<(), Error> {
-        for prop in self.props.iter_mut() {
-            prop.swap(i, j)?;
+    fn check_halfedge(&self, h: u32) -> Result<(), Error> {
+        if (h as usize) < self.num_halfedges() {
+            Ok(())
+        } else {
+            Err(Error::OutOfBoundsAccess)
         }
-        return Ok(());
     }
 
-    fn copy(&mut self, src: usize, dst: usize) -> Result<(), Error> {
-        for prop in self.props.iter_mut() {
-            prop.copy(src, dst)?;
+    fn check_edge(&self, e: u32) -> Result<(), Error> {
+        if (e as usize) < self.num_edges() {
+            Ok(())
+        } else {
+            Err(Error::OutOfBoundsAccess)
         }
-        return Ok(());
     }
 
-    fn len(&self) -> Result<usize, Error> {
-        let first = match self.props.first() {
-            Some(first) => first.len()?,
-            None => return Ok(0),
-        };
-        for prop in self.props.iter().skip(1) {
-            assert_eq!(first, prop.len()?);
+    fn check_face(&self, f: u32) -> Result<(), Error> {
+        if (f as usize) < self.num_faces() {
+            Ok(())
+        } else {
+            Err(Error::OutOfBoundsAccess)
         }
-        return Ok(first);
     }
-}
 
-// 'static lifetime enforces the data stored inside properties is fully owned
-// and doesn't contain any weird references.
-trait TPropData: Default + Clone + Copy + 'static {}
+    /// Same as `vertex_halfedge`, but returns an error instead of panicking
+    /// if the vertex does not exist.
+    pub fn try_vertex_halfedge(&self, v: VertexHandle) -> Result<Option<HalfedgeHandle>, Error> {
+        self.check_vertex(v.0)?;
+        Ok(self.vertex_halfedge(v))
+    }
 
-impl TPropData for glam::Vec3 {}
+    /// Same as `face_halfedge`, but returns an error instead of panicking if
+    /// the face does not exist.
+    pub fn try_face_halfedge(&self, f: FaceHandle) -> Result<HalfedgeHandle, Error> {
+        self.check_face(f.0)?;
+        Ok(self.face_halfedge(f))
+    }
 
-trait GenericProperty {
-    fn reserve(&mut self, n: usize) -> Result<(), Error>;
+    /// Same as `halfedge_face`, but returns an error instead of panicking if
+    /// the halfedge does not exist.
+    pub fn try_halfedge_face(&self, h: HalfedgeHandle) -> Result<Option<FaceHandle>, Error> {
+        self.check_halfedge(h.0)?;
+        Ok(self.halfedge_face(h))
+    }
 
-    fn resize(&mut self, n: usize) -> Result<(), Error>;
+    /// Same as `to_vertex`, but returns an error instead of panicking if the
+    /// halfedge does not exist.
+    pub fn try_to_vertex(&self, h: HalfedgeHandle) -> Result<VertexHandle, Error> {
+        self.check_halfedge(h.0)?;
+        Ok(self.to_vertex(h))
+    }
 
-    fn clear(&mut self) -> Result<(), Error>;
+    /// Same as `from_vertex`, but returns an error instead of panicking if
+    /// the halfedge does not exist.
+    pub fn try_from_vertex(&self, h: HalfedgeHandle) -> Result<VertexHandle, Error> {
+        self.check_halfedge(h.0)?;
+        Ok(self.from_vertex(h))
+    }
 
-    fn push(&mut self) -> Result<(), Error>;
+    /// Same as `next_halfedge`, but returns an error instead of panicking if
+    /// the halfedge does not exist.
+    pub fn try_next_halfedge(&self, h: HalfedgeHandle) -> Result<HalfedgeHandle, Error> {
+        self.check_halfedge(h.0)?;
+        Ok(self.next_halfedge(h))
+    }
 
-    fn swap(&mut self, i: usize, j: usize) -> Result<(), Error>;
+    /// Same as `prev_halfedge`, but returns an error instead of panicking if
+    /// the halfedge does not exist.
+    pub fn try_prev_halfedge(&self, h: HalfedgeHandle) -> Result<HalfedgeHandle, Error> {
+        self.check_halfedge(h.0)?;
+        Ok(self.prev_halfedge(h))
+    }
 
-    fn copy(&mut self, src: usize, dst: usize) -> Result<(), Error>;
+    /// Same as `opposite_halfedge`, but returns an error if the halfedge does
+    /// not exist.
+    pub fn try_opposite_halfedge(&self, h: HalfedgeHandle) -> Result<HalfedgeHandle, Error> {
+        self.check_halfedge(h.0)?;
+        Ok(self.opposite_halfedge(h))
+    }
 
-    fn len(&self) -> Result<usize, Error>;
-}
+    pub fn is_vertex_deleted(&self, v: VertexHandle) -> bool {
+        self.is_vertex_deleted_u32(v.0)
+    }
 
-struct Property<T: TPropData> {
-    data: Arc<RwLock<Vec<T>>>,
-}
+    pub(crate) fn is_vertex_deleted_u32(&self, v: u32) -> bool {
+        self.vertices[v as usize].deleted
+    }
 
-impl<T: TPropData> Property<T> {
-    fn new() -> Self {
-        Property {
-            data: Arc::new(RwLock::new(Vec::new())),
-        }
+    pub fn is_edge_deleted(&self, e: EdgeHandle) -> bool {
+        self.is_edge_deleted_u32(e.0)
     }
 
-    fn with_capacity(n: usize) -> Self {
-        Property {
-            data: Arc::new(RwLock::new(Vec::with_capacity(n))),
-        }
+    pub(crate) fn is_edge_deleted_u32(&self, e: u32) -> bool {
+        self.edges[e as usize].deleted
     }
 
-    fn generic_ref(&self) -> Box<dyn GenericProperty> {
-        Box::new(PropertyRef {
-            data: Arc::downgrade(&self.data),
-        })
+    pub fn is_face_deleted(&self, f: FaceHandle) -> bool {
+        self.is_face_deleted_u32(f.0)
     }
 
-    fn get(&self, i: u32) -> Result<T, Error> {
-        self.data
-            .read()
-            .map_err(|_| Error::ReadPropertyFailed)?
-            .get(i as usize)
-            .ok_or(Error::ReadPropertyFailed)
-            .copied()
+    pub(crate) fn is_face_deleted_u32(&self, f: u32) -> bool {
+        self.faces[f as usize].deleted
     }
 
-    fn set(&mut self, i: u32, val: T) -> Result<(), Error> {
-        let mut buf = self
-            .data
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?;
-        buf[i as usize] = val;
-        return Ok(());
+    /// Number of edges incident on the vertex.
+    pub fn vertex_valence(&self, v: VertexHandle) -> usize {
+        self.vertex_valence_u32(v.0)
     }
-}
 
-impl<T: TPropData> Default for Property<T> {
-    fn default() -> Self {
-        Self {
-            data: Default::default(),
-        }
+    /// Same as `vertex_valence`, with plain indices.
+    pub(crate) fn vertex_valence_u32(&self, v: u32) -> usize {
+        self.voh_ccw_iter_u32(v).count()
     }
-}
 
-struct PropertyRef<T: TPropData> {
-    data: Weak<RwLock<Vec<T>>>,
-}
+    /// Number of vertices of the face.
+    pub fn face_valence(&self, f: FaceHandle) -> usize {
+        self.face_valence_u32(f.0)
+    }
 
-impl<T: TPropData> PropertyRef<T> {
-    fn upgrade(&self) -> Result<Arc<RwLock<Vec<T>>>, Error> {
-        self.data.upgrade().ok_or(Error::PropertyDoesNotExist)
+    /// Same as `face_valence`, with plain indices.
+    pub(crate) fn face_valence_u32(&self, f: u32) -> usize {
+        self.fh_ccw_iter_u32(f).count()
     }
-}
 
-impl<T: TPropData> GenericProperty for PropertyRef<T> {
-    fn reserve(&mut self, n: usize) -> Result<(), Error> {
-        self.upgrade()?
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?
-            .reserve(n); // reserve memory.
-        return Ok(());
+    /// Check whether every face has exactly `n` vertices, without walking
+    /// around faces any further than needed to tell.
+    fn all_faces_have_valence(&self, n: usize) -> bool {
+        self.faces_u32()
+            .all(|f| self.fh_ccw_iter_u32(f).take(n + 1).count() == n)
     }
 
-    fn resize(&mut self, n: usize) -> Result<(), Error> {
-        self.upgrade()?
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?
-            .resize(n, T::default());
-        return Ok(());
+    /// Check whether every face is a triangle.
+    pub fn is_triangle_mesh(&self) -> bool {
+        self.all_faces_have_valence(3)
     }
 
-    fn clear(&mut self) -> Result<(), Error> {
-        self.upgrade()?
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?
-            .clear();
-        return Ok(());
+    /// Check whether every face is a quad.
+    pub fn is_quad_mesh(&self) -> bool {
+        self.all_faces_have_valence(4)
     }
 
-    fn push(&mut self) -> Result<(), Error> {
-        self.upgrade()?
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?
-            .push(T::default());
-        return Ok(());
+    pub fn opposite_halfedge(&self, h: HalfedgeHandle) -> HalfedgeHandle {
+        h.opposite()
     }
 
-    fn swap(&mut self, i: usize, j: usize) -> Result<(), Error> {
-        self.upgrade()?
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?
-            .swap(i, j);
-        return Ok(());
+    pub(crate) const fn opposite_halfedge_u32(&self, h: u32) -> u32 {
+        h ^ 1
     }
 
-    fn copy(&mut self, src: usize, dst: usize) -> Result<(), Error> {
-        self.upgrade()?
-            .write()
-            .map_err(|_| Error::WriteToPropertyFailed)?
-            .copy_within(src..(src + 1), dst);
-        return Ok(());
+    pub fn cw_rotated_halfedge(&self, h: HalfedgeHandle) -> HalfedgeHandle {
+        HalfedgeHandle(self.cw_rotated_halfedge_u32(h.0))
     }
 
-    fn len(&self) -> Result<usize, Error> {
-        Ok(self
-            .upgrade()?
-            .read()
-            .map_err(|_| Error::ReadPropertyFailed)?
-            .len())
+    pub(crate) fn cw_rotated_halfedge_u32(&self, h: u32) -> u32 {
+        self.halfedge(self.opposite_halfedge_u32(h)).next
+    }
+
+    pub fn ccw_rotated_halfedge(&self, h: HalfedgeHandle) -> HalfedgeHandle {
+        HalfedgeHandle(self.ccw_rotated_halfedge_u32(h.0))
+    }
+
+    pub(crate) fn ccw_rotated_halfedge_u32(&self, h: u32) -> u32 {
+        self.opposite_halfedge_u32(self.halfedge(h).prev)
+    }
+
+    pub fn voh_ccw_iter(&self, v: VertexHandle) -> impl Iterator<Item = HalfedgeHandle> + '_ {
+        self.voh_ccw_iter_u32(v.0).map(HalfedgeHandle)
+    }
+
+    pub fn voh_cw_iter(&self, v: VertexHandle) -> impl Iterator<Item = HalfedgeHandle> + '_ {
+        self.voh_cw_iter_u32(v.0).map(HalfedgeHandle)
+    }
+
+    pub fn vih_ccw_iter(&self, v: VertexHandle) -> impl Iterator<Item = HalfedgeHandle> + '_ {
+        self.vih_ccw_iter_u32(v.0).map(HalfedgeHandle)
+    }
+
+    pub fn vih_cw_iter(&self, v: VertexHandle) -> impl Iterator<Item = HalfedgeHandle> + '_ {
+        self.vih_cw_iter_u32(v.0).map(HalfedgeHandle)
+    }
+
+    pub fn ve_ccw_iter(&self, v: VertexHandle) -> impl Iterator<Item = EdgeHandle> + '_ {
+        self.ve_ccw_iter_u32(v.0).map(EdgeHandle)
+    }
+
+    pub fn ve_cw_iter(&self, v: VertexHandle) -> impl Iterator<Item = EdgeHandle> + '_ {
+        self.ve_cw_iter_u32(v.0).map(EdgeHandle)
+    }
+
+    pub fn fh_ccw_iter(
+        &self,
+        f: FaceHandle,
+    ) -> impl DoubleEndedIterator<Item = HalfedgeHandle> + '_ {
+        self.fh_ccw_iter_u32(f.0).map(HalfedgeHandle)
+    }
+
+    pub fn fh_cw_iter(
+        &self,
+        f: FaceHandle,
+    ) -> impl DoubleEndedIterator<Item = HalfedgeHandle> + '_ {
+        self.fh_cw_iter_u32(f.0).map(HalfedgeHandle)
+    }
+
+    pub fn fe_ccw_iter(&self, f: FaceHandle) -> impl Iterator<Item = EdgeHandle> + '_ {
+        self.fe_ccw_iter_u32(f.0).map(EdgeHandle)
+    }
+
+    pub fn fe_cw_iter(&self, f: FaceHandle) -> impl Iterator<Item = EdgeHandle> + '_ {
+        self.fe_cw_iter_u32(f.0).map(EdgeHandle)
+    }
+
+    /// Iterate over the loop of boundary halfedges starting at `h`, which
+    /// must be a boundary halfedge.
+    pub fn boundary_loop_iter(
+        &self,
+        h: HalfedgeHandle,
+    ) -> impl Iterator<Item = HalfedgeHandle> + '_ {
+        self.boundary_loop_iter_u32(h.0).map(HalfedgeHandle)
+    }
+
+    pub fn fv_ccw_iter(&self, f: FaceHandle) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.fv_ccw_iter_u32(f.0).map(VertexHandle)
+    }
+
+    pub fn fv_cw_iter(&self, f: FaceHandle) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.fv_cw_iter_u32(f.0).map(VertexHandle)
+    }
+
+    pub fn ff_ccw_iter(&self, f: FaceHandle) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.ff_ccw_iter_u32(f.0).map(FaceHandle)
+    }
+
+    pub fn ff_cw_iter(&self, f: FaceHandle) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.ff_cw_iter_u32(f.0).map(FaceHandle)
+    }
+
+    pub fn vv_ccw_iter(&self, v: VertexHandle) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.vv_ccw_iter_u32(v.0).map(VertexHandle)
+    }
+
+    pub fn vv_cw_iter(&self, v: VertexHandle) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.vv_cw_iter_u32(v.0).map(VertexHandle)
+    }
+
+    pub fn vf_ccw_iter(&self, v: VertexHandle) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.vf_ccw_iter_u32(v.0).map(FaceHandle)
+    }
+
+    pub fn vf_cw_iter(&self, v: VertexHandle) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.vf_cw_iter_u32(v.0).map(FaceHandle)
+    }
+
+    pub(crate) fn voh_ccw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::voh_ccw_iter(self, v)
+    }
+
+    pub(crate) fn voh_cw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::voh_cw_iter(self, v)
+    }
+
+    pub(crate) fn vih_ccw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::vih_ccw_iter(self, v)
+    }
+
+    pub(crate) fn vih_cw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::vih_cw_iter(self, v)
+    }
+
+    pub(crate) fn ve_ccw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::ve_ccw_iter(self, v)
+    }
+
+    pub(crate) fn ve_cw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::ve_cw_iter(self, v)
+    }
+
+    pub(crate) fn fh_ccw_iter_u32(&self, f: u32) -> impl DoubleEndedIterator<Item = u32> + '_ {
+        iterator::fh_ccw_iter(self, f)
+    }
+
+    pub(crate) fn fh_cw_iter_u32(&self, f: u32) -> impl DoubleEndedIterator<Item = u32> + '_ {
+        iterator::fh_cw_iter(self, f)
+    }
+
+    pub(crate) fn fe_ccw_iter_u32(&self, f: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::fe_ccw_iter(self, f)
+    }
+
+    pub(crate) fn fe_cw_iter_u32(&self, f: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::fe_cw_iter(self, f)
+    }
+
+    /// Same as `boundary_loop_iter`, with plain indices.
+    pub(crate) fn boundary_loop_iter_u32(&self, h: u32) -> impl Iterator<Item = u32> + '_ {
+        debug_assert!(
+            self.is_boundary_halfedge_u32(h),
+            "Boundary loop must start at a boundary halfedge"
+        );
+        iterator::boundary_loop_iter(self, h)
+    }
+
+    pub(crate) fn fv_ccw_iter_u32(&self, f: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::fv_ccw_iter(self, f)
+    }
+
+    pub(crate) fn fv_cw_iter_u32(&self, f: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::fv_cw_iter(self, f)
+    }
+
+    pub(crate) fn ff_ccw_iter_u32(&self, f: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::ff_ccw_iter(self, f)
+    }
+
+    pub(crate) fn ff_cw_iter_u32(&self, f: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::ff_cw_iter(self, f)
+    }
+
+    pub(crate) fn vv_ccw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::vv_ccw_iter(self, v)
+    }
+
+    pub(crate) fn vv_cw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::vv_cw_iter(self, v)
+    }
+
+    pub(crate) fn vf_ccw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::vf_ccw_iter(self, v)
+    }
+
+    pub(crate) fn vf_cw_iter_u32(&self, v: u32) -> impl Iterator<Item = u32> + '_ {
+        iterator::vf_cw_iter(self, v)
+    }
+
+    /// Clear `out` and fill it with the neighbors of the vertex, in
+    /// counter-clockwise order. Reusing `out` across calls avoids allocating
+    /// for every vertex.
+    pub fn vertex_one_ring(&self, v: VertexHandle, out: &mut Vec<VertexHandle>) {
+        out.clear();
+        out.extend(self.vv_ccw_iter(v));
+    }
+
+    /// Clear `out` and fill it with the faces incident on the vertex, in
+    /// counter-clockwise order.
+    pub fn vertex_one_ring_faces(&self, v: VertexHandle, out: &mut Vec<FaceHandle>) {
+        out.clear();
+        out.extend(self.vf_ccw_iter(v));
+    }
+
+    /// Iterate over the vertices along with their positions. The positions
+    /// are read while holding a single lock on the points for the lifetime
+    /// of the iterator.
+    pub fn vertex_points_iter(
+        &self,
+    ) -> Result<impl Iterator<Item = (VertexHandle, glam::Vec3)> + '_, Error> {
+        Ok(self
+            .vertex_points_iter_u32()?
+            .map(|(v, p)| (VertexHandle(v), p)))
+    }
+
+    /// Same as `vertex_points_iter`, with plain indices.
+    pub(crate) fn vertex_points_iter_u32(
+        &self,
+    ) -> Result<impl Iterator<Item = (u32, glam::Vec3)> + '_, Error> {
+        self.points.iter()
+    }
+
+    /// Copy of the positions of all the vertices, indexed by vertex. Useful
+    /// to read the geometry without holding a lock on the points, for
+    /// example from within `par_vertices` or `par_faces`.
+    pub fn points_snapshot(&self) -> Result<Vec<glam::Vec3>, Error> {
+        self.points.to_vec()
+    }
+
+    /// Iterate over the positions of the vertices of the face, in
+    /// counter-clockwise order.
+    pub fn fv_points_ccw_iter(
+        &self,
+        f: FaceHandle,
+    ) -> Result<impl Iterator<Item = glam::Vec3> + '_, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self.fv_ccw_iter_u32(f.0).map(move |v| points[v as usize]))
+    }
+
+    /// The halfedge of `f0` whose opposite halfedge belongs to `f1`, or `None`
+    /// if the faces are not adjacent.
+    pub fn shared_halfedge(&self, f0: FaceHandle, f1: FaceHandle) -> Option<HalfedgeHandle> {
+        self.fh_ccw_iter(f0)
+            .find(|h| self.halfedge_face(h.opposite()) == Some(f1))
+    }
+
+    /// The neighbors of every face, indexed by face, as pairs of the
+    /// neighboring face and the edge shared with it, in counter-clockwise
+    /// order around the face. Boundary edges are skipped, and deleted faces
+    /// have no neighbors.
+    pub fn face_adjacency_list(&self) -> Vec<Vec<(FaceHandle, EdgeHandle)>> {
+        let mut adjacency = vec![Vec::new(); self.num_faces()];
+        for f in self.faces() {
+            adjacency[f].extend(
+                self.fh_ccw_iter(f)
+                    .filter_map(|h| self.halfedge_face(h.opposite()).map(|nf| (nf, h.edge()))),
+            );
+        }
+        adjacency
+    }
+
+    /// Whether the vertices `a` and `b` are connected by an edge.
+    pub fn are_adjacent(&self, a: VertexHandle, b: VertexHandle) -> bool {
+        self.find_halfedge(a.0, b.0).is_some()
+    }
+
+    /// The edge connecting the vertices `a` and `b`, if one exists.
+    pub fn edge_between(&self, a: VertexHandle, b: VertexHandle) -> Option<EdgeHandle> {
+        self.find_halfedge(a.0, b.0).map(|h| EdgeHandle(h >> 1))
+    }
+
+    /// Find the halfedge going from `from` to `to`, if one exists.
+    fn find_halfedge(&self, from: u32, to: u32) -> Option<u32> {
+        self.voh_ccw_iter_u32(from)
+            .find(|h| self.to_vertex_u32(*h) == to)
+    }
+
+    fn link_halfedges(&mut self, prev: u32, next: u32) {
+        self.halfedge_mut(prev).next = next;
+        self.halfedge_mut(next).prev = prev;
+    }
+
+    /// Point the outgoing halfedge of the vertex to a boundary halfedge, if
+    /// one exists. This is what makes `is_boundary_vertex` work.
+    fn adjust_outgoing_halfedge(&mut self, v: u32) {
+        let h = self
+            .voh_ccw_iter_u32(v)
+            .find(|h| self.is_boundary_halfedge_u32(*h));
+        if let Some(h) = h {
+            self.vertices[v as usize].halfedge = Some(h);
+        }
+    }
+
+    /// Create a new face with the given halfedge, along with its properties.
+    fn new_face(&mut self, h: u32) -> Result<u32, Error> {
+        check_capacity(self.faces.len() + 1)?;
+        let fi = self.faces.len() as u32;
+        self.fprops.push_value()?;
+        self.faces.push(Face {
+            halfedge: h,
+            deleted: false,
+        });
+        Ok(fi)
+    }
+
+    /// Create a new edge, along with its properties and the properties of its
+    /// halfedges, and return the halfedge going from `from` to `to`.
+    fn new_edge(&mut self, from: u32, to: u32) -> Result<u32, Error> {
+        check_capacity(2 * (self.edges.len() + 1))?;
+        self.eprops.push_value()?;
+        self.hprops.push_value()?;
+        self.hprops.push_value()?;
+        let ei = self.edges.len() as u32;
+        let h = ei << 1;
+        let oh = h | 1;
+        self.edges.push(Edge {
+            halfedges: [
+                Halfedge {
+                    face: None,
+                    vertex: to,
+                    next: oh,
+                    prev: oh,
+                },
+                Halfedge {
+                    face: None,
+                    vertex: from,
+                    next: h,
+                    prev: h,
+                },
+            ],
+            deleted: false,
+        });
+        Ok(h)
+    }
+
+    pub fn point(&self, v: VertexHandle) -> Result<glam::Vec3, Error> {
+        self.point_u32(v.0)
+    }
+
+    pub(crate) fn point_u32(&self, v: u32) -> Result<glam::Vec3, Error> {
+        self.points.get(v)
+    }
+
+    pub fn set_point(&mut self, v: VertexHandle, pos: glam::Vec3) -> Result<(), Error> {
+        self.points.set(v.0, pos)
+    }
+
+    /// Apply the affine transform `m` to all the points of the mesh. Vertex
+    /// normals, if they exist, are transformed by the inverse transpose of
+    /// `m` and normalized. The points of deleted vertices are transformed
+    /// too, which doesn't affect the rest of the mesh.
+    pub fn transform(&mut self, m: glam::Mat4) -> Result<(), Error> {
+        self.points.transform(m)?;
+        if let Some(normals) = self.vnormals.as_mut() {
+            let nm = m.inverse().transpose();
+            normals.map_in_place(|n| *n = nm.transform_vector3(*n).normalize())?;
+        }
+        Ok(())
+    }
+
+    /// Translate the mesh so that the center of its bounding box is at the
+    /// origin.
+    pub fn center_at_origin(&mut self) -> Result<(), Error> {
+        match self.bounds()? {
+            Some((min, max)) => self.transform(glam::Mat4::from_translation(-0.5 * (min + max))),
+            None => Ok(()),
+        }
+    }
+
+    /// Translate and uniformly scale the mesh so that its bounding box is
+    /// centered at the origin, and its largest extent is 1, i.e. it fits in
+    /// the unit cube centered at the origin. A mesh with zero extent is only
+    /// centered.
+    pub fn normalize_to_unit(&mut self) -> Result<(), Error> {
+        let Some((min, max)) = self.bounds()? else {
+            return Ok(());
+        };
+        let extent = (max - min).max_element();
+        let scale = if extent > 0.0 { 1.0 / extent } else { 1.0 };
+        self.transform(
+            glam::Mat4::from_scale(glam::Vec3::splat(scale))
+                * glam::Mat4::from_translation(-0.5 * (min + max)),
+        )
+    }
+
+    /// Min and max corners of the bounding box of the vertices, or `None` if
+    /// there are no vertices.
+    fn bounds(&self) -> Result<Option<(glam::Vec3, glam::Vec3)>, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self
+            .vertices_u32()
+            .map(|v| points[v as usize])
+            .fold(None, |b, p| {
+                Some(match b {
+                    Some((min, max)) => (p.min(min), p.max(max)),
+                    None => (p, p),
+                })
+            }))
+    }
+
+    /// Order of the vertices along a Z-order (Morton) curve through their
+    /// positions, quantized within the bounding box. The returned order
+    /// contains every vertex, including deleted ones, so it can be passed
+    /// directly to `reorder_vertices` to make vertices that are close in
+    /// space also close in memory.
+    pub fn morton_order(&self) -> Result<Vec<VertexHandle>, Error> {
+        const LEVELS: f32 = (1 << 10) as f32;
+        let Some((min, max)) = self.bounds()? else {
+            return Ok((0..self.num_vertices() as u32).map(VertexHandle).collect());
+        };
+        let extent = (max - min).max_element();
+        let scale = if extent > 0.0 { LEVELS / extent } else { 0.0 };
+        let codes: Vec<u32> = self
+            .points
+            .to_vec()?
+            .iter()
+            .map(|p| {
+                let q = ((*p - min) * scale)
+                    .max(glam::Vec3::zero())
+                    .min(glam::Vec3::splat(LEVELS - 1.0));
+                spread_bits(q.x() as u32)
+                    | (spread_bits(q.y() as u32) << 1)
+                    | (spread_bits(q.z() as u32) << 2)
+            })
+            .collect();
+        let mut order: Vec<VertexHandle> = (0..codes.len() as u32).map(VertexHandle).collect();
+        order.sort_by_key(|v| codes[*v]);
+        Ok(order)
+    }
+
+    pub fn add_vertex(&mut self, pos: glam::Vec3) -> Result<VertexHandle, Error> {
+        self.add_vertex_u32(pos).map(VertexHandle)
+    }
+
+    pub(crate) fn add_vertex_u32(&mut self, pos: glam::Vec3) -> Result<u32, Error> {
+        check_capacity(self.vertices.len() + 1)?;
+        let vi = self.vertices.len() as u32;
+        self.vprops.push_value()?;
+        self.vertices.push(Vertex {
+            halfedge: None,
+            deleted: false,
+        });
+        self.points.set(vi, pos)?;
+        Ok(vi)
+    }
+
+    pub fn add_face(&mut self, verts: &[VertexHandle]) -> Result<FaceHandle, Error> {
+        let mut cache = std::mem::take(&mut self.cache);
+        let mut indices = std::mem::take(&mut cache.vertices);
+        indices.clear();
+        indices.extend(verts.iter().map(|v| v.0));
+        let result = self.add_face_impl(&indices, &mut cache);
+        cache.vertices = indices;
+        self.cache = cache;
+        result.map(FaceHandle)
+    }
+
+    pub(crate) fn add_face_u32(&mut self, verts: &[u32]) -> Result<u32, Error> {
+        let mut cache = std::mem::take(&mut self.cache);
+        let result = self.add_face_impl(verts, &mut cache);
+        self.cache = cache;
+        result
+    }
+
+    /// Add all the `faces`, each given by its vertices, in order, and return
+    /// the new faces. If a face can't be added, the faces before it stay in
+    /// the mesh, and the error is returned along with the handles of those
+    /// faces.
+    pub fn add_faces(
+        &mut self,
+        faces: &[&[VertexHandle]],
+    ) -> Result<Vec<FaceHandle>, (Vec<FaceHandle>, Error)> {
+        let mut added = Vec::with_capacity(faces.len());
+        self.faces.reserve(faces.len());
+        self.fprops
+            .reserve(faces.len())
+            .map_err(|e| (Vec::new(), e))?;
+        for verts in faces {
+            match self.add_face(verts) {
+                Ok(f) => added.push(f),
+                Err(e) => return Err((added, e)),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Same as `add_face`, but also fills `halfedges` with the halfedges of
+    /// the new face, in order. The `i`-th halfedge goes from `verts[i]` to
+    /// `verts[i + 1]`, wrapping around at the end.
+    pub fn add_face_with_halfedges(
+        &mut self,
+        verts: &[VertexHandle],
+        halfedges: &mut Vec<HalfedgeHandle>,
+    ) -> Result<FaceHandle, Error> {
+        let f = self.add_face(verts)?;
+        halfedges.clear();
+        halfedges.extend(self.cache.halfedges.iter().map(|h| HalfedgeHandle(*h)));
+        Ok(f)
+    }
+
+    fn add_face_impl(&mut self, verts: &[u32], cache: &mut TopolCache) -> Result<u32, Error> {
+        let n = verts.len();
+        if n < 3 || (1..n).any(|i| verts[..i].contains(&verts[i])) {
+            return Err(Error::DegenerateFace);
+        }
+        cache.clear();
+        cache.halfedges.reserve(n);
+        cache.is_new.reserve(n);
+        cache.needs_adjust.resize(n, false);
+        // Check for topological errors.
+        for i in 0..n {
+            let v = verts[i];
+            if !self.is_boundary_vertex_u32(v) {
+                return Err(Error::ComplexVertex(v));
+            }
+            let h = self.find_halfedge(v, verts[(i + 1) % n]);
+            if let Some(h) = h {
+                if !self.is_boundary_halfedge_u32(h) {
+                    return Err(Error::ComplexEdge(h >> 1));
+                }
+            }
+            // New halfedges are created later, use a placeholder for now.
+            cache.halfedges.push(h.unwrap_or(u32::MAX));
+            cache.is_new.push(h.is_none());
+        }
+        // Re-link patches if necessary.
+        for (i, ii) in (0..n).map(|i| (i, (i + 1) % n)) {
+            if !cache.is_new[i] && !cache.is_new[ii] {
+                let inner_prev = cache.halfedges[i];
+                let inner_next = cache.halfedges[ii];
+                if self.next_halfedge_u32(inner_prev) == inner_next {
+                    continue;
+                }
+                // Search for a free gap.
+                let outer_prev = self.opposite_halfedge_u32(inner_next);
+                let mut boundary_prev = outer_prev;
+                loop {
+                    boundary_prev =
+                        self.opposite_halfedge_u32(self.next_halfedge_u32(boundary_prev));
+                    if self.is_boundary_halfedge_u32(boundary_prev) && boundary_prev != inner_prev {
+                        break;
+                    }
+                }
+                let boundary_next = self.next_halfedge_u32(boundary_prev);
+                if boundary_next == inner_next {
+                    return Err(Error::PatchRelinkingFailed);
+                }
+                // Relink.
+                let patch_start = self.next_halfedge_u32(inner_prev);
+                let patch_end = self.prev_halfedge_u32(inner_next);
+                cache.next_cache.push((boundary_prev, patch_start));
+                cache.next_cache.push((patch_end, boundary_next));
+                cache.next_cache.push((inner_prev, inner_next));
+            }
+        }
+        // Make room for the new elements before touching the topology, so a
+        // failure here leaves the mesh unchanged.
+        check_capacity(self.faces.len() + 1)?;
+        check_capacity(2 * (self.edges.len() + cache.is_new.iter().filter(|n| **n).count()))?;
+        self.fprops.push_value()?;
+        // Create missing edges.
+        for i in 0..n {
+            if cache.is_new[i] {
+                cache.halfedges[i] = self.new_edge(verts[i], verts[(i + 1) % n])?;
+            }
+        }
+        // Create the face.
+        let fnew = self.faces.len() as u32;
+        self.faces.push(Face {
+            halfedge: cache.halfedges[n - 1],
+            deleted: false,
+        });
+        // Setup halfedges.
+        for (i, ii) in (0..n).map(|i| (i, (i + 1) % n)) {
+            let v = verts[ii];
+            let inner_prev = cache.halfedges[i];
+            let inner_next = cache.halfedges[ii];
+            match (cache.is_new[i], cache.is_new[ii]) {
+                (false, false) => {
+                    cache.needs_adjust[ii] = self.vertex_halfedge_u32(v) == Some(inner_next);
+                }
+                (is_new_prev, is_new_next) => {
+                    let outer_prev = self.opposite_halfedge_u32(inner_next);
+                    let outer_next = self.opposite_halfedge_u32(inner_prev);
+                    match (is_new_prev, is_new_next) {
+                        (true, false) => {
+                            let boundary_prev = self.prev_halfedge_u32(inner_next);
+                            cache.next_cache.push((boundary_prev, outer_next));
+                            self.vertices[v as usize].halfedge = Some(outer_next);
+                        }
+                        (false, true) => {
+                            let boundary_next = self.next_halfedge_u32(inner_prev);
+                            cache.next_cache.push((outer_prev, boundary_next));
+                            self.vertices[v as usize].halfedge = Some(boundary_next);
+                        }
+                        _ => match self.vertex_halfedge_u32(v) {
+                            Some(boundary_next) => {
+                                let boundary_prev = self.prev_halfedge_u32(boundary_next);
+                                cache.next_cache.push((boundary_prev, outer_next));
+                                cache.next_cache.push((outer_prev, boundary_next));
+                            }
+                            None => {
+                                self.vertices[v as usize].halfedge = Some(outer_next);
+                                cache.next_cache.push((outer_prev, outer_next));
+                            }
+                        },
+                    }
+                    // Set inner link.
+                    cache.next_cache.push((inner_prev, inner_next));
+                }
+            }
+            self.halfedge_mut(inner_prev).face = Some(fnew);
+        }
+        // Process the next halfedge cache.
+        for (prev, next) in cache.next_cache.iter() {
+            self.link_halfedges(*prev, *next);
+        }
+        // Adjust vertices' halfedge handles.
+        for (v, needs_adjust) in verts.iter().zip(cache.needs_adjust.iter()) {
+            if *needs_adjust {
+                self.adjust_outgoing_halfedge(*v);
+            }
+        }
+        Ok(fnew)
+    }
+
+    pub fn add_tri_face(
+        &mut self,
+        v0: VertexHandle,
+        v1: VertexHandle,
+        v2: VertexHandle,
+    ) -> Result<FaceHandle, Error> {
+        self.add_face(&[v0, v1, v2])
+    }
+
+    pub(crate) fn add_tri_face_u32(&mut self, v0: u32, v1: u32, v2: u32) -> Result<u32, Error> {
+        self.add_face_u32(&[v0, v1, v2])
+    }
+
+    pub fn add_quad_face(
+        &mut self,
+        v0: VertexHandle,
+        v1: VertexHandle,
+        v2: VertexHandle,
+        v3: VertexHandle,
+    ) -> Result<FaceHandle, Error> {
+        self.add_face(&[v0, v1, v2, v3])
+    }
+
+    /// Append the vertices and faces of `other` to this mesh. The new
+    /// elements get default values for all properties other than the
+    /// positions. Coincident vertices are not merged. Deleted elements of
+    /// `other` are skipped.
+    pub fn append(&mut self, other: &Mesh) -> Result<(), Error> {
+        self.reserve(other.num_vertices(), other.num_edges(), other.num_faces())?;
+        let offset = self.num_vertices() as u32;
+        let (indices, _) = other.compact_vertex_indices();
+        for v in other.vertices_u32() {
+            self.add_vertex_u32(other.point_u32(v)?)?;
+        }
+        let mut fverts = Vec::new();
+        for f in other.faces_u32() {
+            fverts.clear();
+            fverts.extend(
+                other
+                    .fv_ccw_iter_u32(f)
+                    .map(|v| indices[v as usize] + offset),
+            );
+            self.add_face_u32(&fverts)?;
+        }
+        Ok(())
+    }
+
+    /// Build the dual of the mesh, with a vertex at the centroid of every face,
+    /// and a face around every interior vertex connecting the centroids of
+    /// the faces around it, in counter-clockwise order. Boundary vertices
+    /// don't have a closed ring of faces around them, so they have no dual
+    /// face. The dual vertices follow the order of the faces, skipping
+    /// deleted faces.
+    pub fn dual(&self) -> Result<Mesh, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let mut dual = Mesh::with_capacity(self.num_faces(), self.num_edges(), self.num_vertices());
+        let mut fmap = vec![u32::MAX; self.num_faces()];
+        for f in self.faces_u32() {
+            let (sum, count) = self
+                .fv_ccw_iter_u32(f)
+                .fold((glam::Vec3::zero(), 0usize), |(sum, count), v| {
+                    (sum + points[v as usize], count + 1)
+                });
+            fmap[f as usize] = dual.add_vertex_u32(sum / count as f32)?;
+        }
+        let mut fverts = Vec::new();
+        for v in self.vertices_u32() {
+            if self.is_boundary_vertex_u32(v) {
+                continue;
+            }
+            fverts.clear();
+            fverts.extend(self.vf_ccw_iter_u32(v).map(|f| fmap[f as usize]));
+            dual.add_face_u32(&fverts)?;
+        }
+        Ok(dual)
+    }
+
+    /// Build a mesh from a triangle soup, such as a GPU index buffer. If
+    /// `weld_tolerance` is given, positions within that distance of each
+    /// other become a single vertex. Triangles whose winding disagrees with
+    /// their neighbors are reversed, keeping the winding of the majority in
+    /// each connected piece. Triangles that still can't be added, because
+    /// they are degenerate or would make the mesh non-manifold, are skipped,
+    /// and their indices are returned along with the mesh.
+    pub fn from_triangle_soup(
+        positions: &[glam::Vec3],
+        indices: &[[u32; 3]],
+        weld_tolerance: Option<f32>,
+    ) -> Result<(Mesh, Vec<usize>), Error> {
+        let target = match weld_tolerance {
+            Some(tol) if tol > 0.0 => coincident_vertices(positions, tol, |_| false),
+            _ => (0..(positions.len() as u32)).collect(),
+        };
+        let mut newindex = vec![u32::MAX; positions.len()];
+        let nverts = target
+            .iter()
+            .enumerate()
+            .filter(|(v, t)| **t as usize == *v)
+            .count();
+        let mut mesh = Mesh::with_capacity(nverts, indices.len() * 3 / 2, indices.len());
+        for (v, p) in positions.iter().enumerate() {
+            if target[v] as usize == v {
+                newindex[v] = mesh.add_vertex_u32(*p)?;
+            }
+        }
+        let mut triangles = Vec::with_capacity(indices.len());
+        for tri in indices.iter() {
+            let mut verts = [0u32; 3];
+            for (dst, src) in verts.iter_mut().zip(tri.iter()) {
+                let t = *target.get(*src as usize).ok_or(Error::OutOfBoundsAccess)?;
+                *dst = newindex[t as usize];
+            }
+            triangles.push(verts);
+        }
+        orient_polygons(&mut triangles);
+        let mut skipped = Vec::new();
+        for (i, verts) in triangles.iter().enumerate() {
+            match mesh.add_face_u32(verts) {
+                Ok(_) => {}
+                Err(
+                    Error::DegenerateFace
+                    | Error::ComplexVertex(_)
+                    | Error::ComplexEdge(_)
+                    | Error::PatchRelinkingFailed,
+                ) => skipped.push(i),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok((mesh, skipped))
+    }
+
+    /// Export the mesh as indexed triangles, e.g. for uploading to a GPU. The
+    /// positions are indexed by vertex, and every face is split into a fan of
+    /// triangles around its first vertex. If the vertex normals have been
+    /// computed with `update_vertex_normals`, they are returned as well.
+    #[allow(clippy::type_complexity)]
+    pub fn to_triangle_buffers(
+        &self,
+    ) -> Result<(Vec<glam::Vec3>, Vec<[u32; 3]>, Option<Vec<glam::Vec3>>), Error> {
+        let positions = self.points.to_vec()?;
+        let normals = match &self.vnormals {
+            Some(normals) => Some(normals.to_vec()?),
+            None => None,
+        };
+        let mut triangles = Vec::with_capacity(self.num_faces() * 2);
+        for f in self.faces_u32() {
+            let mut verts = self.fv_ccw_iter_u32(f);
+            let first = verts.next().expect("A face must have vertices");
+            let mut prev = verts.next().expect("A face must have vertices");
+            for v in verts {
+                triangles.push([first, prev, v]);
+                prev = v;
+            }
+        }
+        Ok((positions, triangles, normals))
+    }
+
+    /// Merge vertices that are within `tolerance` of each other, and return
+    /// the number of vertices removed. Of each group of coincident vertices,
+    /// the one with the smallest index survives and keeps its properties.
+    /// Faces that become degenerate because of the welding are dropped. The
+    /// topology is rebuilt from the welded faces, so if a welded face can't be
+    /// added, the error is returned and the mesh is left unchanged. All the
+    /// edges are new after welding, so edge and halfedge properties are reset
+    /// to their default values. Deleted vertices are not welded, and the
+    /// deleted elements are dropped along with the welded vertices.
+    pub fn weld_vertices(&mut self, tolerance: f32) -> Result<usize, Error> {
+        if tolerance.is_nan() || tolerance <= 0.0 {
+            return Ok(0);
+        }
+        let nverts = self.num_vertices();
+        let target = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            coincident_vertices(&points, tolerance, |v| self.is_vertex_deleted_u32(v))
+        };
+        // Compact the surviving vertices.
+        let mut newindex = vec![u32::MAX; nverts];
+        let mut survivors: Vec<u32> = Vec::with_capacity(nverts);
+        for v in self.vertices_u32() {
+            if target[v as usize] == v {
+                newindex[v as usize] = survivors.len() as u32;
+                survivors.push(v);
+            }
+        }
+        let nremoved = self.vertices_u32().count() - survivors.len();
+        if nremoved == 0 {
+            return Ok(0);
+        }
+        // Rebuild the topology with the welded faces.
+        let mut topol = Mesh::with_capacity(survivors.len(), self.num_edges(), self.num_faces());
+        for _ in 0..survivors.len() {
+            topol.add_vertex_u32(glam::Vec3::zero())?;
+        }
+        let mut kept_faces: Vec<u32> = Vec::with_capacity(self.num_faces());
+        let mut fverts: Vec<u32> = Vec::new();
+        for f in self.faces_u32() {
+            fverts.clear();
+            for v in self.fv_ccw_iter_u32(f) {
+                let v = newindex[target[v as usize] as usize];
+                if fverts.last() != Some(&v) {
+                    fverts.push(v);
+                }
+            }
+            if fverts.len() > 1 && fverts.first() == fverts.last() {
+                fverts.pop();
+            }
+            let mut sorted = fverts.clone();
+            sorted.sort();
+            sorted.dedup();
+            if sorted.len() < 3 || sorted.len() != fverts.len() {
+                continue; // Degenerate.
+            }
+            topol.add_face_u32(&fverts)?;
+            kept_faces.push(f);
+        }
+        // Compact the properties.
+        for (i, v) in survivors.iter().enumerate() {
+            self.vprops.copy(*v as usize, i)?;
+        }
+        self.vprops.resize(survivors.len())?;
+        for (i, f) in kept_faces.iter().enumerate() {
+            self.fprops.copy(*f as usize, i)?;
+        }
+        self.fprops.resize(kept_faces.len())?;
+        // The edges are all new, so their properties start over.
+        self.hprops.clear()?;
+        self.hprops.resize(topol.num_halfedges())?;
+        self.eprops.clear()?;
+        self.eprops.resize(topol.num_edges())?;
+        self.vertices = std::mem::take(&mut topol.vertices);
+        self.edges = std::mem::take(&mut topol.edges);
+        self.faces = std::mem::take(&mut topol.faces);
+        Ok(nremoved)
+    }
+
+    /// Split the face into a fan of triangles rooted at its first vertex. The
+    /// new triangles inherit the properties of the face. Triangles are left
+    /// untouched.
+    fn triangulate_face(&mut self, f: u32) -> Result<(), Error> {
+        self.triangulate_face_from(f, self.next_halfedge_u32(self.face_halfedge_u32(f)))
+    }
+
+    /// Same as `triangulate_face`, with the fan rooted at the vertex the
+    /// halfedge `base` of the face starts from.
+    fn triangulate_face_from(&mut self, f: u32, mut base: u32) -> Result<(), Error> {
+        let start = self.from_vertex_u32(base);
+        let mut next = self.next_halfedge_u32(base);
+        while self.to_vertex_u32(self.next_halfedge_u32(next)) != start {
+            let next_next = self.next_halfedge_u32(next);
+            let fnew = self.new_face(base)?;
+            self.copy_face_properties_u32(f, fnew)?;
+            let hnew = self.new_edge(self.to_vertex_u32(next), start)?;
+            self.link_halfedges(base, next);
+            self.link_halfedges(next, hnew);
+            self.link_halfedges(hnew, base);
+            for h in [base, next, hnew] {
+                self.halfedge_mut(h).face = Some(fnew);
+            }
+            base = self.opposite_halfedge_u32(hnew);
+            next = next_next;
+        }
+        // The last triangle keeps the original face.
+        self.faces[f as usize].halfedge = base;
+        let last = self.next_halfedge_u32(next);
+        self.link_halfedges(base, next);
+        self.link_halfedges(last, base);
+        self.halfedge_mut(base).face = Some(f);
+        Ok(())
+    }
+
+    /// Replace every face with more than three vertices with a fan of
+    /// triangles, preserving the winding and the face properties. Non-convex
+    /// faces are fanned all the same.
+    pub fn triangulate(&mut self) -> Result<(), Error> {
+        for f in 0..(self.num_faces() as u32) {
+            if self.is_face_deleted_u32(f) {
+                continue;
+            }
+            self.triangulate_face(f)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `triangulate`, except that every quad is split along its
+    /// shorter diagonal, which gives better shaped triangles than always
+    /// splitting at the first vertex. Other faces are fanned as usual.
+    pub fn triangulate_shortest_diagonal(&mut self) -> Result<(), Error> {
+        for f in 0..(self.num_faces() as u32) {
+            if self.is_face_deleted_u32(f) {
+                continue;
+            }
+            let h = self.next_halfedge_u32(self.face_halfedge_u32(f));
+            if self.face_valence_u32(f) != 4 {
+                self.triangulate_face_from(f, h)?;
+                continue;
+            }
+            let hs = [
+                h,
+                self.next_halfedge_u32(h),
+                self.next_halfedge_u32(self.next_halfedge_u32(h)),
+                self.prev_halfedge_u32(h),
+            ];
+            let [d0, d1] = {
+                let points = self
+                    .points
+                    .data
+                    .read()
+                    .map_err(|_| Error::ReadPropertyFailed)?;
+                let p = |i: usize| points[self.from_vertex_u32(hs[i]) as usize];
+                [(p(2) - p(0)).length(), (p(3) - p(1)).length()]
+            };
+            self.triangulate_face_from(f, if d1 < d0 { hs[1] } else { hs[0] })?;
+        }
+        Ok(())
+    }
+
+    /// Unit normal of the face, computed using Newell's method directly over
+    /// the edges of the face, so it works for faces with any number of
+    /// vertices, without triangulating them. For non-planar faces this is
+    /// the normal of the plane that best fits the face.
+    pub fn calc_face_normal(&self, f: FaceHandle) -> Result<glam::Vec3, Error> {
+        self.calc_face_normal_u32(f.0)
+    }
+
+    /// Same as `calc_face_normal`, with plain indices.
+    pub(crate) fn calc_face_normal_u32(&self, f: u32) -> Result<glam::Vec3, Error> {
+        self.check_face(f)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self.face_normal_from(f, &points))
+    }
+
+    /// Newell's vector of the face, i.e. the normal scaled by twice the area.
+    /// This is zero for deleted faces, whose halfedges may no longer form a
+    /// loop.
+    fn newell_vector(&self, f: u32, points: &[glam::Vec3]) -> glam::Vec3 {
+        let mut normal = glam::Vec3::zero();
+        if self.is_face_deleted_u32(f) {
+            return normal;
+        }
+        for h in self.fh_ccw_iter_u32(f) {
+            let a = points[self.from_vertex_u32(h) as usize];
+            let b = points[self.to_vertex_u32(h) as usize];
+            normal += glam::vec3(
+                (a.y() - b.y()) * (a.z() + b.z()),
+                (a.z() - b.z()) * (a.x() + b.x()),
+                (a.x() - b.x()) * (a.y() + b.y()),
+            );
+        }
+        normal
+    }
+
+    fn face_normal_from(&self, f: u32, points: &[glam::Vec3]) -> glam::Vec3 {
+        let normal = self.newell_vector(f, points);
+        let len = normal.length();
+        if len > 0.0 {
+            normal / len
+        } else {
+            normal
+        }
+    }
+
+    /// Area of the face, computed using Newell's method. Like
+    /// `calc_face_normal`, this works for faces with any number of vertices,
+    /// convex or not. For non-planar faces this is the area of the face
+    /// projected onto the plane of its normal.
+    pub fn calc_face_area(&self, f: FaceHandle) -> Result<f32, Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self.newell_vector(f, &points).length() * 0.5)
+    }
+
+    /// Area of the face, summing the signed areas of the triangles of a fan
+    /// rooted at the first vertex, measured along the normal of the face.
+    /// Triangles of the fan that fold back over the face count as negative,
+    /// so the area is correct for non-convex faces, as long as they are
+    /// planar and don't intersect themselves.
+    pub fn calc_face_area_signed(&self, f: FaceHandle) -> Result<f32, Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let normal = self.face_normal_from(f, &points);
+        let mut verts = self.fv_ccw_iter_u32(f).map(|v| points[v as usize]);
+        let (Some(root), Some(mut prev)) = (verts.next(), verts.next()) else {
+            return Ok(0.0);
+        };
+        let mut area = 0.0;
+        for p in verts {
+            area += (prev - root).cross(p - root).dot(normal);
+            prev = p;
+        }
+        Ok(area * 0.5)
+    }
+
+    /// How far the face is from being planar, measured as the largest
+    /// distance of its vertices from the plane through their centroid, along
+    /// the normal of the face. The distance is divided by the average length
+    /// of the edges of the face, so that it doesn't depend on the scale.
+    /// Triangles and planar faces give zero.
+    pub fn face_planarity(&self, f: FaceHandle) -> Result<f32, Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let normal = self.face_normal_from(f, &points);
+        let (sum, count) = self
+            .fv_ccw_iter_u32(f)
+            .fold((glam::Vec3::zero(), 0usize), |(sum, count), v| {
+                (sum + points[v as usize], count + 1)
+            });
+        let centroid = sum / count as f32;
+        let perimeter: f32 = self
+            .fh_ccw_iter_u32(f)
+            .map(|h| {
+                (points[self.to_vertex_u32(h) as usize] - points[self.from_vertex_u32(h) as usize])
+                    .length()
+            })
+            .sum();
+        if perimeter == 0.0 {
+            return Ok(0.0);
+        }
+        let deviation = self
+            .fv_ccw_iter_u32(f)
+            .map(|v| (points[v as usize] - centroid).dot(normal).abs())
+            .fold(0.0f32, f32::max);
+        Ok(deviation * count as f32 / perimeter)
+    }
+
+    /// Total area of all faces of the mesh.
+    pub fn surface_area(&self) -> Result<f32, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self
+            .faces_u32()
+            .map(|f| self.newell_vector(f, &points).length() * 0.5)
+            .sum())
+    }
+
+    /// Signed volume enclosed by the mesh, computed using the divergence
+    /// theorem, by summing the signed volumes of the tetrahedra formed by the
+    /// origin and the faces fanned into triangles. The result is only
+    /// meaningful for closed meshes, and is negative if the faces are oriented
+    /// inwards.
+    pub fn volume(&self) -> Result<f32, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let mut total = 0.0;
+        let mut fverts = Vec::new();
+        for f in self.faces_u32() {
+            fverts.clear();
+            fverts.extend(self.fv_ccw_iter_u32(f).map(|v| points[v as usize]));
+            for i in 1..(fverts.len() - 1) {
+                total += fverts[0].dot(fverts[i].cross(fverts[i + 1]));
+            }
+        }
+        Ok(total / 6.0)
+    }
+
+    /// Distance between the two vertices of the edge.
+    pub fn calc_edge_length(&self, e: EdgeHandle) -> Result<f32, Error> {
+        self.calc_edge_length_u32(e.0)
+    }
+
+    /// Same as `calc_edge_length`, with plain indices.
+    pub(crate) fn calc_edge_length_u32(&self, e: u32) -> Result<f32, Error> {
+        self.check_edge(e)?;
+        let h = e << 1;
+        Ok(
+            (self.point_u32(self.to_vertex_u32(h))? - self.point_u32(self.from_vertex_u32(h))?)
+                .length(),
+        )
+    }
+
+    /// Angle between the normals of the two faces incident on the edge. The
+    /// angle is positive if the edge is convex, and negative if it is
+    /// concave. `None` is returned for boundary edges.
+    pub fn calc_dihedral_angle(&self, e: EdgeHandle) -> Result<Option<f32>, Error> {
+        let e = e.0;
+        self.check_edge(e)?;
+        let h0 = e << 1;
+        let h1 = self.opposite_halfedge_u32(h0);
+        let (f0, f1) = match (self.halfedge_face_u32(h0), self.halfedge_face_u32(h1)) {
+            (Some(f0), Some(f1)) => (f0, f1),
+            _ => return Ok(None),
+        };
+        let n0 = self.calc_face_normal_u32(f0)?;
+        let n1 = self.calc_face_normal_u32(f1)?;
+        let dir =
+            self.point_u32(self.to_vertex_u32(h0))? - self.point_u32(self.from_vertex_u32(h0))?;
+        let cross = n0.cross(n1);
+        let angle = cross.length().atan2(n0.dot(n1));
+        Ok(Some(if cross.dot(dir) >= 0.0 { angle } else { -angle }))
+    }
+
+    /// Normalized sum of the normals of the faces incident on the vertex.
+    /// Deleted vertices get a zero normal.
+    fn vertex_normal_from(&self, v: u32, fnormals: &[glam::Vec3]) -> glam::Vec3 {
+        if self.is_vertex_deleted_u32(v) {
+            return glam::Vec3::zero();
+        }
+        let normal = self
+            .vf_ccw_iter_u32(v)
+            .fold(glam::Vec3::zero(), |total, f| total + fnormals[f as usize]);
+        let len = normal.length();
+        if len > 0.0 {
+            normal / len
+        } else {
+            normal
+        }
+    }
+
+    /// Get the vertex normal property, creating it if it doesn't exist.
+    fn vertex_normals_mut(&mut self) -> &mut Property<glam::Vec3> {
+        if self.vnormals.is_none() {
+            let prop = Property::<glam::Vec3>::new();
+            self.vprops.push_property(prop.generic_ref());
+            self.vnormals = Some(prop);
+        }
+        self.vnormals
+            .as_mut()
+            .expect("Vertex normals must exist here")
+    }
+
+    fn write_vertex_normals(&mut self, normals: Vec<glam::Vec3>) -> Result<(), Error> {
+        *self
+            .vertex_normals_mut()
+            .data
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)? = normals;
+        Ok(())
+    }
+
+    /// Compute the vertex normals and store them in a vertex property. The
+    /// normal of a vertex is the normalized sum of the normals of its incident
+    /// faces.
+    pub fn update_vertex_normals(&mut self) -> Result<(), Error> {
+        let normals = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let fnormals: Vec<_> = (0..(self.num_faces() as u32))
+                .map(|f| self.face_normal_from(f, &points))
+                .collect();
+            (0..(self.num_vertices() as u32))
+                .map(|v| self.vertex_normal_from(v, &fnormals))
+                .collect()
+        };
+        self.write_vertex_normals(normals)
+    }
+
+    /// Same as `update_vertex_normals`, but the face and vertex normals are
+    /// computed in parallel. Each vertex gathers the normals of its incident
+    /// faces, so no two threads ever write to the same vertex.
+    #[cfg(feature = "rayon")]
+    pub fn update_vertex_normals_par(&mut self) -> Result<(), Error> {
+        use rayon::prelude::*;
+        let normals = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let fnormals: Vec<_> = (0..(self.num_faces() as u32))
+                .into_par_iter()
+                .map(|f| self.face_normal_from(f, &points))
+                .collect();
+            (0..(self.num_vertices() as u32))
+                .into_par_iter()
+                .map(|v| self.vertex_normal_from(v, &fnormals))
+                .collect()
+        };
+        self.write_vertex_normals(normals)
+    }
+
+    /// The normal of the vertex, as computed by the most recent call to
+    /// `update_vertex_normals`.
+    pub fn vertex_normal(&self, v: VertexHandle) -> Result<glam::Vec3, Error> {
+        let v = v.0;
+        self.vnormals
+            .as_ref()
+            .ok_or(Error::PropertyDoesNotExist)?
+            .get(v)
+    }
+
+    /// Color of the vertex, as RGB values in the range 0 to 1, if the mesh
+    /// has vertex colors, e.g. from a file that has them.
+    pub fn vertex_color(&self, v: VertexHandle) -> Result<glam::Vec3, Error> {
+        let v = v.0;
+        self.vcolors
+            .as_ref()
+            .ok_or(Error::PropertyDoesNotExist)?
+            .get(v)
+    }
+
+    /// Set the color of the vertex. The property holding the colors is
+    /// created on first use, with all colors set to black.
+    pub fn set_vertex_color(&mut self, v: VertexHandle, color: glam::Vec3) -> Result<(), Error> {
+        let v = v.0;
+        self.check_vertex(v)?;
+        if self.vcolors.is_none() {
+            self.vcolors = Some(self.create_vertex_prop());
+        }
+        self.vcolors
+            .as_mut()
+            .expect("Vertex colors must exist here")
+            .set(v, color)
+    }
+
+    /// Texture coordinates of the corner of the face of the halfedge, at the
+    /// vertex the halfedge points to. Storing the coordinates per halfedge
+    /// lets a vertex on a texture seam have different coordinates in each of
+    /// its faces.
+    pub fn halfedge_uv(&self, h: HalfedgeHandle) -> Result<glam::Vec2, Error> {
+        let h = h.0;
+        self.huvs
+            .as_ref()
+            .ok_or(Error::PropertyDoesNotExist)?
+            .get(h)
+    }
+
+    /// Set the texture coordinates of the halfedge. See `halfedge_uv`. The
+    /// property holding the coordinates is created on first use, with all
+    /// coordinates set to zero.
+    pub fn set_halfedge_uv(&mut self, h: HalfedgeHandle, uv: glam::Vec2) -> Result<(), Error> {
+        let h = h.0;
+        self.check_halfedge(h)?;
+        if self.huvs.is_none() {
+            self.huvs = Some(self.create_halfedge_prop());
+        }
+        self.huvs
+            .as_mut()
+            .expect("Halfedge UVs must exist here")
+            .set(h, uv)
+    }
+
+    /// Normal of the face of the halfedge, weighted by the angle of the face
+    /// at the corner the halfedge points into. Summing these over the
+    /// incoming halfedges of a vertex gives its angle weighted normal.
+    /// Boundary halfedges are not in a face, and get a zero vector.
+    pub fn calc_corner_normal(&self, h: HalfedgeHandle) -> Result<glam::Vec3, Error> {
+        let h = h.0;
+        self.check_halfedge(h)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self.corner_normal_from(h, &points))
+    }
+
+    fn corner_normal_from(&self, h: u32, points: &[glam::Vec3]) -> glam::Vec3 {
+        match self.halfedge_face_u32(h) {
+            Some(f) => self.face_normal_from(f, points) * self.corner_angle_from(h, points),
+            None => glam::Vec3::zero(),
+        }
+    }
+
+    /// Angle of the face of the halfedge at the corner it points into.
+    fn corner_angle_from(&self, h: u32, points: &[glam::Vec3]) -> f32 {
+        let p = points[self.to_vertex_u32(h) as usize];
+        let a = points[self.to_vertex_u32(self.next_halfedge_u32(h)) as usize] - p;
+        let b = points[self.from_vertex_u32(h) as usize] - p;
+        a.cross(b).length().atan2(a.dot(b))
+    }
+
+    /// Compute a normal for every face corner and store it in a halfedge
+    /// property, see `halfedge_normal`. The normal of a corner is the average
+    /// of the normals of the faces around its vertex that are within
+    /// `threshold` radians of the face of the corner, weighted by their
+    /// angles at the vertex. Edges sharper than the threshold are thus shaded
+    /// flat, and the rest smooth. Boundary halfedges get a zero vector.
+    pub fn split_normals_by_angle(&mut self, threshold: f32) -> Result<(), Error> {
+        let normals = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let fnormals: Vec<_> = (0..(self.num_faces() as u32))
+                .map(|f| self.face_normal_from(f, &points))
+                .collect();
+            let min_cos = threshold.cos();
+            (0..(self.num_halfedges() as u32))
+                .map(|h| {
+                    let Some(f) = self.halfedge_face_u32(h) else {
+                        return glam::Vec3::zero();
+                    };
+                    let fnormal = fnormals[f as usize];
+                    let normal = self
+                        .vih_ccw_iter_u32(self.to_vertex_u32(h))
+                        .filter_map(|ih| {
+                            let g = self.halfedge_face_u32(ih)?;
+                            let gnormal = fnormals[g as usize];
+                            (g == f || gnormal.dot(fnormal) >= min_cos)
+                                .then(|| self.corner_normal_from(ih, &points))
+                        })
+                        .fold(glam::Vec3::zero(), |total, n| total + n);
+                    let len = normal.length();
+                    if len > 0.0 {
+                        normal / len
+                    } else {
+                        fnormal
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        if self.hnormals.is_none() {
+            self.hnormals = Some(self.create_halfedge_prop());
+        }
+        self.hnormals
+            .as_mut()
+            .expect("Halfedge normals must exist here")
+            .from_slice(&normals)
+    }
+
+    /// The normal of the face corner the halfedge points into, as computed
+    /// by the most recent call to `split_normals_by_angle`.
+    pub fn halfedge_normal(&self, h: HalfedgeHandle) -> Result<glam::Vec3, Error> {
+        let h = h.0;
+        self.hnormals
+            .as_ref()
+            .ok_or(Error::PropertyDoesNotExist)?
+            .get(h)
+    }
+
+    /// Find the point on the mesh closest to `query`. Returns the face
+    /// containing the closest point, the closest point and the squared
+    /// distance from the query to it. Faces are fanned into triangles from
+    /// their first vertex. `None` is returned if the mesh has no faces. Like
+    /// the other queries that read the positions, this returns an error if
+    /// the positions can't be read, e.g. when the lock on them is poisoned.
+    pub fn closest_point(
+        &self,
+        query: glam::Vec3,
+    ) -> Result<Option<(FaceHandle, glam::Vec3, f32)>, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let mut best: Option<(FaceHandle, glam::Vec3, f32)> = None;
+        let mut fverts = Vec::new();
+        for f in self.faces_u32() {
+            fverts.clear();
+            fverts.extend(self.fv_ccw_iter_u32(f).map(|v| points[v as usize]));
+            for i in 1..(fverts.len() - 1) {
+                let pt = closest_point_on_triangle(query, fverts[0], fverts[i], fverts[i + 1]);
+                let dsq = (pt - query).length_squared();
+                if best.is_none_or(|(_, _, bdsq)| dsq < bdsq) {
+                    best = Some((FaceHandle(f), pt, dsq));
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    /// The vertices whose positions lie within the axis aligned box from
+    /// `min` to `max`, boundary included.
+    pub fn vertices_in_box(
+        &self,
+        min: glam::Vec3,
+        max: glam::Vec3,
+    ) -> Result<Vec<VertexHandle>, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self
+            .vertices()
+            .filter(|v| point_in_box(points[*v], min, max))
+            .collect())
+    }
+
+    /// The faces whose vertices all lie within the axis aligned box from
+    /// `min` to `max`, boundary included.
+    pub fn faces_in_box(&self, min: glam::Vec3, max: glam::Vec3) -> Result<Vec<FaceHandle>, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self
+            .faces()
+            .filter(|f| {
+                self.fv_ccw_iter(*f)
+                    .all(|v| point_in_box(points[v], min, max))
+            })
+            .collect())
+    }
+
+    /// Number of vertices on the boundary. Isolated vertices count as
+    /// boundary vertices, as with `is_boundary_vertex`.
+    pub fn boundary_vertex_count(&self) -> usize {
+        self.vertices_u32()
+            .filter(|v| self.is_boundary_vertex_u32(*v))
+            .count()
+    }
+
+    /// Number of edges on the boundary.
+    pub fn boundary_edge_count(&self) -> usize {
+        self.edges_u32()
+            .filter(|e| self.is_boundary_edge_u32(*e))
+            .count()
+    }
+
+    /// Iterate over all the halfedges on the boundary, i.e. the halfedges
+    /// without a face.
+    pub fn boundary_halfedges(&self) -> impl Iterator<Item = HalfedgeHandle> + '_ {
+        self.boundary_halfedges_u32().map(HalfedgeHandle)
+    }
+
+    /// Same as `boundary_halfedges`, with plain indices.
+    pub(crate) fn boundary_halfedges_u32(&self) -> impl Iterator<Item = u32> + '_ {
+        self.edges_u32()
+            .flat_map(|e| [e << 1, (e << 1) | 1])
+            .filter(|h| self.is_boundary_halfedge_u32(*h))
+    }
+
+    /// The distinct loops of boundary halfedges, each in the order given by
+    /// `next_halfedge`.
+    pub fn boundary_loops(&self) -> Vec<Vec<HalfedgeHandle>> {
+        let mut visited = vec![false; self.num_halfedges()];
+        let mut loops = Vec::new();
+        for h in self.boundary_halfedges() {
+            if visited[h] {
+                continue;
+            }
+            let hloop: Vec<HalfedgeHandle> = self.boundary_loop_iter(h).collect();
+            for bh in &hloop {
+                visited[*bh] = true;
+            }
+            loops.push(hloop);
+        }
+        loops
+    }
+
+    /// Number of distinct loops of boundary halfedges, i.e. the number of
+    /// holes, counting the outer boundary of open surfaces.
+    pub fn boundary_loop_count(&self) -> usize {
+        let mut visited = vec![false; self.num_halfedges()];
+        let mut count = 0usize;
+        for h in self.boundary_halfedges_u32() {
+            if visited[h as usize] {
+                continue;
+            }
+            count += 1;
+            for bh in self.boundary_loop_iter_u32(h) {
+                visited[bh as usize] = true;
+            }
+        }
+        count
+    }
+
+    /// The Euler characteristic `V - E + F` of the mesh. Deleted elements
+    /// are not counted.
+    pub fn euler_characteristic(&self) -> i64 {
+        self.vertices_u32().count() as i64 - self.edges_u32().count() as i64
+            + self.faces_u32().count() as i64
+    }
+
+    /// The genus of a closed mesh, computed as `(2 - χ) / 2`, where `χ` is
+    /// the Euler characteristic. `None` is returned when the mesh is empty,
+    /// has boundary loops, or has more than one connected component.
+    pub fn genus(&self) -> Option<i64> {
+        if self.num_connected_components() != 1 || self.boundary_loop_count() > 0 {
+            return None;
+        }
+        let twice = 2 - self.euler_characteristic();
+        if twice < 0 || twice % 2 != 0 {
+            None
+        } else {
+            Some(twice / 2)
+        }
+    }
+
+    /// Find a path with the fewest edges from `src` to `dst`, using a breadth
+    /// first search. The returned path includes both `src` and `dst`. `None`
+    /// is returned if the vertices are not connected.
+    pub fn topological_path(
+        &self,
+        src: VertexHandle,
+        dst: VertexHandle,
+    ) -> Option<Vec<VertexHandle>> {
+        let mut parent: Vec<Option<VertexHandle>> = vec![None; self.num_vertices()];
+        let mut queue = VecDeque::new();
+        parent[src] = Some(src);
+        queue.push_back(src);
+        while let Some(v) = queue.pop_front() {
+            if v == dst {
+                let mut path = vec![dst];
+                let mut current = dst;
+                while current != src {
+                    current = parent[current]?;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for nv in self.vv_ccw_iter(v) {
+                if parent[nv].is_none() {
+                    parent[nv] = Some(v);
+                    queue.push_back(nv);
+                }
+            }
+        }
+        None
+    }
+
+    /// Label each face with the index of the connected component it belongs
+    /// to. Faces are connected if they share an edge. The returned vector is
+    /// indexed by face, and the labels are numbered from zero. Deleted faces
+    /// are labeled `u32::MAX`.
+    pub fn connected_components(&self) -> Vec<u32> {
+        let mut labels = vec![u32::MAX; self.num_faces()];
+        let mut stack = Vec::new();
+        let mut current = 0u32;
+        for seed in self.faces_u32() {
+            if labels[seed as usize] != u32::MAX {
+                continue;
+            }
+            labels[seed as usize] = current;
+            stack.push(seed);
+            while let Some(f) = stack.pop() {
+                for nf in self.ff_ccw_iter_u32(f) {
+                    if labels[nf as usize] == u32::MAX {
+                        labels[nf as usize] = current;
+                        stack.push(nf);
+                    }
+                }
+            }
+            current += 1;
+        }
+        labels
+    }
+
+    /// Grow a region of faces from `seed`, across shared edges, to the faces
+    /// whose normals are within `angle_tol` radians of the normal of the
+    /// seed. The seed is the first face in the returned list.
+    pub fn select_coplanar_region(
+        &self,
+        seed: FaceHandle,
+        angle_tol: f32,
+    ) -> Result<Vec<FaceHandle>, Error> {
+        let seed = seed.0;
+        self.check_face(seed)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let normal = self.face_normal_from(seed, &points);
+        let min_cos = angle_tol.cos();
+        let mut visited = vec![false; self.num_faces()];
+        let mut region = vec![FaceHandle(seed)];
+        let mut stack = vec![seed];
+        visited[seed as usize] = true;
+        while let Some(f) = stack.pop() {
+            for nf in self.ff_ccw_iter_u32(f) {
+                if visited[nf as usize] {
+                    continue;
+                }
+                visited[nf as usize] = true;
+                if self.face_normal_from(nf, &points).dot(normal) > min_cos {
+                    region.push(FaceHandle(nf));
+                    stack.push(nf);
+                }
+            }
+        }
+        Ok(region)
+    }
+
+    /// Number of connected components of faces in the mesh.
+    pub fn num_connected_components(&self) -> usize {
+        self.connected_components()
+            .iter()
+            .filter(|l| **l != u32::MAX)
+            .max()
+            .map(|l| *l as usize + 1)
+            .unwrap_or(0)
+    }
+
+    /// Check whether the mesh is a manifold. Every edge of the halfedge
+    /// structure has at most two faces, so this checks that the faces around
+    /// each vertex form a single fan, i.e. that no vertex is visited by the
+    /// boundary more than once.
+    pub fn is_manifold(&self) -> bool {
+        self.vertices_u32().all(|v| {
+            self.voh_ccw_iter_u32(v)
+                .filter(|h| self.is_boundary_halfedge_u32(*h))
+                .count()
+                <= 1
+        })
+    }
+
+    /// Split every vertex where several fans of faces meet, such as the tip of
+    /// a bowtie, so that each fan gets its own vertex. The fan containing the
+    /// outgoing halfedge of the vertex, i.e. `vertex_halfedge(v)`, keeps the
+    /// original vertex, and the other fans get new copies of it, with the
+    /// same position and properties. Returns the number of new vertices.
+    pub fn split_nonmanifold_vertices(&mut self) -> Result<usize, Error> {
+        let mut count = 0usize;
+        for v in 0..(self.num_vertices() as u32) {
+            if self.is_vertex_deleted_u32(v) {
+                continue;
+            }
+            let Some(hv) = self.vertex_halfedge_u32(v) else {
+                continue;
+            };
+            let mut hs: Vec<u32> = self.voh_ccw_iter_u32(v).collect();
+            let Some(first) = hs.iter().position(|h| self.is_boundary_halfedge_u32(*h)) else {
+                continue;
+            };
+            // Each fan is a run of halfedges with faces, followed by the
+            // boundary halfedge that ends it.
+            hs.rotate_left(first + 1);
+            let fans: Vec<Vec<u32>> = hs
+                .split_inclusive(|h| self.is_boundary_halfedge_u32(*h))
+                .map(|fan| fan.to_vec())
+                .collect();
+            if fans.len() < 2 {
+                continue;
+            }
+            let keep = fans.iter().position(|fan| fan.contains(&hv)).unwrap_or(0);
+            for (i, fan) in fans.iter().enumerate() {
+                let last = fan[fan.len() - 1];
+                // Close the boundary around the fan on its own.
+                self.link_halfedges(self.opposite_halfedge_u32(fan[0]), last);
+                let vfan = if i == keep {
+                    v
+                } else {
+                    let vnew = self.add_vertex_u32(self.point_u32(v)?)?;
+                    self.vprops.copy(v as usize, vnew as usize)?;
+                    for h in fan {
+                        let oh = self.opposite_halfedge_u32(*h);
+                        self.halfedge_mut(oh).vertex = vnew;
+                    }
+                    count += 1;
+                    vnew
+                };
+                self.vertices[vfan as usize].halfedge = Some(last);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Whether the two meshes have the same vertices and faces, in the same
+    /// order, with the positions of corresponding vertices within `tol` of
+    /// each other. The faces must list the same vertices, starting from the
+    /// same vertex. Other properties are not compared. Useful for comparing
+    /// the output of an algorithm against a known result. The meshes are
+    /// not equal if their positions can't be read.
+    pub fn approx_eq(&self, other: &Mesh, tol: f32) -> bool {
+        if self.num_vertices() != other.num_vertices() || self.num_faces() != other.num_faces() {
+            return false;
+        }
+        {
+            let (Ok(points), Ok(opoints)) = (self.points.data.read(), other.points.data.read())
+            else {
+                return false;
+            };
+            if points
+                .iter()
+                .zip(opoints.iter())
+                .any(|(a, b)| (*a - *b).length() > tol)
+            {
+                return false;
+            }
+        }
+        (0..(self.num_faces() as u32)).all(|f| {
+            match (self.is_face_deleted_u32(f), other.is_face_deleted_u32(f)) {
+                (false, false) => self.fv_ccw_iter_u32(f).eq(other.fv_ccw_iter_u32(f)),
+                (deleted, odeleted) => deleted == odeleted,
+            }
+        })
+    }
+
+    /// Check the invariants of the halfedge data structure, and return an
+    /// error describing the first violation found.
+    pub fn validate(&self) -> Result<(), Error> {
+        let nverts = self.num_vertices() as u32;
+        let nhalfedges = self.num_halfedges() as u32;
+        let nfaces = self.num_faces() as u32;
+        for h in 0..nhalfedges {
+            if self.is_edge_deleted_u32(h >> 1) {
+                continue;
+            }
+            if self.opposite_halfedge_u32(self.opposite_halfedge_u32(h)) != h {
+                return Err(Error::InvalidOppositeHalfedge(h));
+            }
+            let he = self.halfedge(h);
+            if he.next >= nhalfedges
+                || he.prev >= nhalfedges
+                || self.prev_halfedge_u32(he.next) != h
+                || self.next_halfedge_u32(he.prev) != h
+            {
+                return Err(Error::InvalidHalfedgeLink(h));
+            }
+            if he.vertex >= nverts || he.vertex != self.from_vertex_u32(he.next) {
+                return Err(Error::DisconnectedHalfedges(h));
+            }
+        }
+        for f in 0..nfaces {
+            if self.is_face_deleted_u32(f) {
+                continue;
+            }
+            let hstart = self.face_halfedge_u32(f);
+            if hstart >= nhalfedges {
+                return Err(Error::InvalidFaceLoop(f));
+            }
+            // A loop can't be longer than the total number of halfedges.
+            let mut h = hstart;
+            let mut closed = false;
+            for _ in 0..nhalfedges {
+                if self.halfedge_face_u32(h) != Some(f) {
+                    return Err(Error::InvalidFaceLoop(f));
+                }
+                h = self.next_halfedge_u32(h);
+                if h == hstart {
+                    closed = true;
+                    break;
+                }
+            }
+            if !closed {
+                return Err(Error::InvalidFaceLoop(f));
+            }
+        }
+        for v in 0..nverts {
+            if self.is_vertex_deleted_u32(v) {
+                continue;
+            }
+            if let Some(h) = self.vertex_halfedge_u32(v) {
+                if h >= nhalfedges || self.from_vertex_u32(h) != v {
+                    return Err(Error::InvalidVertexHalfedge(v));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Map each point to the first point within `tolerance` of it, which is
+/// itself if there is no such point before it. The points for which `skip`
+/// returns true map to themselves, and nothing maps to them.
+fn coincident_vertices(
+    points: &[glam::Vec3],
+    tolerance: f32,
+    skip: impl Fn(u32) -> bool,
+) -> Vec<u32> {
+    let mut target: Vec<u32> = (0..(points.len() as u32)).collect();
+    let tolsq = tolerance * tolerance;
+    let mut grid = SpatialHash::new(tolerance);
+    for (v, p) in points.iter().enumerate() {
+        if skip(v as u32) {
+            continue;
+        }
+        let found = grid
+            .query_cell_neighbors(*p)
+            .find(|other| (points[*other as usize] - *p).length_squared() <= tolsq);
+        match found {
+            Some(other) => target[v] = other,
+            None => grid.insert(*p, v as u32),
+        }
+    }
+    target
+}
+
+/// Reverse the polygons whose winding disagrees with their neighbors, i.e.
+/// that traverse a shared edge in the same direction as the polygon across
+/// it. Each group of polygons connected by edges is oriented independently,
+/// keeping the winding that most of its polygons already have. Returns the
+/// number of polygons reversed.
+fn orient_polygons<F: AsRef<[u32]> + AsMut<[u32]>>(polygons: &mut [F]) -> usize {
+    let mut edge_faces: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (i, poly) in polygons.iter().enumerate() {
+        let verts = poly.as_ref();
+        for (j, a) in verts.iter().enumerate() {
+            let b = verts[(j + 1) % verts.len()];
+            if *a != b {
+                edge_faces
+                    .entry((*a.min(&b), *a.max(&b)))
+                    .or_default()
+                    .push(i);
+            }
+        }
+    }
+    // Whether the polygon goes from `a` to `b` along one of its edges.
+    let has_directed = |verts: &[u32], a: u32, b: u32| {
+        (0..verts.len()).any(|j| verts[j] == a && verts[(j + 1) % verts.len()] == b)
+    };
+    let mut visited = vec![false; polygons.len()];
+    let mut reversed = vec![false; polygons.len()];
+    let mut total = 0usize;
+    let mut stack = Vec::new();
+    for seed in 0..polygons.len() {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+        stack.push(seed);
+        let mut component = Vec::new();
+        while let Some(i) = stack.pop() {
+            component.push(i);
+            let verts = polygons[i].as_ref().to_vec();
+            for (j, a) in verts.iter().enumerate() {
+                let b = verts[(j + 1) % verts.len()];
+                let Some(neighbors) = edge_faces.get(&(*a.min(&b), *a.max(&b))) else {
+                    continue;
+                };
+                for n in neighbors.iter() {
+                    if visited[*n] {
+                        continue;
+                    }
+                    visited[*n] = true;
+                    if has_directed(polygons[*n].as_ref(), *a, b) {
+                        polygons[*n].as_mut().reverse();
+                        reversed[*n] = true;
+                    }
+                    stack.push(*n);
+                }
+            }
+        }
+        let count = component.iter().filter(|i| reversed[**i]).count();
+        if count * 2 > component.len() {
+            // Flipping the other polygons instead takes fewer reversals.
+            for i in component.iter() {
+                polygons[*i].as_mut().reverse();
+            }
+            total += component.len() - count;
+        } else {
+            total += count;
+        }
+    }
+    total
+}
+
+/// The largest number of elements of any kind, such that every handle fits in
+/// a `u32`. Halfedges count separately from edges.
+#[cfg(not(test))]
+fn max_elements() -> usize {
+    u32::MAX as usize
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Tests lower this to exercise the capacity checks without allocating
+    /// billions of elements.
+    static MAX_ELEMENTS: std::cell::Cell<usize> = const { std::cell::Cell::new(u32::MAX as usize) };
+}
+
+#[cfg(test)]
+fn max_elements() -> usize {
+    MAX_ELEMENTS.with(|m| m.get())
+}
+
+/// Check that a mesh with `count` elements of a kind can be addressed with
+/// `u32` handles.
+fn check_capacity(count: usize) -> Result<(), Error> {
+    if count > max_elements() {
+        Err(Error::CapacityExceeded)
+    } else {
+        Ok(())
+    }
+}
+
+/// Spread the lowest 10 bits of `x` so that there are two zero bits between
+/// consecutive bits, to interleave them into a Morton code.
+fn spread_bits(x: u32) -> u32 {
+    let mut x = x & 0x3ff;
+    x = (x | (x << 16)) & 0x030000ff;
+    x = (x | (x << 8)) & 0x0300f00f;
+    x = (x | (x << 4)) & 0x030c30c3;
+    (x | (x << 2)) & 0x09249249
+}
+
+/// Whether `p` lies within the axis aligned box from `min` to `max`.
+fn point_in_box(p: glam::Vec3, min: glam::Vec3, max: glam::Vec3) -> bool {
+    (p.cmpge(min) & p.cmple(max)).all()
+}
+
+/// Closest point to `p` on the triangle `(a, b, c)`. This is the method
+/// described in Real-Time Collision Detection by Christer Ericson, which finds
+/// the Voronoi region of the triangle that contains `p`.
+fn closest_point_on_triangle(
+    p: glam::Vec3,
+    a: glam::Vec3,
+    b: glam::Vec3,
+    c: glam::Vec3,
+) -> glam::Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+struct PropertyContainer {
+    props: Vec<Box<dyn GenericProperty>>,
+}
+
+#[allow(dead_code)]
+impl PropertyContainer {
+    fn new() -> Self {
+        PropertyContainer { props: Vec::new() }
+    }
+
+    fn push_property(&mut self, prop: Box<dyn GenericProperty>) {
+        self.props.push(prop);
+    }
+
+    /// Forget the properties that have been dropped by their owners.
+    fn prune(&mut self) {
+        self.props.retain(|prop| prop.is_alive());
+    }
+
+    fn reserve(&mut self, n: usize) -> Result<(), Error> {
+        self.prune();
+        for prop in self.props.iter_mut() {
+            prop.reserve(n)?;
+        }
+        Ok(())
+    }
+
+    fn resize(&mut self, n: usize) -> Result<(), Error> {
+        self.prune();
+        for prop in self.props.iter_mut() {
+            prop.resize(n)?;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        self.prune();
+        for prop in self.props.iter_mut() {
+            prop.clear()?;
+        }
+        Ok(())
+    }
+
+    fn push_value(&mut self) -> Result<(), Error> {
+        self.prune();
+        for prop in self.props.iter_mut() {
+            prop.push()?;
+        }
+        Ok(())
+    }
+
+    fn swap(&mut self, i: usize, j: usize) -> Result<(), Error> {
+        self.prune();
+        for prop in self.props.iter_mut() {
+            prop.swap(i, j)?;
+        }
+        Ok(())
+    }
+
+    fn copy(&mut self, src: usize, dst: usize) -> Result<(), Error> {
+        self.prune();
+        for prop in self.props.iter_mut() {
+            prop.copy(src, dst)?;
+        }
+        Ok(())
+    }
+
+    /// Copy the rows of the properties of `src`, matched with the properties
+    /// of this container in order. The properties whose data is in `skip`, or
+    /// in `src_skip` for `src`, are left out of the matching.
+    fn copy_rows_from(
+        &mut self,
+        src: &PropertyContainer,
+        mapping: &[Option<u32>],
+        skip: &[*const ()],
+        src_skip: &[*const ()],
+    ) -> Result<(), Error> {
+        self.prune();
+        let sources: Vec<&dyn GenericProperty> = src
+            .props
+            .iter()
+            .filter(|prop| prop.is_alive() && !src_skip.contains(&prop.data_ptr()))
+            .map(|prop| prop.as_ref())
+            .collect();
+        let mut dests: Vec<&mut Box<dyn GenericProperty>> = self
+            .props
+            .iter_mut()
+            .filter(|prop| !skip.contains(&prop.data_ptr()))
+            .collect();
+        if sources.len() != dests.len()
+            || sources
+                .iter()
+                .zip(dests.iter())
+                .any(|(s, d)| s.as_any().type_id() != d.as_any().type_id())
+        {
+            return Err(Error::PropertyMismatch);
+        }
+        for (dst, src) in dests.iter_mut().zip(sources) {
+            dst.copy_rows_from(src, mapping)?;
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> Result<usize, Error> {
+        let mut alive = self.props.iter().filter(|prop| prop.is_alive());
+        let first = match alive.next() {
+            Some(first) => first.len()?,
+            None => return Ok(0),
+        };
+        for prop in alive {
+            assert_eq!(first, prop.len()?);
+        }
+        Ok(first)
+    }
+}
+
+// 'static lifetime enforces the data stored inside properties is fully owned
+// and doesn't contain any weird references.
+pub trait TPropData: Default + Clone + Copy + Send + Sync + 'static {}
+
+impl TPropData for glam::Vec2 {}
+impl TPropData for glam::Vec3 {}
+impl TPropData for glam::Vec4 {}
+impl TPropData for bool {}
+impl TPropData for u8 {}
+impl TPropData for u16 {}
+impl TPropData for u32 {}
+impl TPropData for u64 {}
+impl TPropData for usize {}
+impl TPropData for i8 {}
+impl TPropData for i16 {}
+impl TPropData for i32 {}
+impl TPropData for i64 {}
+impl TPropData for isize {}
+impl TPropData for f32 {}
+impl TPropData for f64 {}
+
+// Arrays only implement `Default` up to a length of 32, so they can't be
+// covered by a single impl generic over the length.
+macro_rules! impl_prop_data_for_arrays {
+    ($($n:literal)*) => {
+        $(impl<T: TPropData> TPropData for [T; $n] {})*
+    };
+}
+
+impl_prop_data_for_arrays!(
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+);
+
+/// Property data that can be blended linearly, such as texture coordinates.
+pub trait TInterpolate:
+    TPropData + std::ops::Add<Output = Self> + std::ops::Mul<f32, Output = Self>
+{
+}
+
+impl TInterpolate for glam::Vec2 {}
+impl TInterpolate for glam::Vec3 {}
+impl TInterpolate for f32 {}
+
+#[allow(dead_code)]
+trait GenericProperty: Send + Sync {
+    fn is_alive(&self) -> bool;
+
+    fn reserve(&mut self, n: usize) -> Result<(), Error>;
+
+    fn resize(&mut self, n: usize) -> Result<(), Error>;
+
+    fn clear(&mut self) -> Result<(), Error>;
+
+    fn push(&mut self) -> Result<(), Error>;
+
+    fn swap(&mut self, i: usize, j: usize) -> Result<(), Error>;
+
+    fn copy(&mut self, src: usize, dst: usize) -> Result<(), Error>;
+
+    /// Set the value at `i` to the value of `src` at `mapping[i]`, if any.
+    /// `src` must hold values of the same type.
+    fn copy_rows_from(
+        &mut self,
+        src: &dyn GenericProperty,
+        mapping: &[Option<u32>],
+    ) -> Result<(), Error>;
+
+    fn len(&self) -> Result<usize, Error>;
+
+    fn as_any(&self) -> &dyn std::any::Any;
+
+    /// Address of the data, to identify the property.
+    fn data_ptr(&self) -> *const ();
+}
+
+/// Per-element data attached to a mesh. Properties are created by the mesh,
+/// which keeps the number of values in sync with the number of elements as
+/// they are added. The mesh only holds a weak reference to the data, so the
+/// property is removed from the mesh when it is dropped.
+pub struct Property<T: TPropData> {
+    data: Arc<RwLock<Vec<T>>>,
+    /// Value given to new elements.
+    default: T,
+}
+
+impl<T: TPropData> Property<T> {
+    fn new() -> Self {
+        Property {
+            data: Arc::new(RwLock::new(Vec::new())),
+            default: T::default(),
+        }
+    }
+
+    fn with_capacity(n: usize) -> Self {
+        Property {
+            data: Arc::new(RwLock::new(Vec::with_capacity(n))),
+            default: T::default(),
+        }
+    }
+
+    fn with_default(n: usize, default: T) -> Self {
+        Property {
+            data: Arc::new(RwLock::new(vec![default; n])),
+            default,
+        }
+    }
+
+    fn data_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.data) as *const ()
+    }
+
+    fn generic_ref(&self) -> Box<dyn GenericProperty> {
+        Box::new(PropertyRef {
+            data: Arc::downgrade(&self.data),
+            default: self.default,
+        })
+    }
+
+    /// The value given to new elements.
+    pub fn default_value(&self) -> T {
+        self.default
+    }
+
+    pub fn get(&self, i: u32) -> Result<T, Error> {
+        self.data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?
+            .get(i as usize)
+            .ok_or(Error::OutOfBoundsAccess)
+            .copied()
+    }
+
+    pub fn set(&mut self, i: u32, val: T) -> Result<(), Error> {
+        let mut buf = self
+            .data
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?;
+        *buf.get_mut(i as usize).ok_or(Error::OutOfBoundsAccess)? = val;
+        Ok(())
+    }
+
+    /// Copy all the values into a new vector.
+    pub fn to_vec(&self) -> Result<Vec<T>, Error> {
+        Ok(self
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?
+            .clone())
+    }
+
+    /// Iterate over the values along with the indices of their elements. The
+    /// property stays locked for reading until the iterator is dropped, so
+    /// it can't be written to in the meantime.
+    pub fn iter(&self) -> Result<impl Iterator<Item = (u32, T)> + '_, Error> {
+        let data = self.data.read().map_err(|_| Error::ReadPropertyFailed)?;
+        Ok((0..(data.len() as u32)).map(move |i| (i, data[i as usize])))
+    }
+
+    /// Overwrite all the values with `data`, which must have one value per
+    /// element.
+    pub fn from_slice(&mut self, data: &[T]) -> Result<(), Error> {
+        let mut buf = self
+            .data
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?;
+        if buf.len() != data.len() {
+            return Err(Error::LengthMismatch(buf.len(), data.len()));
+        }
+        buf.copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Apply `f` to every value, in place.
+    pub fn map_in_place(&mut self, f: impl FnMut(&mut T)) -> Result<(), Error> {
+        self.data
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .iter_mut()
+            .for_each(f);
+        Ok(())
+    }
+}
+
+impl Property<glam::Vec3> {
+    /// Treat every value as a position and apply the affine transform `m` to
+    /// it.
+    pub fn transform(&mut self, m: glam::Mat4) -> Result<(), Error> {
+        self.map_in_place(|p| *p = m.transform_point3(*p))
+    }
+}
+
+impl<T: TPropData> Default for Property<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct PropertyRef<T: TPropData> {
+    data: Weak<RwLock<Vec<T>>>,
+    default: T,
+}
+
+impl<T: TPropData> PropertyRef<T> {
+    fn upgrade(&self) -> Result<Arc<RwLock<Vec<T>>>, Error> {
+        self.data.upgrade().ok_or(Error::PropertyDoesNotExist)
+    }
+}
+
+impl<T: TPropData> GenericProperty for PropertyRef<T> {
+    fn is_alive(&self) -> bool {
+        self.data.strong_count() > 0
+    }
+
+    fn reserve(&mut self, n: usize) -> Result<(), Error> {
+        self.upgrade()?
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .reserve(n); // reserve memory.
+        Ok(())
+    }
+
+    fn resize(&mut self, n: usize) -> Result<(), Error> {
+        self.upgrade()?
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .resize(n, self.default);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        self.upgrade()?
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .clear();
+        Ok(())
+    }
+
+    fn push(&mut self) -> Result<(), Error> {
+        self.upgrade()?
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .push(self.default);
+        Ok(())
+    }
+
+    fn swap(&mut self, i: usize, j: usize) -> Result<(), Error> {
+        self.upgrade()?
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .swap(i, j);
+        Ok(())
+    }
+
+    fn copy(&mut self, src: usize, dst: usize) -> Result<(), Error> {
+        self.upgrade()?
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)?
+            .copy_within(src..(src + 1), dst);
+        Ok(())
+    }
+
+    fn copy_rows_from(
+        &mut self,
+        src: &dyn GenericProperty,
+        mapping: &[Option<u32>],
+    ) -> Result<(), Error> {
+        let src = src
+            .as_any()
+            .downcast_ref::<PropertyRef<T>>()
+            .ok_or(Error::PropertyMismatch)?
+            .upgrade()?;
+        let src = src.read().map_err(|_| Error::ReadPropertyFailed)?;
+        let dst = self.upgrade()?;
+        let mut dst = dst.write().map_err(|_| Error::WriteToPropertyFailed)?;
+        for (d, s) in dst.iter_mut().zip(mapping) {
+            if let Some(s) = s {
+                *d = *src.get(*s as usize).ok_or(Error::OutOfBoundsAccess)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> Result<usize, Error> {
+        Ok(self
+            .upgrade()?
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?
+            .len())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn data_ptr(&self) -> *const () {
+        self.data.as_ptr() as *const ()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{
+        orient_polygons, EdgeHandle, Error, FaceHandle, HalfedgeHandle, Mesh, Property,
+        VertexHandle,
+    };
+
+    /// A unit box made of 6 quad faces, with outward facing normals.
+    pub(crate) fn quad_box() -> Mesh {
+        let mut mesh = Mesh::with_capacity(8, 12, 6);
+        let verts = [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(1.0, 1.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(0.0, 0.0, 1.0),
+            glam::vec3(1.0, 0.0, 1.0),
+            glam::vec3(1.0, 1.0, 1.0),
+            glam::vec3(0.0, 1.0, 1.0),
+        ];
+        for v in verts {
+            mesh.add_vertex_u32(v).expect("Unable to add a vertex");
+        }
+        let faces = [
+            [0u32, 3, 2, 1],
+            [0, 1, 5, 4],
+            [1, 2, 6, 5],
+            [2, 3, 7, 6],
+            [3, 0, 4, 7],
+            [4, 5, 6, 7],
+        ];
+        for f in faces {
+            mesh.add_face_u32(&f).expect("Unable to add a face");
+        }
+        mesh
+    }
+
+    /// A flat grid of `nx` x `ny` unit quads in the XY plane.
+    pub(crate) fn quad_grid(nx: u32, ny: u32) -> Mesh {
+        let mut mesh = Mesh::with_capacity(
+            ((nx + 1) * (ny + 1)) as usize,
+            (nx * (ny + 1) + ny * (nx + 1)) as usize,
+            (nx * ny) as usize,
+        );
+        for y in 0..=ny {
+            for x in 0..=nx {
+                mesh.add_vertex_u32(glam::vec3(x as f32, y as f32, 0.0))
+                    .expect("Unable to add a vertex");
+            }
+        }
+        let vi = |x: u32, y: u32| VertexHandle(y * (nx + 1) + x);
+        for y in 0..ny {
+            for x in 0..nx {
+                mesh.add_quad_face(vi(x, y), vi(x + 1, y), vi(x + 1, y + 1), vi(x, y + 1))
+                    .expect("Unable to add a face");
+            }
+        }
+        mesh
+    }
+
+    /// Same as `quad_grid`, with every quad split into two triangles.
+    pub(crate) fn tri_grid(nx: u32, ny: u32) -> Mesh {
+        let mut mesh = quad_grid(nx, ny);
+        mesh.triangulate().expect("Unable to triangulate");
+        mesh
+    }
+
+    /// A UV sphere centered at the origin, with `nu` segments around the
+    /// z-axis and `nv` segments from pole to pole. The faces at the poles are
+    /// triangles, the rest are quads.
+    pub(crate) fn uv_sphere(radius: f32, nu: u32, nv: u32) -> Mesh {
+        use std::f32::consts::{PI, TAU};
+        let mut mesh = Mesh::with_capacity(
+            (nu * (nv - 1) + 2) as usize,
+            (nu * (2 * nv - 1)) as usize,
+            (nu * nv) as usize,
+        );
+        let north = mesh
+            .add_vertex(glam::vec3(0.0, 0.0, radius))
+            .expect("Unable to add a vertex");
+        for j in 1..nv {
+            let theta = PI * (j as f32) / (nv as f32);
+            for i in 0..nu {
+                let phi = TAU * (i as f32) / (nu as f32);
+                mesh.add_vertex_u32(
+                    glam::vec3(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    ) * radius,
+                )
+                .expect("Unable to add a vertex");
+            }
+        }
+        let south = mesh
+            .add_vertex(glam::vec3(0.0, 0.0, -radius))
+            .expect("Unable to add a vertex");
+        let ring = |j: u32, i: u32| VertexHandle(1 + (j - 1) * nu + (i % nu));
+        for i in 0..nu {
+            mesh.add_tri_face(north, ring(1, i), ring(1, i + 1))
+                .expect("Unable to add a face");
+        }
+        for j in 1..(nv - 1) {
+            for i in 0..nu {
+                mesh.add_quad_face(
+                    ring(j, i),
+                    ring(j + 1, i),
+                    ring(j + 1, i + 1),
+                    ring(j, i + 1),
+                )
+                .expect("Unable to add a face");
+            }
+        }
+        for i in 0..nu {
+            mesh.add_tri_face(ring(nv - 1, i), south, ring(nv - 1, i + 1))
+                .expect("Unable to add a face");
+        }
+        mesh
+    }
+
+    #[test]
+    fn t_quad_box() {
+        let mesh = quad_box();
+        mesh.validate().expect("Box mesh is invalid");
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_halfedges(), 24);
+        assert_eq!(mesh.num_edges(), 12);
+        assert_eq!(mesh.num_faces(), 6);
+        for v in 0..8 {
+            assert!(!mesh.is_boundary_vertex_u32(v));
+        }
+        for h in 0..24 {
+            assert!(!mesh.is_boundary_halfedge_u32(h));
+        }
+    }
+
+    #[test]
+    fn t_clear() {
+        let mut mesh = quad_box();
+        let points: Vec<_> = (0..8).map(|v| mesh.point_u32(v).unwrap()).collect();
+        let faces: Vec<Vec<u32>> = (0..6).map(|f| mesh.fv_ccw_iter_u32(f).collect()).collect();
+        let mut fprop = mesh.create_face_prop::<u8>();
+        mesh.clear().unwrap();
+        assert_eq!(mesh.num_vertices(), 0);
+        assert_eq!(mesh.num_edges(), 0);
+        assert_eq!(mesh.num_faces(), 0);
+        assert!(matches!(mesh.point_u32(0), Err(Error::OutOfBoundsAccess)));
+        assert!(matches!(fprop.get(0), Err(Error::OutOfBoundsAccess)));
+        for p in points.iter() {
+            mesh.add_vertex_u32(*p).unwrap();
+        }
+        for f in faces.iter() {
+            mesh.add_face_u32(f).unwrap();
+        }
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_edges(), 12);
+        assert_eq!(mesh.num_faces(), 6);
+        assert_eq!(mesh.point_u32(6).unwrap(), points[6]);
+        fprop.set(5, 42).unwrap();
+        assert_eq!(fprop.get(5).unwrap(), 42);
+    }
+
+    #[test]
+    fn t_select_coplanar_region() {
+        let mesh = quad_box();
+        for f in mesh.faces() {
+            assert_eq!(mesh.select_coplanar_region(f, 0.1).unwrap(), vec![f]);
+        }
+        let mesh = quad_grid(4, 3);
+        let mut region = mesh.select_coplanar_region(FaceHandle(5), 0.01).unwrap();
+        assert_eq!(region[0], FaceHandle(5));
+        region.sort();
+        assert_eq!(region, mesh.faces().collect::<Vec<_>>());
+        // A wide enough tolerance spreads over the edges of the box.
+        let mesh = quad_box();
+        assert_eq!(
+            mesh.select_coplanar_region(FaceHandle(0), 1.6)
+                .unwrap()
+                .len(),
+            5
+        );
+        assert!(matches!(
+            mesh.select_coplanar_region(FaceHandle(mesh.num_faces() as u32), 0.1),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_property_snapshot() {
+        let mut mesh = quad_box();
+        let snapshot = mesh.points.to_vec().unwrap();
+        assert_eq!(snapshot.len(), 8);
+        for v in (0..8).map(VertexHandle) {
+            let p = mesh.point(v).unwrap();
+            mesh.set_point(v, p * 2.0).unwrap();
+        }
+        assert_ne!(mesh.points.to_vec().unwrap(), snapshot);
+        mesh.points.from_slice(&snapshot).unwrap();
+        for v in 0..8 {
+            assert_eq!(mesh.point_u32(v).unwrap(), snapshot[v as usize]);
+        }
+        assert!(matches!(
+            mesh.points.from_slice(&snapshot[..5]),
+            Err(Error::LengthMismatch(8, 5))
+        ));
+    }
+
+    #[test]
+    fn t_property_with_default() {
+        let mut mesh = quad_grid(1, 1);
+        let sentinel = glam::vec2(-1.0, -1.0);
+        let uvs = mesh.create_vertex_prop_with_default(sentinel);
+        let plain = mesh.create_vertex_prop::<glam::Vec2>();
+        assert_eq!(uvs.default_value(), sentinel);
+        assert_eq!(uvs.get(3).unwrap(), sentinel);
+        let v = mesh.add_vertex_u32(glam::vec3(2.0, 0.0, 0.0)).unwrap();
+        assert_eq!(uvs.get(v).unwrap(), sentinel);
+        assert_eq!(plain.get(v).unwrap(), glam::Vec2::zero());
+        // Faces, edges and halfedges too.
+        let tags = mesh.create_face_prop_with_default(7u32);
+        let creases = mesh.create_edge_prop_with_default(0.5f32);
+        let flags = mesh.create_halfedge_prop_with_default(true);
+        let f = mesh.add_tri_face_u32(1, v, 2).unwrap();
+        assert_eq!(tags.get(f).unwrap(), 7);
+        assert_eq!(creases.get(mesh.num_edges() as u32 - 1).unwrap(), 0.5);
+        assert!(flags.get(mesh.num_halfedges() as u32 - 1).unwrap());
+    }
+
+    #[test]
+    fn t_array_property() {
+        let mut mesh = quad_grid(2, 1);
+        let mut weights = mesh.create_vertex_prop::<[f32; 4]>();
+        assert_eq!(weights.get(0).unwrap(), [0.0; 4]);
+        for v in 0..(mesh.num_vertices() as u32) {
+            let w = v as f32 / 10.0;
+            weights.set(v, [w, 1.0 - w, 0.0, 0.0]).unwrap();
+        }
+        // New vertices get the default, and the values follow edits.
+        mesh.split_face(FaceHandle(0), glam::vec3(0.5, 0.5, 0.0))
+            .unwrap();
+        let last = mesh.num_vertices() as u32 - 1;
+        assert_eq!(weights.get(last).unwrap(), [0.0; 4]);
+        for v in 0..last {
+            let w = v as f32 / 10.0;
+            assert_eq!(weights.get(v).unwrap(), [w, 1.0 - w, 0.0, 0.0]);
+        }
+        let joints = mesh.create_vertex_prop_with_default([u16::MAX; 2]);
+        assert_eq!(joints.get(last).unwrap(), [u16::MAX; 2]);
+    }
+
+    #[test]
+    fn t_property_iter() {
+        let mesh = quad_box();
+        let mut expected = glam::Vec3::zero();
+        for v in 0..(mesh.num_vertices() as u32) {
+            expected += mesh.point_u32(v).unwrap();
+        }
+        let mut count = 0;
+        let mut total = glam::Vec3::zero();
+        for (v, p) in mesh.points.iter().unwrap() {
+            assert_eq!(v, count);
+            total += p;
+            count += 1;
+        }
+        assert_eq!(count, 8);
+        assert_eq!(total, expected);
+        assert_eq!(total, glam::Vec3::splat(4.0));
+    }
+
+    #[test]
+    fn t_transform() {
+        let mut mesh = quad_box();
+        let before = mesh.points.to_vec().unwrap();
+        mesh.transform(glam::Mat4::from_translation(glam::vec3(1.0, 0.0, 0.0)))
+            .unwrap();
+        for (v, p) in before.iter().enumerate() {
+            assert_eq!(
+                mesh.point_u32(v as u32).unwrap(),
+                *p + glam::vec3(1.0, 0.0, 0.0)
+            );
+        }
+        // Normals follow non-uniform scaling correctly.
+        let mut mesh = uv_sphere(1.0, 16, 8);
+        mesh.update_vertex_normals().unwrap();
+        let scale = glam::vec3(1.0, 1.0, 3.0);
+        mesh.transform(glam::Mat4::from_scale(scale)).unwrap();
+        for v in mesh.vertices() {
+            // The ellipsoid x^2 + y^2 + (z / 3)^2 = 1 has the gradient below.
+            let p = mesh.point(v).unwrap();
+            let expected = (p / (scale * scale)).normalize();
+            let n = mesh.vertex_normal(v).unwrap();
+            assert!((n.length() - 1.0).abs() < 1e-5);
+            assert!(n.dot(expected) > 0.99);
+        }
+        let mut prop = mesh.create_vertex_prop::<u32>();
+        prop.map_in_place(|x| *x += 3).unwrap();
+        assert!(prop.to_vec().unwrap().iter().all(|x| *x == 3));
+    }
+
+    #[test]
+    fn t_live_element_iters() {
+        let mut mesh = quad_grid(2, 1);
+        assert!(mesh.faces_u32().eq(0..2));
+        assert!(mesh.vertices_u32().eq(0..6));
+        assert!(mesh.edges_u32().eq(0..7));
+        mesh.delete_face_u32(0, true).unwrap();
+        assert_eq!(mesh.num_faces(), 2);
+        assert!(mesh.faces_u32().eq([1]));
+        // The three boundary edges and the two corners of the deleted face.
+        assert_eq!(mesh.edges_u32().count(), 4);
+        assert_eq!(mesh.vertices_u32().count(), 4);
+        assert!(mesh.vertices_u32().all(|v| !mesh.is_vertex_deleted_u32(v)));
+        mesh.garbage_collection().unwrap();
+        assert!(mesh.faces_u32().eq(0..1));
+    }
+
+    #[test]
+    fn t_typed_handle_iters() {
+        let mut mesh = quad_box();
+        assert!(mesh.vertices().map(u32::from).eq(mesh.vertices_u32()));
+        assert!(mesh.edges().map(u32::from).eq(mesh.edges_u32()));
+        assert!(mesh.faces().map(u32::from).eq(mesh.faces_u32()));
+        for f in mesh.faces() {
+            let fi = u32::from(f);
+            let fv: Vec<VertexHandle> = mesh.fv_ccw_iter(f).collect();
+            assert!(fv
+                .iter()
+                .copied()
+                .map(u32::from)
+                .eq(mesh.fv_ccw_iter_u32(fi)));
+            assert!(mesh
+                .fh_ccw_iter(f)
+                .rev()
+                .map(u32::from)
+                .eq(mesh.fh_ccw_iter_u32(fi).rev()));
+            let fe: Vec<EdgeHandle> = mesh.fe_cw_iter(f).collect();
+            assert!(fe
+                .iter()
+                .copied()
+                .map(u32::from)
+                .eq(mesh.fe_cw_iter_u32(fi)));
+            let ff: Vec<FaceHandle> = mesh.ff_ccw_iter(f).collect();
+            assert!(ff
+                .iter()
+                .copied()
+                .map(u32::from)
+                .eq(mesh.ff_ccw_iter_u32(fi)));
+        }
+        for v in mesh.vertices() {
+            let vi = u32::from(v);
+            let voh: Vec<HalfedgeHandle> = mesh.voh_ccw_iter(v).collect();
+            assert!(voh
+                .iter()
+                .copied()
+                .map(u32::from)
+                .eq(mesh.voh_ccw_iter_u32(vi)));
+            assert!(mesh
+                .vv_cw_iter(v)
+                .map(u32::from)
+                .eq(mesh.vv_cw_iter_u32(vi)));
+            assert!(mesh
+                .vf_ccw_iter(v)
+                .map(u32::from)
+                .eq(mesh.vf_ccw_iter_u32(vi)));
+        }
+        let v = VertexHandle(6);
+        mesh.set_point(v, glam::vec3(1.0, 1.0, 2.0)).unwrap();
+        assert_eq!(mesh.point(v).unwrap(), glam::vec3(1.0, 1.0, 2.0));
+        assert_eq!(mesh.point_u32(6).unwrap(), glam::vec3(1.0, 1.0, 2.0));
+        let mesh = quad_grid(2, 1);
+        let h = mesh.boundary_halfedges().next().unwrap();
+        let boundary: Vec<HalfedgeHandle> = mesh.boundary_loop_iter(h).collect();
+        assert_eq!(boundary.len(), 6);
+        assert!(boundary.iter().all(|h| mesh.is_boundary_halfedge(*h)));
+    }
+
+    #[test]
+    fn t_typed_handle_api() {
+        let mut mesh = Mesh::new();
+        let [a, b, c, d] = [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(1.0, 1.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+        ]
+        .map(|p| mesh.add_vertex(p).unwrap());
+        let f = mesh.add_face(&[a, b, c]).unwrap();
+        let g = mesh.add_tri_face(a, c, d).unwrap();
+        mesh.validate().unwrap();
+        // Walk around the first face.
+        let h = mesh.face_halfedge(f);
+        assert_eq!(mesh.halfedge_face(h), Some(f));
+        let corners = [h, mesh.next_halfedge(h), mesh.prev_halfedge(h)].map(|h| mesh.to_vertex(h));
+        let mut fverts: Vec<_> = mesh.fv_ccw_iter(f).collect();
+        fverts.sort_by_key(|v| v.index());
+        assert_eq!(fverts, [a, b, c]);
+        assert_eq!(corners[2], mesh.from_vertex(h));
+        assert_eq!(mesh.next_halfedge(mesh.prev_halfedge(h)), h);
+        // The diagonal is shared by both faces.
+        let e = mesh.edge_between(a, c).unwrap();
+        assert_eq!(mesh.edge_faces(e), (Some(f), Some(g)));
+        assert!(!mesh.is_boundary_edge(e));
+        assert!(mesh.is_boundary_vertex(a));
+        assert_eq!(mesh.vertex_valence(a), 3);
+        assert!(mesh.vertex_halfedge(a).is_some());
+        assert_eq!(mesh.calc_face_normal(f).unwrap(), glam::Vec3::unit_z());
+        assert_eq!(mesh.calc_face_area(g).unwrap(), 0.5);
+        assert_eq!(mesh.calc_edge_length(e).unwrap(), 2.0f32.sqrt());
+        assert_eq!(mesh.calc_dihedral_angle(e).unwrap(), Some(0.0));
+        // Edits take and return handles too.
+        let v = mesh.split_edge(e, glam::vec3(0.5, 0.5, 0.0)).unwrap();
+        assert_eq!(mesh.point(v).unwrap(), glam::vec3(0.5, 0.5, 0.0));
+        assert_eq!(mesh.vertex_valence(v), 4);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_face_degree_checks() {
+        let mut mesh = quad_box();
+        assert!(mesh.is_quad_mesh());
+        assert!(!mesh.is_triangle_mesh());
+        mesh.triangulate().unwrap();
+        assert!(mesh.is_triangle_mesh());
+        assert!(!mesh.is_quad_mesh());
+        // Mixed.
+        let mut mesh = quad_grid(2, 1);
+        mesh.triangulate_face(0).unwrap();
+        assert!(!mesh.is_triangle_mesh());
+        assert!(!mesh.is_quad_mesh());
+        // Deleted faces don't count.
+        mesh.delete_face_u32(1, true).unwrap();
+        assert!(mesh.is_triangle_mesh());
+    }
+
+    #[test]
+    fn t_is_manifold() {
+        assert!(quad_box().is_manifold());
+        assert!(quad_grid(3, 3).is_manifold());
+        // Two triangles sharing only a vertex.
+        let mut mesh = Mesh::new();
+        for p in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [-1.0, -1.0, 0.0],
+        ] {
+            mesh.add_vertex_u32(glam::Vec3::from(p)).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.add_tri_face_u32(0, 3, 4).unwrap();
+        mesh.validate().unwrap();
+        assert!(!mesh.is_manifold());
+    }
+
+    #[test]
+    fn t_split_nonmanifold_vertices() {
+        // Two triangles sharing only a vertex.
+        let mut mesh = Mesh::new();
+        for p in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [-1.0, -1.0, 0.0],
+        ] {
+            mesh.add_vertex_u32(glam::Vec3::from(p)).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.add_tri_face_u32(0, 3, 4).unwrap();
+        let mut tags = mesh.create_vertex_prop::<u32>();
+        tags.set(0, 7).unwrap();
+        // The face in the fan of the outgoing halfedge of the vertex keeps it.
+        let hv = mesh.vertex_halfedge_u32(0).unwrap();
+        let fkeep = mesh
+            .halfedge_face_u32(mesh.opposite_halfedge_u32(hv))
+            .unwrap();
+        assert_eq!(mesh.boundary_loop_count(), 1);
+        assert_eq!(mesh.split_nonmanifold_vertices().unwrap(), 1);
+        mesh.validate().unwrap();
+        assert!(mesh.is_manifold());
+        assert_eq!(mesh.num_vertices(), 6);
+        assert_eq!(mesh.point_u32(5).unwrap(), mesh.point_u32(0).unwrap());
+        assert_eq!(tags.get(5).unwrap(), 7);
+        assert_eq!(mesh.vertex_valence_u32(0), 2);
+        assert_eq!(mesh.vertex_valence_u32(5), 2);
+        assert!(mesh.is_boundary_vertex_u32(0) && mesh.is_boundary_vertex_u32(5));
+        let mut fverts: Vec<Vec<u32>> = mesh
+            .faces_u32()
+            .map(|f| {
+                let mut vs: Vec<u32> = mesh.fv_ccw_iter_u32(f).collect();
+                vs.sort();
+                vs
+            })
+            .collect();
+        fverts.sort();
+        assert_eq!(
+            fverts,
+            if fkeep == 0 {
+                [vec![0, 1, 2], vec![3, 4, 5]]
+            } else {
+                [vec![0, 3, 4], vec![1, 2, 5]]
+            }
+        );
+        assert!(mesh.fv_ccw_iter_u32(fkeep).any(|v| v == 0));
+        assert!(mesh.fv_ccw_iter_u32(1 - fkeep).any(|v| v == 5));
+        assert_eq!(mesh.boundary_loop_count(), 2);
+        assert_eq!(mesh.num_connected_components(), 2);
+        // Nothing to do on a manifold mesh.
+        assert_eq!(mesh.split_nonmanifold_vertices().unwrap(), 0);
+        let mut grid = quad_grid(2, 2);
+        assert_eq!(grid.split_nonmanifold_vertices().unwrap(), 0);
+        assert_eq!(grid.num_vertices(), 9);
+    }
+
+    #[test]
+    fn t_from_triangle_soup() {
+        // Every triangle of a box has its own copy of its corners.
+        let boxmesh = quad_box();
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        for f in 0..6 {
+            let verts: Vec<u32> = boxmesh.fv_ccw_iter_u32(f).collect();
+            for tri in [
+                [verts[0], verts[1], verts[2]],
+                [verts[0], verts[2], verts[3]],
+            ] {
+                let base = positions.len() as u32;
+                for v in tri {
+                    positions.push(boxmesh.point_u32(v).unwrap());
+                }
+                indices.push([base, base + 1, base + 2]);
+            }
+        }
+        let (mesh, skipped) = Mesh::from_triangle_soup(&positions, &indices, Some(1e-5)).unwrap();
+        assert!(skipped.is_empty());
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_edges(), 18);
+        assert_eq!(mesh.num_faces(), 12);
+        assert!((mesh.volume().unwrap() - 1.0).abs() < 1e-6);
+        // Without welding, it's a soup.
+        let (mesh, skipped) = Mesh::from_triangle_soup(&positions, &indices, None).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(mesh.num_vertices(), 36);
+        assert_eq!(mesh.num_connected_components(), 12);
+        // A third triangle on an edge, and a degenerate triangle, are skipped.
+        let positions = [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(0.0, -1.0, 0.0),
+            glam::vec3(0.0, 0.0, 1.0),
+        ];
+        let indices = [[0, 1, 2], [1, 0, 3], [0, 1, 4], [2, 2, 3]];
+        let (mesh, skipped) = Mesh::from_triangle_soup(&positions, &indices, None).unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(skipped, vec![2, 3]);
+        assert_eq!(mesh.num_faces(), 2);
+        assert!(matches!(
+            Mesh::from_triangle_soup(&positions, &[[0, 1, 5]], None),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_from_triangle_soup_reversed() {
+        let (positions, mut triangles, _) = quad_box().to_triangle_buffers().unwrap();
+        triangles[5].reverse();
+        let (mesh, skipped) = Mesh::from_triangle_soup(&positions, &triangles, None).unwrap();
+        assert!(skipped.is_empty());
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 12);
+        assert!((mesh.volume().unwrap() - 1.0).abs() < 1e-6);
+        // The reversed triangle is the odd one out, even when it comes first.
+        triangles[5].reverse();
+        triangles[0].reverse();
+        let mut reversed = triangles.clone();
+        assert_eq!(orient_polygons(&mut reversed), 1);
+        assert_eq!(
+            reversed[0],
+            [triangles[0][2], triangles[0][1], triangles[0][0]]
+        );
+        // Disconnected pieces are oriented independently.
+        let mut polygons = vec![
+            vec![0, 1, 2],
+            vec![1, 2, 3],
+            vec![4, 5, 6, 7],
+            vec![4, 5, 8],
+        ];
+        assert_eq!(orient_polygons(&mut polygons), 2);
+        assert_eq!(polygons[1], vec![3, 2, 1]);
+        assert_eq!(polygons[3], vec![8, 5, 4]);
+    }
+
+    #[test]
+    fn t_dual() {
+        // The dual of a cube is an octahedron.
+        let mesh = quad_box();
+        let dual = mesh.dual().unwrap();
+        dual.validate().unwrap();
+        assert_eq!(dual.num_vertices(), 6);
+        assert_eq!(dual.num_faces(), 8);
+        assert_eq!(dual.num_edges(), 12);
+        assert!(dual.is_triangle_mesh());
+        assert_eq!(dual.point_u32(5).unwrap(), glam::vec3(0.5, 0.5, 1.0));
+        // Outward facing, like the cube.
+        assert!((dual.volume().unwrap() - 1.0 / 6.0).abs() < 1e-6);
+        // The dual of the dual is a smaller cube.
+        let ddual = dual.dual().unwrap();
+        assert_eq!(ddual.num_vertices(), 8);
+        assert!(ddual.is_quad_mesh());
+        // Only interior vertices get a face.
+        let dual = quad_grid(3, 3).dual().unwrap();
+        dual.validate().unwrap();
+        assert_eq!(dual.num_vertices(), 9);
+        assert_eq!(dual.num_faces(), 4);
+        assert!(dual.is_quad_mesh());
+    }
+
+    #[test]
+    fn t_to_triangle_buffers() {
+        let mut mesh = quad_box();
+        let (positions, triangles, normals) = mesh.to_triangle_buffers().unwrap();
+        assert_eq!(positions.len(), 8);
+        assert_eq!(triangles.len(), 12);
+        assert!(normals.is_none());
+        assert!(triangles
+            .iter()
+            .flatten()
+            .all(|v| (*v as usize) < positions.len()));
+        // The triangles describe the same closed surface.
+        let (copy, skipped) = Mesh::from_triangle_soup(&positions, &triangles, None).unwrap();
+        assert!(skipped.is_empty());
+        assert!((copy.volume().unwrap() - 1.0).abs() < 1e-6);
+        mesh.update_vertex_normals().unwrap();
+        let (_, _, normals) = mesh.to_triangle_buffers().unwrap();
+        let normals = normals.unwrap();
+        assert_eq!(normals.len(), 8);
+        assert_eq!(normals[3], mesh.vertex_normal(VertexHandle(3)).unwrap());
+    }
+
+    #[test]
+    fn t_interpolate_vertex_prop() {
+        let mut mesh = Mesh::new();
+        for p in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            mesh.add_vertex_u32(glam::Vec3::from(p)).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        let mut uv = mesh.create_vertex_prop::<glam::Vec2>();
+        let corners = [
+            glam::vec2(0.1, 0.2),
+            glam::vec2(0.9, 0.3),
+            glam::vec2(0.4, 0.8),
+        ];
+        uv.from_slice(&corners).unwrap();
+        let third = 1.0 / 3.0;
+        let center = mesh
+            .interpolate_vertex_prop(FaceHandle(0), glam::Vec3::splat(third), &uv)
+            .unwrap();
+        let average = (corners[0] + corners[1] + corners[2]) / 3.0;
+        assert!((center - average).length() < 1e-6);
+        // At a corner, the value of that corner.
+        let corner = mesh
+            .interpolate_vertex_prop(FaceHandle(0), glam::vec3(0.0, 1.0, 0.0), &uv)
+            .unwrap();
+        let expected = corners[mesh.fv_ccw_iter_u32(0).nth(1).unwrap() as usize];
+        assert!((corner - expected).length() < 1e-6);
+        // Interpolating positions gives the point.
+        let p = mesh
+            .interpolate_vertex_prop(FaceHandle(0), glam::vec3(0.5, 0.25, 0.25), &mesh.points)
+            .unwrap();
+        let expected = mesh
+            .fv_ccw_iter_u32(0)
+            .zip([0.5, 0.25, 0.25])
+            .fold(glam::Vec3::zero(), |sum, (v, w)| {
+                sum + mesh.point_u32(v).unwrap() * w
+            });
+        assert!((p - expected).length() < 1e-6);
+        let mut quad = quad_grid(1, 1);
+        let w = quad.create_vertex_prop::<f32>();
+        assert!(matches!(
+            quad.interpolate_vertex_prop(FaceHandle(0), glam::Vec3::splat(third), &w),
+            Err(Error::NotATriangle(0))
+        ));
+    }
+
+    #[test]
+    fn t_copy_vertex_properties_from() {
+        let mut src = quad_box();
+        let mut tags = src.create_vertex_prop::<u32>();
+        src.transform(glam::Mat4::from_translation(glam::vec3(1.0, 2.0, 3.0)))
+            .unwrap();
+        for v in 0..8 {
+            tags.set(v, 10 * v).unwrap();
+        }
+        let mut dst = quad_box();
+        let dst_tags = dst.create_vertex_prop::<u32>();
+        let identity: Vec<_> = src.vertices().map(Some).collect();
+        dst.copy_vertex_properties_from(&src, &identity).unwrap();
+        for v in 0..8 {
+            assert_eq!(dst.point_u32(v).unwrap(), src.point_u32(v).unwrap());
+            assert_eq!(dst_tags.get(v).unwrap(), 10 * v);
+        }
+        // Partial, shuffled mapping.
+        let mut dst = quad_box();
+        let dst_tags = dst.create_vertex_prop::<u32>();
+        let mut mapping = vec![None; 8];
+        mapping[0] = Some(VertexHandle(7));
+        mapping[3] = Some(VertexHandle(1));
+        dst.copy_vertex_properties_from(&src, &mapping).unwrap();
+        assert_eq!(dst.point_u32(0).unwrap(), src.point_u32(7).unwrap());
+        assert_eq!(dst.point_u32(3).unwrap(), src.point_u32(1).unwrap());
+        assert_eq!(dst.point_u32(1).unwrap(), glam::vec3(1.0, 0.0, 0.0));
+        assert_eq!(
+            (dst_tags.get(0).unwrap(), dst_tags.get(3).unwrap()),
+            (70, 10)
+        );
+        assert_eq!(dst_tags.get(1).unwrap(), 0);
+        assert!(matches!(
+            dst.copy_vertex_properties_from(&src, &identity[..4]),
+            Err(Error::LengthMismatch(8, 4))
+        ));
+        mapping[5] = Some(VertexHandle(8));
+        assert!(matches!(
+            dst.copy_vertex_properties_from(&src, &mapping),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        // The properties must match in type and order.
+        let mut dst = quad_box();
+        let _dst_tags = dst.create_vertex_prop::<f32>();
+        assert!(matches!(
+            dst.copy_vertex_properties_from(&src, &identity),
+            Err(Error::PropertyMismatch)
+        ));
+        assert_eq!(dst.point_u32(7).unwrap(), glam::vec3(0.0, 1.0, 1.0));
+        let mut dst = quad_box();
+        assert!(matches!(
+            dst.copy_vertex_properties_from(&src, &identity),
+            Err(Error::PropertyMismatch)
+        ));
+    }
+
+    #[test]
+    fn t_copy_vertex_properties_builtin() {
+        let mut src = quad_box();
+        let mut tags = src.create_vertex_prop::<u32>();
+        for v in 0..8 {
+            tags.set(v, 10 * v).unwrap();
+        }
+        // Only the source has normals, created after the user property.
+        src.update_vertex_normals().unwrap();
+        let identity: Vec<_> = src.vertices().map(Some).collect();
+        let mut dst = quad_box();
+        let dst_tags = dst.create_vertex_prop::<u32>();
+        dst.copy_vertex_properties_from(&src, &identity).unwrap();
+        for v in 0..8 {
+            assert_eq!(dst_tags.get(v).unwrap(), 10 * v);
+        }
+        assert!(matches!(
+            dst.vertex_normal(VertexHandle(0)),
+            Err(Error::PropertyDoesNotExist)
+        ));
+        // Only the destination has normals, created before the user property.
+        let mut dst = quad_box();
+        dst.update_vertex_normals().unwrap();
+        let dst_tags = dst.create_vertex_prop::<u32>();
+        let mut src = quad_box();
+        let mut tags = src.create_vertex_prop::<u32>();
+        for v in 0..8 {
+            tags.set(v, 10 * v).unwrap();
+        }
+        src.transform(glam::Mat4::from_translation(glam::vec3(1.0, 2.0, 3.0)))
+            .unwrap();
+        dst.copy_vertex_properties_from(&src, &identity).unwrap();
+        for v in 0..8 {
+            assert_eq!(dst_tags.get(v).unwrap(), 10 * v);
+            assert_eq!(dst.point_u32(v).unwrap(), src.point_u32(v).unwrap());
+        }
+        // Both have normals, so they are copied.
+        src.update_vertex_normals().unwrap();
+        let mapping: Vec<_> = (0..8).map(|v| Some(VertexHandle(7 - v))).collect();
+        dst.copy_vertex_properties_from(&src, &mapping).unwrap();
+        for v in 0..8 {
+            assert_eq!(
+                dst.vertex_normal(VertexHandle(v)).unwrap(),
+                src.vertex_normal(VertexHandle(7 - v)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn t_edge_faces() {
+        let mesh = quad_box();
+        for e in mesh.edges() {
+            let (f0, f1) = mesh.edge_faces(e);
+            assert!(f0.is_some() && f1.is_some() && f0 != f1);
+            assert_eq!(
+                mesh.shared_halfedge(f0.unwrap(), f1.unwrap()),
+                Some(e.halfedge(0))
+            );
+        }
+        let mesh = quad_grid(3, 2);
+        let mut nboundary = 0;
+        for e in 0..(mesh.num_edges() as u32) {
+            match mesh.edge_faces_u32(e) {
+                (Some(_), Some(_)) => {}
+                (Some(_), None) | (None, Some(_)) => nboundary += 1,
+                (None, None) => panic!("Edge {e} has no faces"),
+            }
+        }
+        assert_eq!(nboundary, 10);
+    }
+
+    #[test]
+    fn t_checked_navigation() {
+        let mesh = quad_box();
+        let h = HalfedgeHandle(mesh.num_halfedges() as u32);
+        assert!(matches!(
+            mesh.try_to_vertex(h),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_from_vertex(h),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_next_halfedge(h),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_prev_halfedge(h),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_opposite_halfedge(h),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_halfedge_face(h),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_vertex_halfedge(VertexHandle(8)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.try_face_halfedge(FaceHandle(6)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        for h in (0..h.0).map(HalfedgeHandle) {
+            assert_eq!(mesh.try_to_vertex(h).unwrap(), mesh.to_vertex(h));
+            assert_eq!(mesh.try_from_vertex(h).unwrap(), mesh.from_vertex(h));
+            assert_eq!(mesh.try_next_halfedge(h).unwrap(), mesh.next_halfedge(h));
+            assert_eq!(mesh.try_prev_halfedge(h).unwrap(), mesh.prev_halfedge(h));
+            assert_eq!(mesh.try_halfedge_face(h).unwrap(), mesh.halfedge_face(h));
+        }
+    }
+
+    #[test]
+    fn t_box_manual_face_walk() {
+        let mesh = quad_box();
+        for f in 0..(mesh.num_faces() as u32) {
+            let hstart = mesh.face_halfedge_u32(f);
+            let mut h = hstart;
+            let mut verts = Vec::new();
+            loop {
+                assert_eq!(mesh.halfedge_face_u32(h), Some(f));
+                assert!(!mesh.is_boundary_halfedge_u32(h));
+                assert_eq!(mesh.prev_halfedge_u32(mesh.next_halfedge_u32(h)), h);
+                assert_eq!(
+                    mesh.from_vertex_u32(h),
+                    mesh.to_vertex_u32(mesh.opposite_halfedge_u32(h))
+                );
+                assert_eq!(
+                    mesh.to_vertex_u32(h),
+                    mesh.from_vertex_u32(mesh.next_halfedge_u32(h))
+                );
+                verts.push(mesh.to_vertex_u32(h));
+                h = mesh.next_halfedge_u32(h);
+                if h == hstart {
+                    break;
+                }
+            }
+            assert_eq!(verts.len(), 4);
+            for v in verts {
+                assert!(!mesh.is_boundary_vertex_u32(v));
+                let vh = mesh.vertex_halfedge_u32(v).unwrap();
+                assert_eq!(mesh.from_vertex_u32(vh), v);
+            }
+        }
+    }
+
+    #[test]
+    fn t_error_display() {
+        let errors = [
+            (Error::ReadPropertyFailed, None),
+            (Error::WriteToPropertyFailed, None),
+            (Error::PropertyDoesNotExist, None),
+            (Error::ComplexVertex(3), Some("3")),
+            (Error::ComplexEdge(5), Some("5")),
+            (Error::PatchRelinkingFailed, None),
+            (Error::InvalidOppositeHalfedge(7), Some("7")),
+            (Error::InvalidHalfedgeLink(11), Some("11")),
+            (Error::DisconnectedHalfedges(13), Some("13")),
+            (Error::InvalidFaceLoop(2), Some("2")),
+            (Error::InvalidVertexHalfedge(4), Some("4")),
+            (
+                Error::Io(std::io::Error::other("disk on fire")),
+                Some("disk on fire"),
+            ),
+            (Error::ParseFailed(42, "bad token".to_string()), Some("42")),
+            (Error::DegenerateFace, None),
+            (Error::OutOfBoundsAccess, None),
+            (Error::NotBoundaryHalfedge(17), Some("17")),
+            (Error::BoundaryEdge(31), Some("31")),
+            (Error::NotBoundaryEdge(37), Some("37")),
+            (Error::LengthMismatch(19, 23), Some("23")),
+            (Error::NotATriangle(29), Some("29")),
+            (Error::CapacityExceeded, None),
+            (Error::InvalidPermutation, None),
+            (Error::PropertyMismatch, None),
+            (Error::InvalidLength(-2.5), Some("-2.5")),
+        ];
+        for (err, index) in errors {
+            let msg = err.to_string();
+            assert!(!msg.is_empty());
+            if let Some(index) = index {
+                assert!(msg.contains(index));
+            }
+        }
+        assert_eq!(
+            Error::ComplexEdge(5).to_string(),
+            "edge 5 would become non-manifold"
+        );
+        let boxed: Box<dyn std::error::Error> = Box::new(Error::ComplexVertex(1));
+        assert!(boxed.to_string().contains('1'));
+    }
+
+    #[test]
+    fn t_capacity_exceeded() {
+        super::MAX_ELEMENTS.with(|m| m.set(8));
+        let mut mesh = Mesh::new();
+        for i in 0..8 {
+            mesh.add_vertex_u32(glam::vec3(i as f32, (i % 2) as f32, 0.0))
+                .unwrap();
+        }
+        assert!(matches!(
+            mesh.add_vertex_u32(glam::Vec3::zero()),
+            Err(Error::CapacityExceeded)
+        ));
+        assert_eq!(mesh.num_vertices(), 8);
+        // A quad uses up all 8 halfedges.
+        mesh.add_face_u32(&[0, 1, 3, 2]).unwrap();
+        assert_eq!(mesh.num_halfedges(), 8);
+        // Faces with new edges are rejected without modifying the mesh.
+        assert!(matches!(
+            mesh.add_tri_face_u32(2, 3, 4),
+            Err(Error::CapacityExceeded)
+        ));
+        assert_eq!(mesh.num_faces(), 1);
+        assert_eq!(mesh.num_edges(), 4);
+        mesh.validate().unwrap();
+        super::MAX_ELEMENTS.with(|m| m.set(u32::MAX as usize));
+    }
+
+    #[test]
+    fn t_grid_validate() {
+        quad_grid(5, 7).validate().expect("Grid mesh is invalid");
+    }
+
+    #[test]
+    fn t_box_corrupt_next_halfedge() {
+        let mut mesh = quad_box();
+        let h = mesh.face_halfedge_u32(0);
+        let hnext = mesh.next_halfedge_u32(mesh.next_halfedge_u32(h));
+        mesh.halfedge_mut(h).next = hnext;
+        assert!(matches!(
+            mesh.validate(),
+            Err(Error::InvalidHalfedgeLink(_))
+        ));
+    }
+
+    #[test]
+    fn t_box_corrupt_halfedge_vertex() {
+        let mut mesh = quad_box();
+        let h = mesh.face_halfedge_u32(0);
+        let v = mesh.from_vertex_u32(h);
+        mesh.halfedge_mut(h).vertex = v;
+        assert!(matches!(
+            mesh.validate(),
+            Err(Error::DisconnectedHalfedges(_))
+        ));
+    }
+
+    #[test]
+    fn t_box_corrupt_vertex_halfedge() {
+        let mut mesh = quad_box();
+        let h = mesh.vertex_halfedge_u32(0).unwrap();
+        mesh.vertices[0].halfedge = Some(mesh.opposite_halfedge_u32(h));
+        assert!(matches!(
+            mesh.validate(),
+            Err(Error::InvalidVertexHalfedge(0))
+        ));
+    }
+
+    #[test]
+    fn t_recompute_boundary_status() {
+        let mut mesh = quad_grid(2, 2);
+        let expected: Vec<bool> = mesh
+            .vertices_u32()
+            .map(|v| mesh.is_boundary_vertex_u32(v))
+            .collect();
+        // Point the boundary vertices to interior halfedges, as if they were
+        // relinked without updating the vertices.
+        for v in 0..(mesh.num_vertices() as u32) {
+            let h = mesh
+                .voh_ccw_iter_u32(v)
+                .find(|h| !mesh.is_boundary_halfedge_u32(*h))
+                .unwrap();
+            mesh.vertices[v as usize].halfedge = Some(h);
+        }
+        assert!(mesh.vertices_u32().all(|v| !mesh.is_boundary_vertex_u32(v)));
+        mesh.recompute_boundary_status();
+        assert!(mesh
+            .vertices_u32()
+            .map(|v| mesh.is_boundary_vertex_u32(v))
+            .eq(expected));
+        assert_eq!(mesh.boundary_vertex_count(), 8);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_box_corrupt_face_loop() {
+        let mut mesh = quad_box();
+        let h = mesh.face_halfedge_u32(0);
+        mesh.halfedge_mut(h).face = Some(1);
+        assert!(matches!(mesh.validate(), Err(Error::InvalidFaceLoop(0))));
+    }
+
+    #[test]
+    fn t_box_approx_eq() {
+        let mesh = quad_box();
+        assert!(mesh.approx_eq(&mesh, 0.0));
+        assert!(mesh.approx_eq(&quad_box(), 0.0));
+        let mut moved = quad_box();
+        moved
+            .transform(glam::Mat4::from_translation(glam::vec3(0.0, 0.0, 0.01)))
+            .unwrap();
+        assert!(!mesh.approx_eq(&moved, 1e-3));
+        assert!(mesh.approx_eq(&moved, 0.1));
+        // Same positions, with the bottom face added last.
+        let mut reordered = quad_box();
+        reordered.delete_face_u32(0, false).unwrap();
+        reordered.garbage_collection().unwrap();
+        reordered.add_face_u32(&[0, 3, 2, 1]).unwrap();
+        assert_eq!(reordered.num_faces(), 6);
+        assert!(!mesh.approx_eq(&reordered, 1.0));
+        let mut triangulated = quad_box();
+        triangulated.triangulate().unwrap();
+        assert!(!mesh.approx_eq(&triangulated, 1.0));
+    }
+
+    #[test]
+    fn t_box_triangulate() {
+        let mut mesh = quad_box();
+        mesh.triangulate().expect("Unable to triangulate");
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_edges(), 18);
+        assert_eq!(mesh.num_faces(), 12);
+        mesh.validate().expect("Triangulated box is invalid");
+        for f in 0..(mesh.num_faces() as u32) {
+            assert_eq!(mesh.fv_ccw_iter_u32(f).count(), 3);
+        }
+    }
+
+    #[test]
+    fn t_triangulate_shortest_diagonal() {
+        let quad = |points: [glam::Vec3; 4]| {
+            let mut mesh = Mesh::new();
+            for p in points {
+                mesh.add_vertex_u32(p).unwrap();
+            }
+            mesh.add_face_u32(&[0, 1, 2, 3]).unwrap();
+            mesh
+        };
+        // A sheared quad, with the diagonal 1-3 much shorter than 0-2.
+        let sheared = [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(4.0, 0.0, 0.0),
+            glam::vec3(5.0, 1.0, 0.0),
+            glam::vec3(1.0, 1.0, 0.0),
+        ];
+        let mut fanned = quad(sheared);
+        fanned.triangulate().unwrap();
+        assert!(fanned
+            .edge_between(VertexHandle(0), VertexHandle(2))
+            .is_some());
+        let mut mesh = quad(sheared);
+        mesh.triangulate_shortest_diagonal().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 2);
+        assert!(mesh.is_triangle_mesh());
+        assert!(mesh
+            .edge_between(VertexHandle(1), VertexHandle(3))
+            .is_some());
+        assert!(mesh
+            .edge_between(VertexHandle(0), VertexHandle(2))
+            .is_none());
+        assert_eq!(mesh.calc_face_normal_u32(0).unwrap(), glam::Vec3::unit_z());
+        assert_eq!(mesh.calc_face_normal_u32(1).unwrap(), glam::Vec3::unit_z());
+        // The other way around.
+        let mut mesh = quad([
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(5.0, 1.0, 0.0),
+            glam::vec3(4.0, 2.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+        ]);
+        mesh.triangulate_shortest_diagonal().unwrap();
+        mesh.validate().unwrap();
+        assert!(mesh
+            .edge_between(VertexHandle(0), VertexHandle(2))
+            .is_some());
+        assert!(mesh
+            .edge_between(VertexHandle(1), VertexHandle(3))
+            .is_none());
+    }
+
+    #[test]
+    fn t_triangulate_pentagon() {
+        let mut mesh = Mesh::new();
+        for i in 0..5 {
+            let angle = std::f32::consts::TAU * (i as f32) / 5.0;
+            mesh.add_vertex_u32(glam::vec3(angle.cos(), angle.sin(), 0.0))
+                .unwrap();
+        }
+        mesh.add_face_u32(&[0, 1, 2, 3, 4]).unwrap();
+        mesh.triangulate().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 3);
+        // Fanned from the first vertex, with the original winding.
+        let mut tris: Vec<Vec<u32>> = (0..3)
+            .map(|f| {
+                let mut fv: Vec<_> = mesh.fv_ccw_iter_u32(f).collect();
+                let i = fv.iter().position(|v| *v == 0).unwrap();
+                fv.rotate_left(i);
+                fv
+            })
+            .collect();
+        tris.sort();
+        assert_eq!(tris, vec![vec![0, 1, 2], vec![0, 2, 3], vec![0, 3, 4]]);
+    }
+
+    #[test]
+    fn t_box_euler_characteristic() {
+        let mesh = quad_box();
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert_eq!(mesh.genus(), Some(0));
+    }
+
+    #[test]
+    fn t_grid_genus() {
+        let mesh = quad_grid(4, 3);
+        assert_eq!(mesh.euler_characteristic(), 1);
+        assert_eq!(mesh.boundary_loop_count(), 1);
+        // Only closed meshes have a genus.
+        assert_eq!(mesh.genus(), None);
+    }
+
+    #[test]
+    fn t_euler_characteristic_skips_deleted() {
+        let mut mesh = quad_box();
+        mesh.delete_face_u32(0, true).unwrap();
+        // 8 vertices, 12 edges and 5 faces remain.
+        assert_eq!(mesh.euler_characteristic(), 1);
+        assert_eq!(mesh.genus(), None);
+    }
+
+    #[test]
+    fn t_two_boxes_genus() {
+        let mut mesh = quad_box();
+        mesh.append(&quad_box()).unwrap();
+        assert_eq!(mesh.euler_characteristic(), 4);
+        assert_eq!(mesh.genus(), None);
+    }
+
+    #[test]
+    fn t_boundary_counts() {
+        let mut mesh = quad_grid(3, 3);
+        assert_eq!(mesh.boundary_vertex_count(), 12);
+        assert_eq!(mesh.boundary_edge_count(), 12);
+        assert_eq!(mesh.boundary_loop_count(), 1);
+        // Punch a hole in the middle.
+        mesh.delete_face_u32(4, true).unwrap();
+        assert_eq!(mesh.boundary_vertex_count(), 16);
+        assert_eq!(mesh.boundary_edge_count(), 16);
+        assert_eq!(mesh.boundary_loop_count(), 2);
+        mesh.garbage_collection().unwrap();
+        assert_eq!(mesh.boundary_loop_count(), 2);
+        let mesh = quad_box();
+        assert_eq!(
+            (
+                mesh.boundary_vertex_count(),
+                mesh.boundary_edge_count(),
+                mesh.boundary_loop_count()
+            ),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn t_grid_boundary_loops() {
+        let mut mesh = quad_grid(3, 3);
+        assert_eq!(mesh.boundary_halfedges_u32().count(), 12);
+        assert_eq!(mesh.boundary_loops().len(), 1);
+        // Punch a hole in the middle.
+        mesh.delete_face_u32(4, true).unwrap();
+        assert_eq!(mesh.boundary_halfedges_u32().count(), 16);
+        assert!(mesh
+            .boundary_halfedges_u32()
+            .all(|h| mesh.is_boundary_halfedge_u32(h)));
+        let mut loops = mesh.boundary_loops();
+        loops.sort_by_key(|hs| hs.len());
+        assert_eq!(loops.iter().map(|hs| hs.len()).collect::<Vec<_>>(), [4, 12]);
+        for hs in &loops {
+            for (i, h) in hs.iter().enumerate() {
+                assert!(mesh.is_boundary_halfedge(*h));
+                assert_eq!(mesh.next_halfedge(*h), hs[(i + 1) % hs.len()]);
+            }
+        }
+        // The hole is bounded by the vertices of the deleted face.
+        let mut hole: Vec<_> = loops[0].iter().map(|h| mesh.to_vertex(*h)).collect();
+        hole.sort();
+        assert_eq!(hole, [5, 6, 9, 10].map(VertexHandle));
+        assert!(quad_box().boundary_loops().is_empty());
+    }
+
+    #[test]
+    fn t_torus_genus() {
+        let (nu, nv) = (8u32, 6u32);
+        let mut mesh = Mesh::new();
+        for i in 0..nu {
+            let u = std::f32::consts::TAU * (i as f32) / (nu as f32);
+            for j in 0..nv {
+                let v = std::f32::consts::TAU * (j as f32) / (nv as f32);
+                let r = 2.0 + v.cos();
+                mesh.add_vertex_u32(glam::vec3(r * u.cos(), r * u.sin(), v.sin()))
+                    .unwrap();
+            }
+        }
+        let vi = |i: u32, j: u32| VertexHandle((i % nu) * nv + (j % nv));
+        for i in 0..nu {
+            for j in 0..nv {
+                mesh.add_quad_face(vi(i, j), vi(i + 1, j), vi(i + 1, j + 1), vi(i, j + 1))
+                    .unwrap();
+            }
+        }
+        mesh.validate().unwrap();
+        assert_eq!(mesh.euler_characteristic(), 0);
+        assert_eq!(mesh.genus(), Some(1));
+    }
+
+    #[test]
+    fn t_box_connected_components() {
+        let mesh = quad_box();
+        let labels = mesh.connected_components();
+        assert_eq!(labels.len(), 6);
+        assert!(labels.iter().all(|l| *l == labels[0]));
+        assert_eq!(mesh.num_connected_components(), 1);
+    }
+
+    #[test]
+    fn t_two_triangles_connected_components() {
+        let mut mesh = Mesh::new();
+        for pos in [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(2.0, 0.0, 0.0),
+            glam::vec3(3.0, 0.0, 0.0),
+            glam::vec3(2.0, 1.0, 0.0),
+        ] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.add_tri_face_u32(3, 4, 5).unwrap();
+        let labels = mesh.connected_components();
+        assert_eq!(labels.len(), 2);
+        assert_ne!(labels[0], labels[1]);
+        assert_eq!(mesh.num_connected_components(), 2);
+    }
+
+    #[test]
+    fn t_box_reserve() {
+        let mut mesh = quad_box();
+        mesh.reserve(100, 200, 300).expect("Unable to reserve");
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_edges(), 12);
+        assert_eq!(mesh.num_faces(), 6);
+        assert!(mesh.vertices.capacity() >= 108);
+        assert!(mesh.edges.capacity() >= 212);
+        assert!(mesh.faces.capacity() >= 306);
+        assert!(mesh.points.data.read().unwrap().capacity() >= 108);
+        assert_eq!(mesh.vprops.len().unwrap(), 8);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_box_face_normals() {
+        let mesh = quad_box();
+        let expected = [
+            glam::vec3(0.0, 0.0, -1.0),
+            glam::vec3(0.0, -1.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(-1.0, 0.0, 0.0),
+            glam::vec3(0.0, 0.0, 1.0),
+        ];
+        for (f, e) in expected.iter().enumerate() {
+            let n = mesh.calc_face_normal_u32(f as u32).unwrap();
+            assert!((n - *e).length() < 1e-6);
+        }
+        assert!(matches!(
+            mesh.calc_face_normal_u32(99),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_sphere_vertex_normals() {
+        let mut mesh = uv_sphere(2.0, 16, 12);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.genus(), Some(0));
+        assert!(matches!(
+            mesh.vertex_normal(VertexHandle(0)),
+            Err(Error::PropertyDoesNotExist)
+        ));
+        mesh.update_vertex_normals().unwrap();
+        for v in mesh.vertices() {
+            let n = mesh.vertex_normal(v).unwrap();
+            let p = mesh.point(v).unwrap().normalize();
+            assert!((n.length() - 1.0).abs() < 1e-5);
+            assert!(n.dot(p) > 0.99);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn t_sphere_vertex_normals_par() {
+        let mut mesh = uv_sphere(2.0, 32, 24);
+        mesh.update_vertex_normals().unwrap();
+        let serial: Vec<_> = mesh
+            .vertices()
+            .map(|v| (v, mesh.vertex_normal(v).unwrap()))
+            .collect();
+        mesh.update_vertex_normals_par().unwrap();
+        for (v, s) in serial.iter() {
+            let p = mesh.vertex_normal(*v).unwrap();
+            assert!((p - *s).length() < 1e-6);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn t_par_bounding_box() {
+        use rayon::prelude::*;
+        let mesh = uv_sphere(2.0, 32, 24);
+        let points = mesh.points_snapshot().unwrap();
+        let empty = (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN));
+        let serial = mesh.vertices_u32().fold(empty, |(min, max), v| {
+            let p = points[v as usize];
+            (min.min(p), max.max(p))
+        });
+        let parallel = mesh
+            .par_vertices()
+            .map(|v| (points[v], points[v]))
+            .reduce(|| empty, |(a0, a1), (b0, b1)| (a0.min(b0), a1.max(b1)));
+        assert_eq!(serial, parallel);
+        assert!((serial.1 - glam::Vec3::splat(2.0)).max_element() < 1e-5);
+        // Faces too: the centroids lie within the same box.
+        let centroids: Vec<glam::Vec3> = mesh
+            .par_faces()
+            .map(|f| {
+                let (sum, n) = mesh
+                    .fv_ccw_iter(f)
+                    .fold((glam::Vec3::zero(), 0.0), |(sum, n), v| {
+                        (sum + points[v], n + 1.0)
+                    });
+                sum / n
+            })
+            .collect();
+        assert_eq!(centroids.len(), mesh.num_faces());
+        assert!(centroids
+            .iter()
+            .all(|c| c.cmpge(serial.0).all() && c.cmple(serial.1).all()));
+    }
+
+    #[test]
+    fn t_send_mesh_to_thread() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Mesh>();
+        assert_send_sync::<Property<glam::Vec3>>();
+        let mesh = quad_box();
+        let expected = mesh.point_u32(6).unwrap();
+        let point = std::thread::spawn(move || mesh.point_u32(6).unwrap())
+            .join()
+            .unwrap();
+        assert_eq!(point, expected);
+    }
+
+    #[test]
+    fn t_box_corner_normals() {
+        let mut mesh = quad_box();
+        let points = mesh.points_snapshot().unwrap();
+        assert!(matches!(
+            mesh.halfedge_normal(HalfedgeHandle(0)),
+            Err(Error::PropertyDoesNotExist)
+        ));
+        mesh.split_normals_by_angle(std::f32::consts::FRAC_PI_4)
+            .unwrap();
+        for h in 0..(mesh.num_halfedges() as u32) {
+            let f = mesh.halfedge_face_u32(h).unwrap();
+            let fnormal = mesh.face_normal_from(f, &points);
+            // Every corner of a box is a right angle.
+            let expected = fnormal * std::f32::consts::FRAC_PI_2;
+            let h = HalfedgeHandle(h);
+            assert!((mesh.calc_corner_normal(h).unwrap() - expected).length() < 1e-6);
+            // Every edge of a box is sharp, so the shading is flat.
+            assert!((mesh.halfedge_normal(h).unwrap() - fnormal).length() < 1e-6);
+        }
+        // With a large enough threshold, the corners blend into the vertex
+        // normal.
+        mesh.split_normals_by_angle(std::f32::consts::FRAC_PI_2 + 0.1)
+            .unwrap();
+        let expected = glam::Vec3::one().normalize();
+        for h in mesh.vih_ccw_iter(VertexHandle(6)) {
+            assert!((mesh.halfedge_normal(h).unwrap() - expected).length() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn t_sphere_corner_normals() {
+        let mut mesh = uv_sphere(1.0, 32, 16);
+        mesh.split_normals_by_angle(std::f32::consts::FRAC_PI_3)
+            .unwrap();
+        for v in mesh.vertices() {
+            let radial = mesh.point(v).unwrap().normalize();
+            let expected = mesh
+                .vih_ccw_iter(v)
+                .map(|h| mesh.calc_corner_normal(h).unwrap())
+                .fold(glam::Vec3::zero(), |total, n| total + n)
+                .normalize();
+            assert!(expected.dot(radial) > 0.999);
+            for h in mesh.vih_ccw_iter(v) {
+                // All the corners at a vertex blend into the angle weighted
+                // normal, which is not the normal of any of the faces.
+                let n = mesh.halfedge_normal(h).unwrap();
+                assert!((n - expected).length() < 1e-5);
+                assert!(n.dot(mesh.calc_corner_normal(h).unwrap().normalize()) < 0.9999);
+            }
+        }
+    }
+
+    #[test]
+    fn t_select_in_box() {
+        let mesh = quad_grid(4, 4);
+        let min = glam::vec3(0.5, 0.5, -1.0);
+        let max = glam::vec3(3.0, 2.0, 1.0);
+        let verts = mesh.vertices_in_box(min, max).unwrap();
+        // x in 1..=3 and y in 1..=2.
+        assert_eq!(verts.len(), 6);
+        assert!(verts.iter().all(|v| {
+            let p = mesh.point(*v).unwrap();
+            p.x() >= 1.0 && p.x() <= 3.0 && p.y() >= 1.0 && p.y() <= 2.0
+        }));
+        let faces = mesh.faces_in_box(min, max).unwrap();
+        assert_eq!(faces.len(), 2);
+        assert_eq!(faces, vec![FaceHandle(5), FaceHandle(6)]);
+        assert!(mesh
+            .faces_in_box(glam::vec3(-1.0, -1.0, -1.0), glam::vec3(0.5, 0.5, 1.0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn t_center_and_normalize() {
+        let mut mesh = quad_box();
+        mesh.transform(
+            glam::Mat4::from_translation(glam::vec3(3.0, -2.0, 5.0))
+                * glam::Mat4::from_scale(glam::vec3(4.0, 2.0, 1.0)),
+        )
+        .unwrap();
+        mesh.center_at_origin().unwrap();
+        let (min, max) = mesh.bounds().unwrap().unwrap();
+        assert_eq!(min, glam::vec3(-2.0, -1.0, -0.5));
+        assert_eq!(max, glam::vec3(2.0, 1.0, 0.5));
+        mesh.transform(glam::Mat4::from_translation(glam::vec3(1.0, 1.0, 1.0)))
+            .unwrap();
+        mesh.normalize_to_unit().unwrap();
+        let (min, max) = mesh.bounds().unwrap().unwrap();
+        assert!((min + max).length() < 1e-6);
+        assert!(((max - min).max_element() - 1.0).abs() < 1e-6);
+        assert!(((max - min) - glam::vec3(1.0, 0.5, 0.25)).length() < 1e-6);
+        // Empty meshes are left alone.
+        let mut empty = Mesh::new();
+        empty.center_at_origin().unwrap();
+        empty.normalize_to_unit().unwrap();
+    }
+
+    #[test]
+    fn t_morton_order() {
+        let mut mesh = quad_grid(8, 8);
+        let order = mesh.morton_order().unwrap();
+        assert_eq!(order.len(), mesh.num_vertices());
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert!(sorted.iter().copied().eq(mesh.vertices()));
+        assert_eq!(&order[..4], &[0, 1, 9, 10].map(VertexHandle));
+        // Within the 8 x 8 vertices away from the far sides, every run of 4
+        // in the order is a 2 x 2 block, and every run of 16 is a 4 x 4 block.
+        let points = mesh.points_snapshot().unwrap();
+        let inner: Vec<glam::Vec3> = order
+            .iter()
+            .map(|v| points[*v])
+            .filter(|p| p.x() < 8.0 && p.y() < 8.0)
+            .collect();
+        assert_eq!(inner.len(), 64);
+        for (size, run) in [(1.0, 4), (3.0, 16)] {
+            for block in inner.chunks(run) {
+                let (min, max) = block.iter().fold((block[0], block[0]), |(min, max), p| {
+                    (min.min(*p), max.max(*p))
+                });
+                assert_eq!(max - min, glam::vec3(size, size, 0.0));
+            }
+        }
+        mesh.reorder_vertices(&order).unwrap();
+        mesh.validate().unwrap();
+        assert!(Mesh::new().morton_order().unwrap().is_empty());
+    }
+
+    #[test]
+    fn t_grid_closest_point() {
+        let mesh = quad_grid(3, 3);
+        let (f, pt, dsq) = mesh
+            .closest_point(glam::vec3(1.5, 1.25, 2.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(f, FaceHandle(4));
+        assert!((pt - glam::vec3(1.5, 1.25, 0.0)).length() < 1e-6);
+        assert!((dsq - 4.0).abs() < 1e-6);
+        // Outside the grid, the foot of the perpendicular is clamped to the
+        // boundary.
+        let (f, pt, dsq) = mesh
+            .closest_point(glam::vec3(-1.0, 0.5, 1.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(f, FaceHandle(0));
+        assert!((pt - glam::vec3(0.0, 0.5, 0.0)).length() < 1e-6);
+        assert!((dsq - 2.0).abs() < 1e-6);
+        assert!(Mesh::new()
+            .closest_point(glam::Vec3::zero())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn t_box_append() {
+        let mut mesh = quad_box();
+        let other = quad_box();
+        mesh.append(&other).expect("Unable to append");
+        assert_eq!(mesh.num_vertices(), 16);
+        assert_eq!(mesh.num_edges(), 24);
+        assert_eq!(mesh.num_faces(), 12);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_connected_components(), 2);
+        for v in 0..8 {
+            assert_eq!(mesh.point_u32(v + 8).unwrap(), other.point_u32(v).unwrap());
+        }
+        for f in 0..6 {
+            assert!(mesh
+                .fv_ccw_iter_u32(f + 6)
+                .eq(other.fv_ccw_iter_u32(f).map(|v| v + 8)));
+            assert!(mesh.ff_ccw_iter_u32(f + 6).all(|nf| nf >= 6));
+        }
+    }
+
+    #[test]
+    fn t_weld_two_triangles() {
+        let mut mesh = Mesh::new();
+        for pos in [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(1.0 + 1e-4, 0.0, 0.0),
+            glam::vec3(1.0, 1.0, 0.0),
+        ] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.add_tri_face_u32(3, 4, 5).unwrap();
+        assert_eq!(mesh.num_edges(), 6);
+        assert_eq!(mesh.weld_vertices(1e-3).unwrap(), 2);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_edges(), 5);
+        assert_eq!(mesh.num_faces(), 2);
+        assert_eq!(mesh.point_u32(3).unwrap(), glam::vec3(1.0, 1.0, 0.0));
+        let h = mesh.find_halfedge(1, 2).unwrap();
+        assert!(!mesh.is_boundary_halfedge_u32(h));
+        assert!(!mesh.is_boundary_halfedge_u32(mesh.opposite_halfedge_u32(h)));
+        // Nothing left to weld.
+        assert_eq!(mesh.weld_vertices(1e-3).unwrap(), 0);
+    }
+
+    #[test]
+    fn t_weld_skips_deleted() {
+        let mut mesh = Mesh::new();
+        for pos in [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(1.0 + 1e-5, 0.0, 0.0),
+            glam::vec3(1.0, 1.0, 0.0),
+            glam::vec3(0.0, 0.0, 1.0),
+            glam::vec3(1.0, 0.0, 1.0),
+            glam::vec3(1.0, 1.0, 1.0),
+        ] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.add_tri_face_u32(3, 4, 5).unwrap();
+        mesh.add_tri_face_u32(6, 7, 8).unwrap();
+        mesh.delete_face_u32(2, true).unwrap();
+        // A deleted vertex coincident with a live one is not welded to it.
+        mesh.set_point(VertexHandle(6), glam::vec3(0.0, 0.0, 0.0))
+            .unwrap();
+        assert_eq!(mesh.weld_vertices(1e-4).unwrap(), 2);
+        mesh.validate().unwrap();
+        assert_eq!(
+            (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces()),
+            (4, 5, 2)
+        );
+        assert!(mesh.faces_u32().eq(0..2));
+        assert_eq!(mesh.point_u32(0).unwrap(), glam::Vec3::zero());
+        assert_eq!(mesh.point_u32(3).unwrap(), glam::vec3(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn t_weld_drops_degenerate_faces() {
+        let mut mesh = quad_grid(2, 1);
+        // Move a vertex of the second quad onto its neighbor, so that quad
+        // collapses into a triangle, and add a sliver triangle that collapses
+        // completely.
+        let mut mesh2 = Mesh::new();
+        for pos in [
+            glam::vec3(5.0, 0.0, 0.0),
+            glam::vec3(5.0, 0.0, 0.0),
+            glam::vec3(5.0, 1.0, 0.0),
+        ] {
+            mesh2.add_vertex_u32(pos).unwrap();
+        }
+        mesh2.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.append(&mesh2).unwrap();
+        mesh.points.set(5, glam::vec3(2.0, 0.0, 0.0)).unwrap();
+        assert_eq!(mesh.num_faces(), 3);
+        assert_eq!(mesh.weld_vertices(1e-3).unwrap(), 2);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 2);
+        assert_eq!(mesh.fv_ccw_iter_u32(1).count(), 3);
+    }
+
+    #[test]
+    fn t_box_dihedral_angle() {
+        let mesh = quad_box();
+        for e in mesh.edges() {
+            let angle = mesh.calc_dihedral_angle(e).unwrap().unwrap();
+            assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn t_concave_dihedral_angle() {
+        // Two faces folded upwards along the y-axis, like an open book.
+        let mut mesh = Mesh::new();
+        for pos in [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+            glam::vec3(1.0, 0.0, 1.0),
+            glam::vec3(1.0, 1.0, 1.0),
+            glam::vec3(-1.0, 0.0, 1.0),
+            glam::vec3(-1.0, 1.0, 1.0),
+        ] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        mesh.add_face_u32(&[0, 2, 3, 1]).unwrap();
+        mesh.add_face_u32(&[0, 1, 5, 4]).unwrap();
+        let e = mesh.edge_between(VertexHandle(0), VertexHandle(1)).unwrap();
+        let angle = mesh.calc_dihedral_angle(e).unwrap().unwrap();
+        assert!((angle + std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn t_grid_dihedral_angle() {
+        let mesh = quad_grid(3, 3);
+        for e in mesh.edges() {
+            let angle = mesh.calc_dihedral_angle(e).unwrap();
+            if mesh.is_boundary_edge(e) {
+                assert!(angle.is_none());
+            } else {
+                assert!(angle.unwrap().abs() < 1e-6);
+            }
+        }
+        assert!(matches!(
+            mesh.calc_dihedral_angle(EdgeHandle(99)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_box_surface_area() {
+        let mesh = quad_box();
+        for f in mesh.faces() {
+            assert!((mesh.calc_face_area(f).unwrap() - 1.0).abs() < 1e-6);
+        }
+        assert!((mesh.surface_area().unwrap() - 6.0).abs() < 1e-6);
+        assert!((quad_grid(3, 4).surface_area().unwrap() - 12.0).abs() < 1e-6);
+        assert!(matches!(
+            mesh.calc_face_area(FaceHandle(99)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_deleted_face_ignored() {
+        let mut mesh = quad_grid(2, 1);
+        mesh.delete_face_u32(0, true).unwrap();
+        assert!(mesh.is_vertex_deleted_u32(0) && mesh.is_vertex_deleted_u32(3));
+        assert!((mesh.surface_area().unwrap() - 1.0).abs() < 1e-6);
+        assert_eq!(mesh.num_connected_components(), 1);
+        assert_eq!(mesh.connected_components(), [u32::MAX, 0]);
+        let (f, _, _) = mesh
+            .closest_point(glam::vec3(0.0, 0.0, 1.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(f, FaceHandle(1));
+        mesh.update_vertex_normals().unwrap();
+        assert_eq!(
+            mesh.vertex_normal(VertexHandle(0)).unwrap(),
+            glam::Vec3::zero()
+        );
+        assert_eq!(
+            mesh.vertex_normal(VertexHandle(1)).unwrap(),
+            glam::Vec3::unit_z()
+        );
+        let mut copy = Mesh::new();
+        copy.append(&mesh).unwrap();
+        copy.validate().unwrap();
+        assert_eq!((copy.num_vertices(), copy.num_faces()), (4, 1));
+        assert!((copy.surface_area().unwrap() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn t_face_planarity() {
+        let mut mesh = quad_box();
+        for f in mesh.faces() {
+            assert!(mesh.face_planarity(f).unwrap() < 1e-6);
+        }
+        // Lift one corner of the top face.
+        mesh.set_point(VertexHandle(6), glam::vec3(1.0, 1.0, 1.4))
+            .unwrap();
+        let warped = mesh.face_planarity(FaceHandle(5)).unwrap();
+        assert!(warped > 0.05);
+        // Each corner is 0.1 away from the plane through the centroid.
+        let edges: f32 = (0..4)
+            .map(|i| {
+                (mesh.point_u32(4 + (i + 1) % 4).unwrap() - mesh.point_u32(4 + i).unwrap()).length()
+            })
+            .sum();
+        let normal = mesh.calc_face_normal_u32(5).unwrap();
+        assert!((warped - 4.0 * 0.1 * normal.z() / edges).abs() < 1e-5);
+        // Scaling doesn't change the measure.
+        mesh.transform(glam::Mat4::from_scale(glam::Vec3::splat(3.0)))
+            .unwrap();
+        assert!((mesh.face_planarity(FaceHandle(5)).unwrap() - warped).abs() < 1e-5);
+        assert!(tri_grid(1, 1).face_planarity(FaceHandle(0)).unwrap() < 1e-6);
+        assert!(matches!(
+            mesh.face_planarity(FaceHandle(6)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_pentagon_normal_area() {
+        use std::f32::consts::TAU;
+        let rot = glam::Mat4::from_rotation_x(0.7) * glam::Mat4::from_rotation_y(-0.3);
+        let mut mesh = Mesh::new();
+        for i in 0..5 {
+            let angle = TAU * (i as f32) / 5.0;
+            mesh.add_vertex_u32(rot.transform_point3(glam::vec3(angle.cos(), angle.sin(), 0.0)))
+                .unwrap();
+        }
+        let f = mesh.add_face(&[0, 1, 2, 3, 4].map(VertexHandle)).unwrap();
+        let normal = rot.transform_vector3(glam::Vec3::unit_z());
+        assert!((mesh.calc_face_normal(f).unwrap() - normal).length() < 1e-5);
+        // Area of a regular pentagon with a unit circumradius.
+        let area = 2.5 * (TAU / 5.0).sin();
+        assert!((mesh.calc_face_area(f).unwrap() - area).abs() < 1e-5);
+        assert_eq!(mesh.face_valence(f), 5);
+    }
+
+    #[test]
+    fn t_non_convex_face_area() {
+        // An L shaped face in a tilted plane, starting at a vertex that can't
+        // see the whole face, so a fan of triangles from it folds back.
+        let corners = [
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+            (0.0, 0.0),
+        ];
+        let rot = glam::Mat4::from_rotation_x(0.7) * glam::Mat4::from_rotation_y(-0.3);
+        let points: Vec<glam::Vec3> = corners
+            .iter()
+            .map(|(x, y)| rot.transform_point3(glam::vec3(*x, *y, 0.0)))
+            .collect();
+        let mut mesh = Mesh::new();
+        for p in &points {
+            mesh.add_vertex_u32(*p).unwrap();
+        }
+        let f = mesh
+            .add_face(&[0, 1, 2, 3, 4, 5].map(VertexHandle))
+            .unwrap();
+        assert_eq!(mesh.fv_ccw_iter(f).next(), Some(VertexHandle(0)));
+        let naive: f32 = (1..5)
+            .map(|i| {
+                (points[i] - points[0])
+                    .cross(points[i + 1] - points[0])
+                    .length()
+                    * 0.5
+            })
+            .sum();
+        assert!((naive - 4.0).abs() < 1e-5);
+        let area = mesh.calc_face_area_signed(f).unwrap();
+        assert!((area - 3.0).abs() < 1e-5);
+        assert!((area - mesh.calc_face_area(f).unwrap()).abs() < 1e-5);
+        assert!((quad_box().calc_face_area_signed(FaceHandle(2)).unwrap() - 1.0).abs() < 1e-6);
+        assert!(matches!(
+            mesh.calc_face_area_signed(FaceHandle(99)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_box_edge_length() {
+        let mesh = quad_box();
+        for e in 0..(mesh.num_edges() as u32) {
+            assert!((mesh.calc_edge_length_u32(e).unwrap() - 1.0).abs() < 1e-6);
+        }
+        assert!(matches!(
+            mesh.calc_edge_length_u32(99),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_box_volume() {
+        let mesh = quad_box();
+        assert!((mesh.volume().unwrap() - 1.0).abs() < 1e-6);
+        // Same box with the winding of all faces reversed.
+        let mut reversed = Mesh::new();
+        for v in 0..(mesh.num_vertices() as u32) {
+            reversed.add_vertex_u32(mesh.point_u32(v).unwrap()).unwrap();
+        }
+        for f in 0..(mesh.num_faces() as u32) {
+            let mut fverts: Vec<_> = mesh.fv_ccw_iter_u32(f).collect();
+            fverts.reverse();
+            reversed.add_face_u32(&fverts).unwrap();
+        }
+        assert!((reversed.volume().unwrap() + 1.0).abs() < 1e-6);
+        let sphere = uv_sphere(1.0, 64, 48);
+        let expected = 4.0 * std::f32::consts::PI / 3.0;
+        assert!((sphere.volume().unwrap() - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn t_add_degenerate_face() {
+        let mut mesh = Mesh::new();
+        for pos in [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 1.0, 0.0),
+        ] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        assert!(matches!(
+            mesh.add_face_u32(&[0, 0, 1]),
+            Err(Error::DegenerateFace)
+        ));
+        assert!(matches!(
+            mesh.add_face_u32(&[0, 1]),
+            Err(Error::DegenerateFace)
+        ));
+        assert!(matches!(
+            mesh.add_face_u32(&[0, 1, 2, 1]),
+            Err(Error::DegenerateFace)
+        ));
+        assert!(matches!(mesh.add_face_u32(&[]), Err(Error::DegenerateFace)));
+        assert_eq!(mesh.num_edges(), 0);
+        assert_eq!(mesh.num_faces(), 0);
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_add_face_with_halfedges() {
+        let mut mesh = quad_grid(1, 1);
+        for pos in [glam::vec3(2.0, 0.0, 0.0), glam::vec3(2.0, 1.0, 0.0)] {
+            mesh.add_vertex_u32(pos).unwrap();
+        }
+        let mut halfedges = vec![HalfedgeHandle(42)];
+        let verts = [1, 4, 5, 3].map(VertexHandle);
+        let f = mesh
+            .add_face_with_halfedges(&verts, &mut halfedges)
+            .unwrap();
+        assert_eq!(halfedges.len(), 4);
+        for (i, h) in halfedges.iter().enumerate() {
+            assert_eq!(mesh.halfedge_face(*h), Some(f));
+            assert_eq!(mesh.from_vertex(*h), verts[i]);
+            assert_eq!(mesh.to_vertex(*h), verts[(i + 1) % 4]);
+        }
+        // The halfedge shared with the existing face is reused.
+        let shared = mesh.opposite_halfedge(halfedges[3]);
+        assert_eq!(mesh.halfedge_face(shared), Some(FaceHandle(0)));
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_box_add_faces() {
+        let mut mesh = Mesh::new();
+        for v in 0..8 {
+            mesh.add_vertex_u32(quad_box().point_u32(v).unwrap())
+                .unwrap();
+        }
+        let faces = [
+            [0, 3, 2, 1],
+            [0, 1, 5, 4],
+            [1, 2, 6, 5],
+            [2, 3, 7, 6],
+            [3, 0, 4, 7],
+            [4, 5, 6, 7],
+        ]
+        .map(|f| f.map(VertexHandle));
+        let slices: Vec<&[VertexHandle]> = faces.iter().map(|f| &f[..]).collect();
+        assert!(mesh
+            .add_faces(&slices)
+            .unwrap()
+            .into_iter()
+            .eq(mesh.faces()));
+        mesh.validate().unwrap();
+        assert_eq!(
+            (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces()),
+            (8, 12, 6)
+        );
+        for (f, verts) in mesh.faces().zip(faces) {
+            assert!(mesh.fv_ccw_iter(f).eq(verts));
+        }
+        // The faces before the failing one are kept.
+        let mut mesh = quad_grid(1, 1);
+        mesh.add_vertex_u32(glam::vec3(2.0, 0.0, 0.0)).unwrap();
+        let (added, err) = mesh
+            .add_faces(&[&[1, 4, 3].map(VertexHandle), &[0, 0, 1].map(VertexHandle)])
+            .unwrap_err();
+        assert!(matches!(err, Error::DegenerateFace));
+        assert_eq!(added, [FaceHandle(1)]);
+        assert_eq!(mesh.num_faces(), 2);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_box_shared_halfedge() {
+        let mesh = quad_box();
+        let [f0, f1, f2, f3, f4, f5] = [0, 1, 2, 3, 4, 5].map(FaceHandle);
+        let h = mesh.shared_halfedge(f0, f1).unwrap();
+        assert_eq!(mesh.halfedge_face(h), Some(f0));
+        assert_eq!(mesh.halfedge_face(mesh.opposite_halfedge(h)), Some(f1));
+        assert_eq!(
+            (mesh.from_vertex(h), mesh.to_vertex(h)),
+            (VertexHandle(1), VertexHandle(0))
+        );
+        let oh = mesh.shared_halfedge(f1, f0).unwrap();
+        assert_eq!(oh, mesh.opposite_halfedge(h));
+        // Opposite sides of the box.
+        assert_eq!(mesh.shared_halfedge(f0, f5), None);
+        assert_eq!(mesh.shared_halfedge(f1, f3), None);
+        assert_eq!(mesh.shared_halfedge(f2, f4), None);
+        assert_eq!(mesh.shared_halfedge(f2, f2), None);
+    }
+
+    #[test]
+    fn t_vertex_one_ring() {
+        let mesh = quad_box();
+        let mut ring = vec![VertexHandle(42); 10];
+        let mut faces = Vec::new();
+        for v in mesh.vertices() {
+            mesh.vertex_one_ring(v, &mut ring);
+            assert!(ring.iter().copied().eq(mesh.vv_ccw_iter(v)));
+            assert_eq!(ring.len(), 3);
+            mesh.vertex_one_ring_faces(v, &mut faces);
+            assert!(faces.iter().copied().eq(mesh.vf_ccw_iter(v)));
+            assert_eq!(faces.len(), 3);
+        }
+        // Fewer neighbors than the previous call.
+        let grid = quad_grid(2, 2);
+        grid.vertex_one_ring(VertexHandle(4), &mut ring);
+        assert_eq!(ring.len(), 4);
+        grid.vertex_one_ring(VertexHandle(0), &mut ring);
+        let mut sorted = ring.clone();
+        sorted.sort();
+        assert_eq!(sorted, [1, 3].map(VertexHandle));
+        grid.vertex_one_ring_faces(VertexHandle(0), &mut faces);
+        assert_eq!(faces, [FaceHandle(0)]);
+    }
+
+    #[test]
+    fn t_box_face_adjacency_list() {
+        let mesh = quad_box();
+        let adjacency = mesh.face_adjacency_list();
+        assert_eq!(adjacency.len(), 6);
+        for (f, neighbors) in mesh.faces().zip(adjacency.iter()) {
+            assert_eq!(neighbors.len(), 4);
+            let mut edges: Vec<_> = neighbors.iter().map(|(_, e)| *e).collect();
+            edges.sort();
+            edges.dedup();
+            assert_eq!(edges.len(), 4);
+            for (nf, e) in neighbors {
+                assert_ne!(*nf, f);
+                assert_eq!(mesh.shared_halfedge(f, *nf).map(|h| h.edge()), Some(*e));
+                // Symmetric.
+                assert!(adjacency[*nf].contains(&(f, *e)));
+            }
+        }
+        let mut grid = quad_grid(2, 1);
+        let e = grid.edge_between(VertexHandle(1), VertexHandle(4)).unwrap();
+        assert_eq!(
+            grid.face_adjacency_list(),
+            [vec![(FaceHandle(1), e)], vec![(FaceHandle(0), e)]]
+        );
+        grid.delete_face_u32(1, true).unwrap();
+        assert_eq!(grid.face_adjacency_list(), [vec![], vec![]]);
+    }
+
+    #[test]
+    fn t_box_adjacency() {
+        let mesh = quad_box();
+        // Bottom, top and vertical edges.
+        let edges = (0..4u32).flat_map(|i| {
+            let j = (i + 1) % 4;
+            [(i, j), (i + 4, j + 4), (i, i + 4)]
+        });
+        for (a, b) in edges.map(|(a, b)| (VertexHandle(a), VertexHandle(b))) {
+            assert!(mesh.are_adjacent(a, b));
+            assert!(mesh.are_adjacent(b, a));
+            let e = mesh.edge_between(a, b).unwrap();
+            assert_eq!(mesh.edge_between(b, a), Some(e));
+            let h = e.halfedge(0);
+            let mut verts = [mesh.from_vertex(h), mesh.to_vertex(h)];
+            verts.sort();
+            assert_eq!(verts, [a.min(b), a.max(b)]);
+        }
+        // Face and body diagonals.
+        let diagonals = [(0, 2), (1, 3), (4, 6), (0, 5), (1, 6), (0, 6), (3, 5)];
+        for (a, b) in diagonals.map(|(a, b)| (VertexHandle(a), VertexHandle(b))) {
+            assert!(!mesh.are_adjacent(a, b));
+            assert_eq!(mesh.edge_between(a, b), None);
+        }
+    }
+
+    #[test]
+    fn t_grid_topological_path() {
+        let (nx, ny) = (5u32, 3u32);
+        let mesh = quad_grid(nx, ny);
+        let first = VertexHandle(0);
+        let last = VertexHandle((nx + 1) * (ny + 1) - 1);
+        let path = mesh.topological_path(first, last).unwrap();
+        assert_eq!(path.len() as u32 - 1, nx + ny);
+        assert_eq!(path.first(), Some(&first));
+        assert_eq!(path.last(), Some(&last));
+        for pair in path.windows(2) {
+            assert!(mesh.vv_ccw_iter(pair[0]).any(|v| v == pair[1]));
+        }
+        let v = VertexHandle(7);
+        assert_eq!(mesh.topological_path(v, v), Some(vec![v]));
+    }
+
+    #[test]
+    fn t_disconnected_topological_path() {
+        let mut mesh = quad_box();
+        mesh.append(&quad_box()).unwrap();
+        let [a, b, c] = [0, 6, 9].map(VertexHandle);
+        assert!(mesh.topological_path(a, b).is_some());
+        assert!(mesh.topological_path(a, c).is_none());
+    }
+
+    #[test]
+    fn t_triangulate_copies_face_properties() {
+        let mut mesh = quad_grid(2, 1);
+        let mut material: Property<u32> = mesh.create_face_prop();
+        material.set(0, 7).unwrap();
+        material.set(1, 3).unwrap();
+        mesh.triangulate().unwrap();
+        assert_eq!(mesh.num_faces(), 4);
+        for f in 0..(mesh.num_faces() as u32) {
+            let fverts: Vec<_> = mesh.fv_ccw_iter_u32(f).collect();
+            // Vertices 0 and 3 only belong to the first quad, and every
+            // triangle of that quad has at least one of them.
+            let expected = if fverts.contains(&0) || fverts.contains(&3) {
+                7
+            } else {
+                3
+            };
+            assert_eq!(material.get(f).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn t_dropped_property() {
+        let mut mesh = quad_box();
+        {
+            let prop: Property<f32> = mesh.create_vertex_prop();
+            assert_eq!(prop.get(7).unwrap(), 0.0);
+        }
+        let v = mesh.add_vertex_u32(glam::vec3(2.0, 2.0, 2.0)).unwrap();
+        assert_eq!(mesh.point_u32(v).unwrap(), glam::vec3(2.0, 2.0, 2.0));
+        let prop: Property<f32> = mesh.create_vertex_prop();
+        assert_eq!(mesh.vprops.len().unwrap(), 9);
+        assert_eq!(prop.get(8).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn t_box_vertex_prop_access() {
+        let mut mesh = quad_box();
+        let mut uv: Property<glam::Vec2> = mesh.create_vertex_prop();
+        mesh.set_vertex_prop(&mut uv, VertexHandle(3), glam::vec2(0.25, 0.75))
+            .unwrap();
+        assert_eq!(
+            mesh.get_vertex_prop(&uv, VertexHandle(3)).unwrap(),
+            glam::vec2(0.25, 0.75)
+        );
+        assert_eq!(
+            mesh.get_vertex_prop(&uv, VertexHandle(4)).unwrap(),
+            glam::Vec2::zero()
+        );
+        assert!(matches!(
+            mesh.get_vertex_prop(&uv, VertexHandle(8)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.set_vertex_prop(&mut uv, VertexHandle(8), glam::Vec2::one()),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_box_points_iter() {
+        let mesh = quad_box();
+        let pts: Vec<_> = mesh.fv_points_ccw_iter(FaceHandle(0)).unwrap().collect();
+        assert_eq!(
+            pts,
+            vec![
+                glam::vec3(0.0, 0.0, 0.0),
+                glam::vec3(0.0, 1.0, 0.0),
+                glam::vec3(1.0, 1.0, 0.0),
+                glam::vec3(1.0, 0.0, 0.0),
+            ]
+        );
+        for (v, p) in mesh.vertex_points_iter().unwrap() {
+            assert_eq!(mesh.point(v).unwrap(), p);
+        }
+        assert_eq!(mesh.vertex_points_iter().unwrap().count(), 8);
     }
 }
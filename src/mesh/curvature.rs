@@ -0,0 +1,277 @@
+use super::{Error, Mesh, Property, VertexHandle};
+
+/// Share of the area of the triangle `(p, a, b)` that belongs to `p`, using
+/// the mixed Voronoi region of Meyer et al., "Discrete Differential-Geometry
+/// Operators for Triangulated 2-Manifolds". The Voronoi region is used for
+/// non-obtuse triangles; otherwise the triangle is split between its
+/// vertices, with half going to the obtuse vertex.
+fn mixed_area(p: glam::Vec3, a: glam::Vec3, b: glam::Vec3) -> f32 {
+    let (pa, pb, ab) = (a - p, b - p, b - a);
+    let area = pa.cross(pb).length() * 0.5;
+    if area == 0.0 {
+        return 0.0;
+    }
+    if pa.dot(pb) < 0.0 {
+        area * 0.5
+    } else if pa.dot(ab) > 0.0 || pb.dot(ab) < 0.0 {
+        area * 0.25
+    } else {
+        // The cotangent of an angle is dot / |cross|, and |cross| is twice the
+        // area for every corner.
+        let cot_a = (-pa).dot(ab) / (2.0 * area);
+        let cot_b = (-pb).dot(-ab) / (2.0 * area);
+        (pb.length_squared() * cot_a + pa.length_squared() * cot_b) / 8.0
+    }
+}
+
+impl Mesh {
+    /// Mixed Voronoi area of the vertex, summed over its incident faces.
+    /// Faces with more than three vertices are split into a fan of triangles
+    /// around the vertex.
+    pub fn vertex_area(&self, v: VertexHandle) -> Result<f32, Error> {
+        let v = v.0;
+        self.check_vertex(v)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        Ok(self.vertex_area_from(v, &points))
+    }
+
+    fn vertex_area_from(&self, v: u32, points: &[glam::Vec3]) -> f32 {
+        let p = points[v as usize];
+        let mut total = 0.0;
+        for h in self.voh_ccw_iter_u32(v) {
+            if self.is_boundary_halfedge_u32(h) {
+                continue;
+            }
+            // Walk the rest of the face, starting after the vertex.
+            let mut h = self.next_halfedge_u32(h);
+            while self.to_vertex_u32(h) != v {
+                let a = points[self.from_vertex_u32(h) as usize];
+                let b = points[self.to_vertex_u32(h) as usize];
+                total += mixed_area(p, a, b);
+                h = self.next_halfedge_u32(h);
+            }
+        }
+        total
+    }
+
+    /// Cotangent of the angle opposite to the halfedge, in its triangle.
+    /// Zero for boundary halfedges.
+    fn opposite_cotangent(&self, h: u32, points: &[glam::Vec3]) -> Result<f32, Error> {
+        let Some(f) = self.halfedge_face_u32(h) else {
+            return Ok(0.0);
+        };
+        if self.face_valence_u32(f) != 3 {
+            return Err(Error::NotATriangle(f));
+        }
+        let apex = points[self.to_vertex_u32(self.next_halfedge_u32(h)) as usize];
+        let a = points[self.from_vertex_u32(h) as usize] - apex;
+        let b = points[self.to_vertex_u32(h) as usize] - apex;
+        Ok(a.dot(b) / a.cross(b).length())
+    }
+
+    /// Cotangent weighted sum of the vectors from the vertex to its
+    /// neighbors, i.e. the sum of `(cot(alpha) + cot(beta)) * (n - v)` where
+    /// `alpha` and `beta` are the angles opposite to the edge in its two
+    /// triangles. Boundary edges only use the triangle they have. Dividing
+    /// this by twice the `vertex_area` gives the mean curvature normal. The
+    /// faces around the vertex must be triangles, otherwise
+    /// `Error::NotATriangle` is returned.
+    pub fn cotan_laplacian(&self, v: VertexHandle) -> Result<glam::Vec3, Error> {
+        let v = v.0;
+        self.check_vertex(v)?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        self.cotan_laplacian_from(v, &points)
+    }
+
+    fn cotan_laplacian_from(&self, v: u32, points: &[glam::Vec3]) -> Result<glam::Vec3, Error> {
+        let p = points[v as usize];
+        self.voh_ccw_iter_u32(v)
+            .try_fold(glam::Vec3::zero(), |total, h| {
+                let w = self.opposite_cotangent(h, points)?
+                    + self.opposite_cotangent(self.opposite_halfedge_u32(h), points)?;
+                Ok(total + (points[self.to_vertex_u32(h) as usize] - p) * w)
+            })
+    }
+
+    /// Sum of the angles of the incident faces at the corners of the vertex.
+    fn angle_sum_from(&self, v: u32, points: &[glam::Vec3]) -> f32 {
+        let p = points[v as usize];
+        self.voh_ccw_iter_u32(v)
+            .filter(|h| !self.is_boundary_halfedge_u32(*h))
+            .map(|h| {
+                let a = points[self.to_vertex_u32(h) as usize] - p;
+                let b = points[self.from_vertex_u32(self.prev_halfedge_u32(h)) as usize] - p;
+                a.cross(b).length().atan2(a.dot(b))
+            })
+            .sum()
+    }
+
+    /// Estimate the mean and Gaussian curvatures of every vertex, and store
+    /// them in two new vertex properties, returned in that order. The mean
+    /// curvature is half the magnitude of the cotangent Laplacian divided by
+    /// twice the vertex area, and is positive where the surface is convex.
+    /// The Gaussian curvature is the angle defect divided by the vertex area.
+    /// Neither is well defined on the boundary, so boundary vertices are
+    /// assigned NaN, and so are deleted vertices. The faces must be
+    /// triangles, otherwise `Error::NotATriangle` is returned.
+    pub fn update_curvatures(&mut self) -> Result<(Property<f32>, Property<f32>), Error> {
+        let curvatures: Vec<(f32, f32)> = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let fnormals: Vec<_> = (0..(self.num_faces() as u32))
+                .map(|f| self.face_normal_from(f, &points))
+                .collect();
+            (0..(self.num_vertices() as u32))
+                .map(|v| {
+                    if self.is_vertex_deleted_u32(v) {
+                        return Ok((f32::NAN, f32::NAN));
+                    }
+                    let area = self.vertex_area_from(v, &points);
+                    if self.is_boundary_vertex_u32(v) || area == 0.0 {
+                        return Ok((f32::NAN, f32::NAN));
+                    }
+                    let lap = self.cotan_laplacian_from(v, &points)?;
+                    let normal = self.vertex_normal_from(v, &fnormals);
+                    let mean = lap.length() / (4.0 * area);
+                    let mean = if lap.dot(normal) > 0.0 { -mean } else { mean };
+                    let defect = 2.0 * std::f32::consts::PI - self.angle_sum_from(v, &points);
+                    Ok((mean, defect / area))
+                })
+                .collect::<Result<_, Error>>()?
+        };
+        let (mean, gauss): (Vec<f32>, Vec<f32>) = curvatures.into_iter().unzip();
+        let mut mprop = self.create_vertex_prop::<f32>();
+        let mut gprop = self.create_vertex_prop::<f32>();
+        for (prop, vals) in [(&mut mprop, mean), (&mut gprop, gauss)] {
+            *prop
+                .data
+                .write()
+                .map_err(|_| Error::WriteToPropertyFailed)? = vals;
+        }
+        Ok((mprop, gprop))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::mixed_area;
+    use crate::mesh::{
+        test::{quad_grid, tri_grid, uv_sphere},
+        Error, VertexHandle,
+    };
+
+    #[test]
+    fn t_mixed_area_partitions_triangle() {
+        let tris = [
+            // Acute.
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.4, 0.8, 0.0]],
+            // Obtuse at the first vertex.
+            [[0.0, 0.0, 0.0], [1.0, 0.1, 0.0], [-1.0, 0.2, 0.0]],
+        ];
+        for [a, b, c] in tris.map(|t| t.map(glam::Vec3::from)) {
+            let area = (b - a).cross(c - a).length() * 0.5;
+            let total = mixed_area(a, b, c) + mixed_area(b, c, a) + mixed_area(c, a, b);
+            assert!((total - area).abs() < 1e-6);
+        }
+        let (a, b, c) = (
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.1, 0.0),
+            glam::vec3(-1.0, 0.2, 0.0),
+        );
+        let area = (b - a).cross(c - a).length() * 0.5;
+        assert!((mixed_area(a, b, c) - area * 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn t_vertex_area_grid() {
+        for mesh in [quad_grid(3, 3), tri_grid(3, 3)] {
+            let area = |x: u32, y: u32| mesh.vertex_area(VertexHandle(y * 4 + x)).unwrap();
+            // Interior.
+            assert!((area(1, 1) - 1.0).abs() < 1e-5);
+            assert!((area(2, 1) - 1.0).abs() < 1e-5);
+            // Boundary.
+            assert!((area(1, 0) - 0.5).abs() < 1e-5);
+            assert!((area(0, 2) - 0.5).abs() < 1e-5);
+            // Corners.
+            assert!((area(0, 0) - 0.25).abs() < 1e-5);
+            assert!((area(3, 3) - 0.25).abs() < 1e-5);
+            // The areas of all the vertices add up to the area of the grid.
+            let total: f32 = mesh.vertices().map(|v| mesh.vertex_area(v).unwrap()).sum();
+            assert!((total - 9.0).abs() < 1e-4);
+            assert!(matches!(
+                mesh.vertex_area(VertexHandle(mesh.num_vertices() as u32)),
+                Err(Error::OutOfBoundsAccess)
+            ));
+        }
+    }
+
+    #[test]
+    fn t_cotan_laplacian() {
+        let mesh = tri_grid(4, 4);
+        for v in mesh.vertices() {
+            if !mesh.is_boundary_vertex(v) {
+                assert!(mesh.cotan_laplacian(v).unwrap().length() < 1e-5);
+            }
+        }
+        // On a sphere the laplacian points towards the center.
+        let mut mesh = uv_sphere(2.0, 24, 12);
+        mesh.triangulate().unwrap();
+        for v in (1..(mesh.num_vertices() as u32 - 1)).map(VertexHandle) {
+            let lap = mesh.cotan_laplacian(v).unwrap();
+            let inward = -mesh.point(v).unwrap().normalize();
+            assert!(lap.normalize().dot(inward) > 0.95);
+            // Mean curvature is 1 / radius.
+            let h = lap.length() / (4.0 * mesh.vertex_area(v).unwrap());
+            assert!((h - 0.5).abs() < 0.05, "{h}");
+        }
+        assert!(matches!(
+            mesh.cotan_laplacian(VertexHandle(mesh.num_vertices() as u32)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        // The cotangent weights are only defined for triangles.
+        let mesh = quad_grid(2, 2);
+        assert!(matches!(
+            mesh.cotan_laplacian(VertexHandle(4)),
+            Err(Error::NotATriangle(_))
+        ));
+    }
+
+    #[test]
+    fn t_update_curvatures_sphere() {
+        const RADIUS: f32 = 2.0;
+        let mut mesh = uv_sphere(RADIUS, 48, 24);
+        mesh.triangulate().unwrap();
+        let (mean, gauss) = mesh.update_curvatures().unwrap();
+        for v in 0..(mesh.num_vertices() as u32) {
+            // Stay away from the poles, where the triangles are slivers.
+            if mesh.point_u32(v).unwrap().z().abs() > 0.8 * RADIUS {
+                continue;
+            }
+            let h = mean.get(v).unwrap();
+            let k = gauss.get(v).unwrap();
+            assert!((h - 1.0 / RADIUS).abs() < 0.05 / RADIUS, "{h}");
+            assert!(
+                (k - 1.0 / (RADIUS * RADIUS)).abs() < 0.1 / (RADIUS * RADIUS),
+                "{k}"
+            );
+        }
+        // Boundary vertices are flagged, and flat interiors have no
+        // curvature.
+        let mut mesh = tri_grid(3, 3);
+        let (mean, gauss) = mesh.update_curvatures().unwrap();
+        assert!(mean.get(0).unwrap().is_nan() && gauss.get(0).unwrap().is_nan());
+        assert!(mean.get(5).unwrap().abs() < 1e-5);
+        assert!(gauss.get(5).unwrap().abs() < 1e-5);
+    }
+}
@@ -0,0 +1,219 @@
+use super::{Error, Mesh};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// Symmetric 4x4 matrix measuring the sum of squared distances to a set of
+/// planes. Only the upper triangle is stored.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Quadric of the plane with unit normal `n` passing through `p`.
+    fn from_plane(n: glam::Vec3, p: glam::Vec3) -> Self {
+        let (a, b, c) = (n.x() as f64, n.y() as f64, n.z() as f64);
+        let d = -(n.dot(p) as f64);
+        Quadric([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a += b;
+        }
+    }
+
+    fn eval(&self, p: glam::Vec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x() as f64, p.y() as f64, p.z() as f64);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// A candidate collapse in the priority queue. The versions of the two
+/// vertices at the time the candidate was pushed are used to discard stale
+/// candidates.
+struct Candidate {
+    cost: f64,
+    h: u32,
+    versions: (u32, u32),
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that the max-heap pops the cheapest collapse first.
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.h.cmp(&self.h))
+    }
+}
+
+impl Mesh {
+    /// Simplify the mesh by collapsing halfedges, cheapest first, until it
+    /// has at most `target` faces or no more valid collapses remain. The cost
+    /// of a collapse is the quadric error metric of Garland and Heckbert,
+    /// with the surviving vertex staying where it is. Boundary vertices are
+    /// never removed, and collapses that would flip a face are skipped. Faces
+    /// with more than three vertices are triangulated first. Returns the
+    /// number of faces after decimation.
+    pub fn decimate_to_face_count(&mut self, target: usize) -> Result<usize, Error> {
+        self.triangulate()?;
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?
+            .clone();
+        let mut quadrics = vec![Quadric::default(); self.num_vertices()];
+        for f in 0..(self.num_faces() as u32) {
+            let normal = self.face_normal_from(f, &points);
+            let q = Quadric::from_plane(
+                normal,
+                points[self.to_vertex_u32(self.face_halfedge_u32(f)) as usize],
+            );
+            for v in self.fv_ccw_iter_u32(f) {
+                quadrics[v as usize].add(&q);
+            }
+        }
+        let mut versions = vec![0u32; self.num_vertices()];
+        let mut queue = BinaryHeap::new();
+        let candidate = |mesh: &Mesh, quadrics: &[Quadric], versions: &[u32], h: u32| {
+            let (from, to) = (mesh.from_vertex_u32(h), mesh.to_vertex_u32(h));
+            let mut q = quadrics[from as usize];
+            q.add(&quadrics[to as usize]);
+            Candidate {
+                cost: q.eval(points[to as usize]),
+                h,
+                versions: (versions[from as usize], versions[to as usize]),
+            }
+        };
+        for h in 0..(self.num_halfedges() as u32) {
+            queue.push(candidate(self, &quadrics, &versions, h));
+        }
+        let mut nfaces = self.num_faces();
+        while nfaces > target {
+            let Some(Candidate {
+                h, versions: ver, ..
+            }) = queue.pop()
+            else {
+                break;
+            };
+            if self.is_edge_deleted_u32(h >> 1) {
+                continue;
+            }
+            let (from, to) = (self.from_vertex_u32(h), self.to_vertex_u32(h));
+            if ver != (versions[from as usize], versions[to as usize])
+                || self.is_boundary_vertex_u32(from)
+                || !self.is_collapse_ok_u32(h)
+                || self.collapse_flips_face(h, &points)
+            {
+                continue;
+            }
+            let faces = [self.halfedge_face_u32(h), self.halfedge_face_u32(h ^ 1)];
+            self.collapse_halfedge_u32(h);
+            nfaces -= faces
+                .iter()
+                .flatten()
+                .filter(|f| self.is_face_deleted_u32(**f))
+                .count();
+            let q = quadrics[from as usize];
+            quadrics[to as usize].add(&q);
+            versions[to as usize] += 1;
+            let ring: Vec<u32> = self.voh_ccw_iter_u32(to).collect();
+            for h in ring {
+                queue.push(candidate(self, &quadrics, &versions, h));
+                queue.push(candidate(self, &quadrics, &versions, h ^ 1));
+            }
+        }
+        self.garbage_collection()?;
+        Ok(self.num_faces())
+    }
+
+    /// Check whether moving the start vertex of the halfedge onto its end
+    /// vertex would flip the normal of any of the faces that survive the
+    /// collapse.
+    fn collapse_flips_face(&self, h: u32, points: &[glam::Vec3]) -> bool {
+        let (from, to) = (self.from_vertex_u32(h), self.to_vertex_u32(h));
+        let target = points[to as usize];
+        self.vf_ccw_iter_u32(from).any(|f| {
+            if self.fv_ccw_iter_u32(f).any(|v| v == to) {
+                return false; // This face degenerates and is removed.
+            }
+            let before = self.newell_vector(f, points);
+            let moved: Vec<glam::Vec3> = self
+                .fv_ccw_iter_u32(f)
+                .map(|v| {
+                    if v == from {
+                        target
+                    } else {
+                        points[v as usize]
+                    }
+                })
+                .collect();
+            let after = (moved[1] - moved[0]).cross(moved[2] - moved[0]);
+            before.dot(after) <= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::test::uv_sphere;
+
+    #[test]
+    fn t_decimate_sphere() {
+        let mut mesh = uv_sphere(1.0, 32, 16);
+        mesh.triangulate().unwrap();
+        let nfaces = mesh.num_faces();
+        let target = nfaces / 2;
+        let achieved = mesh.decimate_to_face_count(target).unwrap();
+        assert_eq!(achieved, mesh.num_faces());
+        assert!(achieved <= target && achieved + 2 >= target);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.euler_characteristic(), 2);
+        let (min, max) = mesh.vertex_points_iter_u32().unwrap().fold(
+            (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN)),
+            |(min, max), (_, p)| (min.min(p), max.max(p)),
+        );
+        assert!(
+            (min + glam::Vec3::one()).abs().max_element() < 0.05,
+            "{min:?}"
+        );
+        assert!(
+            (max - glam::Vec3::one()).abs().max_element() < 0.05,
+            "{max:?}"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use super::{Error, Mesh};
+use std::collections::VecDeque;
+
+impl Mesh {
+    /// Flip edges until the triangulation is Delaunay in the XY plane, i.e.
+    /// no vertex lies inside the circumcircle of a triangle adjacent to it
+    /// across an edge. The z coordinates are ignored. Edges are checked with
+    /// the in-circle test, and the neighbors of every flipped edge are queued
+    /// to be checked again, so this converges. Boundary edges and edges not
+    /// shared by two triangles are never flipped. Returns the number of
+    /// flips.
+    pub fn make_delaunay_2d(&mut self) -> Result<usize, Error> {
+        let points = self.points_snapshot()?;
+        let mut queue: VecDeque<u32> = self.edges_u32().collect();
+        let mut queued = vec![true; self.num_edges()];
+        let mut flips = 0usize;
+        while let Some(e) = queue.pop_front() {
+            queued[e as usize] = false;
+            if !self.is_flip_ok_u32(e) {
+                continue;
+            }
+            let h = e << 1;
+            let oh = h | 1;
+            let (a, b) = (self.to_vertex_u32(h), self.to_vertex_u32(oh));
+            let c = self.to_vertex_u32(self.next_halfedge_u32(h));
+            let d = self.to_vertex_u32(self.next_halfedge_u32(oh));
+            let [pa, pb, pc, pd] = [a, b, c, d].map(|v| {
+                let p = points[v as usize];
+                [p.x() as f64, p.y() as f64]
+            });
+            // Triangles (b, a, c) and (a, b, d) become (a, c, d) and (c, b, d).
+            let sign = orient_2d(pb, pa, pc).signum();
+            if in_circle(pb, pa, pc, pd) * sign <= 0.0
+                || orient_2d(pa, pc, pd) * sign <= 0.0
+                || orient_2d(pc, pb, pd) * sign <= 0.0
+            {
+                continue;
+            }
+            self.flip_edge_u32(e);
+            flips += 1;
+            let next = [
+                self.next_halfedge_u32(h),
+                self.prev_halfedge_u32(h),
+                self.next_halfedge_u32(oh),
+                self.prev_halfedge_u32(oh),
+            ];
+            for ne in next.map(|h| h >> 1) {
+                if !queued[ne as usize] {
+                    queued[ne as usize] = true;
+                    queue.push_back(ne);
+                }
+            }
+        }
+        Ok(flips)
+    }
+}
+
+/// Twice the signed area of the triangle, positive if it is
+/// counter-clockwise.
+fn orient_2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Positive if `d` lies inside the circumcircle of the counter-clockwise
+/// triangle `(a, b, c)`, negative if outside and zero if on it. The sign is
+/// reversed for clockwise triangles.
+fn in_circle(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> f64 {
+    let row = |p: [f64; 2]| {
+        let (x, y) = (p[0] - d[0], p[1] - d[1]);
+        [x, y, x * x + y * y]
+    };
+    let [a, b, c] = [row(a), row(b), row(c)];
+    a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+        + a[2] * (b[0] * c[1] - b[1] * c[0])
+}
+
+#[cfg(test)]
+mod test {
+    use super::{in_circle, orient_2d};
+    use crate::mesh::{test::tri_grid, Mesh, VertexHandle};
+
+    fn is_delaunay(mesh: &Mesh) -> bool {
+        let points = mesh.points_snapshot().unwrap();
+        let p = |v: u32| {
+            let p = points[v as usize];
+            [p.x() as f64, p.y() as f64]
+        };
+        mesh.edges_u32()
+            .filter(|e| mesh.is_flip_ok_u32(*e))
+            .all(|e| {
+                let h = e << 1;
+                let (a, b) = (mesh.to_vertex_u32(h), mesh.to_vertex_u32(h | 1));
+                let c = mesh.to_vertex_u32(mesh.next_halfedge_u32(h));
+                let d = mesh.to_vertex_u32(mesh.next_halfedge_u32(h | 1));
+                in_circle(p(b), p(a), p(c), p(d)) * orient_2d(p(b), p(a), p(c)).signum() <= 1e-9
+            })
+    }
+
+    #[test]
+    fn t_make_delaunay_2d_square() {
+        let mut mesh = tri_grid(1, 1);
+        let e = mesh.edges_u32().find(|e| mesh.is_flip_ok_u32(*e)).unwrap();
+        let ends = [mesh.from_vertex_u32(e << 1), mesh.to_vertex_u32(e << 1)];
+        // Stretch the diagonal, so the square becomes a rhombus whose other
+        // diagonal is the Delaunay one.
+        let center = glam::vec3(0.5, 0.5, 0.0);
+        for v in ends.map(VertexHandle) {
+            let p = mesh.point(v).unwrap();
+            mesh.set_point(v, p + (p - center)).unwrap();
+        }
+        assert!(!is_delaunay(&mesh));
+        assert_eq!(mesh.make_delaunay_2d().unwrap(), 1);
+        mesh.validate().unwrap();
+        let after = [mesh.from_vertex_u32(e << 1), mesh.to_vertex_u32(e << 1)];
+        assert!(after.iter().all(|v| !ends.contains(v)));
+        assert!(is_delaunay(&mesh));
+        assert_eq!(mesh.make_delaunay_2d().unwrap(), 0);
+    }
+
+    #[test]
+    fn t_make_delaunay_2d_grid() {
+        let mut mesh = tri_grid(6, 6);
+        // Shear the grid so many diagonals are no longer Delaunay.
+        for v in (0..mesh.num_vertices() as u32).map(VertexHandle) {
+            let p = mesh.point(v).unwrap();
+            let offset = glam::vec3(
+                0.7 * p.y() + 0.2 * ((v.index() * 7) % 5) as f32 / 5.0,
+                0.0,
+                0.0,
+            );
+            mesh.set_point(v, p + offset).unwrap();
+        }
+        assert!(!is_delaunay(&mesh));
+        assert!(mesh.make_delaunay_2d().unwrap() > 0);
+        mesh.validate().unwrap();
+        assert!(is_delaunay(&mesh));
+    }
+}
@@ -0,0 +1,1702 @@
+use super::{EdgeHandle, Error, FaceHandle, HalfedgeHandle, Mesh, VertexHandle};
+use std::collections::HashSet;
+
+/// New handles of the elements of a mesh after garbage collection, indexed by
+/// their old indices. Deleted elements map to `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcRemap {
+    pub vertices: Vec<Option<VertexHandle>>,
+    pub edges: Vec<Option<EdgeHandle>>,
+    pub faces: Vec<Option<FaceHandle>>,
+}
+
+/// Largest hole filled with a single face by `Mesh::fill_hole`.
+const MAX_HOLE_FACE_SIZE: usize = 6;
+
+impl Mesh {
+    /// Split the edge by inserting a new vertex at `pos`, and split the
+    /// triangles on either side of the edge into two. The new faces inherit
+    /// the properties of the faces they were split from, and both halves of
+    /// the edge keep the properties of the edge. Returns the new vertex.
+    /// `Error::NotATriangle` is returned if a face next to the edge is not a
+    /// triangle.
+    pub fn split_edge(&mut self, e: EdgeHandle, pos: glam::Vec3) -> Result<VertexHandle, Error> {
+        self.split_edge_u32(e.0, pos).map(VertexHandle)
+    }
+
+    /// Same as `split_edge`, with plain indices.
+    pub(crate) fn split_edge_u32(&mut self, e: u32, pos: glam::Vec3) -> Result<u32, Error> {
+        self.check_edge(e)?;
+        let (fa, fb) = self.edge_faces_u32(e);
+        for f in [fa, fb].into_iter().flatten() {
+            if self.face_valence_u32(f) != 3 {
+                return Err(Error::NotATriangle(f));
+            }
+        }
+        let vnew = self.add_vertex_u32(pos)?;
+        let h0 = e << 1;
+        let o0 = h0 | 1;
+        let v2 = self.to_vertex_u32(o0);
+        let e1 = self.new_edge(vnew, v2)?;
+        self.copy_edge_properties_u32(e, e1 >> 1)?;
+        let t1 = self.opposite_halfedge_u32(e1);
+        let f0 = self.halfedge_face_u32(h0);
+        let f3 = self.halfedge_face_u32(o0);
+        self.vertices[vnew as usize].halfedge = Some(h0);
+        self.halfedge_mut(o0).vertex = vnew;
+        match f0 {
+            Some(f0) => {
+                let h1 = self.next_halfedge_u32(h0);
+                let h2 = self.next_halfedge_u32(h1);
+                let v1 = self.to_vertex_u32(h1);
+                let e0 = self.new_edge(vnew, v1)?;
+                let t0 = self.opposite_halfedge_u32(e0);
+                let f1 = self.new_face(h2)?;
+                self.copy_face_properties_u32(f0, f1)?;
+                self.faces[f0 as usize].halfedge = h0;
+                for h in [h1, t0, h0] {
+                    self.halfedge_mut(h).face = Some(f0);
+                }
+                for h in [h2, t1, e0] {
+                    self.halfedge_mut(h).face = Some(f1);
+                }
+                self.link_halfedges(h0, h1);
+                self.link_halfedges(h1, t0);
+                self.link_halfedges(t0, h0);
+                self.link_halfedges(e0, h2);
+                self.link_halfedges(h2, t1);
+                self.link_halfedges(t1, e0);
+            }
+            None => {
+                let prev = self.prev_halfedge_u32(h0);
+                self.link_halfedges(prev, t1);
+                self.link_halfedges(t1, h0);
+            }
+        }
+        match f3 {
+            Some(f3) => {
+                let o1 = self.next_halfedge_u32(o0);
+                let o2 = self.next_halfedge_u32(o1);
+                let v3 = self.to_vertex_u32(o1);
+                let e2 = self.new_edge(vnew, v3)?;
+                let t2 = self.opposite_halfedge_u32(e2);
+                let f2 = self.new_face(o1)?;
+                self.copy_face_properties_u32(f3, f2)?;
+                self.faces[f3 as usize].halfedge = o0;
+                for h in [o1, t2, e1] {
+                    self.halfedge_mut(h).face = Some(f2);
+                }
+                for h in [o2, o0, e2] {
+                    self.halfedge_mut(h).face = Some(f3);
+                }
+                self.link_halfedges(e1, o1);
+                self.link_halfedges(o1, t2);
+                self.link_halfedges(t2, e1);
+                self.link_halfedges(o0, e2);
+                self.link_halfedges(e2, o2);
+                self.link_halfedges(o2, o0);
+            }
+            None => {
+                let next = self.next_halfedge_u32(o0);
+                self.link_halfedges(e1, next);
+                self.link_halfedges(o0, e1);
+                self.vertices[vnew as usize].halfedge = Some(e1);
+            }
+        }
+        if self.vertex_halfedge_u32(v2) == Some(h0) {
+            self.vertices[v2 as usize].halfedge = Some(t1);
+        }
+        Ok(vnew)
+    }
+
+    /// Split the face into a fan of triangles around a new vertex at `pos`,
+    /// one triangle per edge of the face. The new triangles inherit the
+    /// properties of the face. Returns the new vertex.
+    pub fn split_face(&mut self, f: FaceHandle, pos: glam::Vec3) -> Result<VertexHandle, Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        let halfedges: Vec<u32> = self.fh_ccw_iter_u32(f).collect();
+        let corners: Vec<u32> = halfedges.iter().map(|h| self.from_vertex_u32(*h)).collect();
+        let vnew = self.add_vertex_u32(pos)?;
+        // The spokes go from the new vertex to the corners.
+        let spokes = corners
+            .iter()
+            .map(|v| self.new_edge(vnew, *v))
+            .collect::<Result<Vec<u32>, Error>>()?;
+        let n = halfedges.len();
+        for (i, h) in halfedges.iter().enumerate() {
+            let out = spokes[i];
+            let inc = self.opposite_halfedge_u32(spokes[(i + 1) % n]);
+            let face = if i == 0 {
+                f
+            } else {
+                let fnew = self.new_face(*h)?;
+                self.copy_face_properties_u32(f, fnew)?;
+                fnew
+            };
+            self.faces[face as usize].halfedge = *h;
+            for h in [out, *h, inc] {
+                self.halfedge_mut(h).face = Some(face);
+            }
+            self.link_halfedges(out, *h);
+            self.link_halfedges(*h, inc);
+            self.link_halfedges(inc, out);
+        }
+        self.vertices[vnew as usize].halfedge = Some(spokes[0]);
+        Ok(VertexHandle(vnew))
+    }
+
+    /// Remove the interior edge and merge the two faces on either side of it
+    /// into one. The merged face keeps the handle and the properties of the
+    /// face of the first halfedge of the edge; the other face and the edge
+    /// are marked as deleted. Fails with `BoundaryEdge` if the edge is on the
+    /// boundary, and with `ComplexEdge` if the two faces are the same or
+    /// share another edge, because the merged face would then run along an
+    /// edge on both sides. Returns the merged face.
+    pub fn remove_edge(&mut self, e: EdgeHandle) -> Result<FaceHandle, Error> {
+        let e = e.0;
+        self.check_halfedge(e << 1)?;
+        let h0 = e << 1;
+        let h1 = h0 | 1;
+        let (f0, f1) = match (self.halfedge_face_u32(h0), self.halfedge_face_u32(h1)) {
+            (Some(f0), Some(f1)) => (f0, f1),
+            _ => return Err(Error::BoundaryEdge(e)),
+        };
+        if f0 == f1 {
+            return Err(Error::ComplexEdge(e));
+        }
+        if let Some(shared) = self.fh_ccw_iter_u32(f0).find(|h| {
+            *h != h0 && self.halfedge_face_u32(self.opposite_halfedge_u32(*h)) == Some(f1)
+        }) {
+            return Err(Error::ComplexEdge(shared >> 1));
+        }
+        let (prev0, next0) = (self.prev_halfedge_u32(h0), self.next_halfedge_u32(h0));
+        let (prev1, next1) = (self.prev_halfedge_u32(h1), self.next_halfedge_u32(h1));
+        let (v0, v1) = (self.to_vertex_u32(h0), self.to_vertex_u32(h1));
+        let moved: Vec<u32> = self.fh_ccw_iter_u32(f1).filter(|h| *h != h1).collect();
+        for h in moved {
+            self.halfedge_mut(h).face = Some(f0);
+        }
+        self.link_halfedges(prev0, next1);
+        self.link_halfedges(prev1, next0);
+        if self.vertex_halfedge_u32(v0) == Some(h1) {
+            self.vertices[v0 as usize].halfedge = Some(next0);
+        }
+        if self.vertex_halfedge_u32(v1) == Some(h0) {
+            self.vertices[v1 as usize].halfedge = Some(next1);
+        }
+        self.faces[f0 as usize].halfedge = prev0;
+        self.faces[f1 as usize].deleted = true;
+        self.edges[e as usize].deleted = true;
+        Ok(FaceHandle(f0))
+    }
+
+    /// Check whether collapsing the halfedge keeps the mesh manifold. This
+    /// is the link condition, along with a few checks to avoid degenerate
+    /// faces near the boundary.
+    pub fn is_collapse_ok(&self, h: HalfedgeHandle) -> bool {
+        self.is_collapse_ok_u32(h.0)
+    }
+
+    /// Same as `is_collapse_ok`, with plain indices.
+    pub(crate) fn is_collapse_ok_u32(&self, h: u32) -> bool {
+        let oh = self.opposite_halfedge_u32(h);
+        let v0 = self.to_vertex_u32(oh);
+        let v1 = self.to_vertex_u32(h);
+        if self.is_edge_deleted_u32(h >> 1)
+            || self.is_vertex_deleted_u32(v0)
+            || self.is_vertex_deleted_u32(v1)
+        {
+            return false;
+        }
+        let is_triangle = |h: u32| match self.halfedge_face_u32(h) {
+            Some(f) => self.face_valence_u32(f) == 3,
+            None => false,
+        };
+        let h_tri = is_triangle(h);
+        let oh_tri = is_triangle(oh);
+        // The two other edges of a triangle being collapsed must not both be
+        // on the boundary.
+        let apex = |h: u32| {
+            let h1 = self.next_halfedge_u32(h);
+            let h2 = self.next_halfedge_u32(h1);
+            if self.is_boundary_halfedge_u32(self.opposite_halfedge_u32(h1))
+                && self.is_boundary_halfedge_u32(self.opposite_halfedge_u32(h2))
+            {
+                None
+            } else {
+                Some(self.to_vertex_u32(h1))
+            }
+        };
+        let vl = if h_tri {
+            match apex(h) {
+                Some(v) => Some(v),
+                None => return false,
+            }
+        } else {
+            None
+        };
+        let vr = if oh_tri {
+            match apex(oh) {
+                Some(v) => Some(v),
+                None => return false,
+            }
+        } else {
+            None
+        };
+        if vl.is_some() && vl == vr {
+            return false;
+        }
+        // An edge between two boundary vertices must be a boundary edge.
+        if self.is_boundary_vertex_u32(v0)
+            && self.is_boundary_vertex_u32(v1)
+            && !self.is_boundary_halfedge_u32(h)
+            && !self.is_boundary_halfedge_u32(oh)
+        {
+            return false;
+        }
+        // The one-rings of the two vertices may only share the apexes of the
+        // triangles being collapsed.
+        let ring1: Vec<u32> = self.vv_ccw_iter_u32(v1).collect();
+        for v in self.vv_ccw_iter_u32(v0) {
+            if ring1.contains(&v) && Some(v) != vl && Some(v) != vr {
+                return false;
+            }
+        }
+        // A face on the other side of a collapsed triangle must not degenerate.
+        for (h, tri) in [(h, h_tri), (oh, oh_tri)] {
+            if !tri {
+                continue;
+            }
+            let one = self.next_halfedge_u32(h);
+            let two = self.next_halfedge_u32(one);
+            let f1 = self.halfedge_face_u32(self.opposite_halfedge_u32(one));
+            let f2 = self.halfedge_face_u32(self.opposite_halfedge_u32(two));
+            if let Some(f) = f1 {
+                if f1 == f2 && self.face_valence_u32(f) != 3 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Collapse the halfedge by merging its start vertex into its end vertex.
+    /// The start vertex, the edge and any faces that degenerate are marked as
+    /// deleted. Use `is_collapse_ok` to check the collapse is valid first,
+    /// and `garbage_collection` to get rid of the deleted elements.
+    pub fn collapse_halfedge(&mut self, h: HalfedgeHandle) {
+        self.collapse_halfedge_u32(h.0)
+    }
+
+    /// Same as `collapse_halfedge`, with plain indices.
+    pub(crate) fn collapse_halfedge_u32(&mut self, h: u32) {
+        let h1 = self.next_halfedge_u32(h);
+        let o1 = self.next_halfedge_u32(self.opposite_halfedge_u32(h));
+        self.collapse_edge(h);
+        if self.next_halfedge_u32(self.next_halfedge_u32(h1)) == h1 {
+            self.collapse_loop(self.next_halfedge_u32(h1));
+        }
+        if self.next_halfedge_u32(self.next_halfedge_u32(o1)) == o1 {
+            self.collapse_loop(o1);
+        }
+    }
+
+    /// Collapse the edges shorter than `min_length`, for cleaning up slivers
+    /// and near duplicate vertices. An edge is collapsed into whichever of
+    /// its vertices passes `is_collapse_ok`, and boundary vertices are never
+    /// pulled into the interior. The lengths are checked again just before
+    /// collapsing, because earlier collapses move the edges around them. The
+    /// mesh is garbage collected afterwards, which invalidates all handles.
+    /// Returns the number of edges collapsed.
+    pub fn collapse_short_edges(&mut self, min_length: f32) -> Result<usize, Error> {
+        let candidates: Vec<u32> = self
+            .edges_u32()
+            .filter(|e| {
+                self.calc_edge_length_u32(*e)
+                    .is_ok_and(|length| length < min_length)
+            })
+            .collect();
+        let mut count = 0usize;
+        for e in candidates {
+            if self.is_edge_deleted_u32(e) || self.calc_edge_length_u32(e)? >= min_length {
+                continue;
+            }
+            let h = e << 1;
+            let found = [h, self.opposite_halfedge_u32(h)].into_iter().find(|h| {
+                (!self.is_boundary_vertex_u32(self.from_vertex_u32(*h))
+                    || self.is_boundary_vertex_u32(self.to_vertex_u32(*h)))
+                    && self.is_collapse_ok_u32(*h)
+            });
+            if let Some(h) = found {
+                self.collapse_halfedge_u32(h);
+                count += 1;
+            }
+        }
+        self.garbage_collection()?;
+        Ok(count)
+    }
+
+    fn collapse_edge(&mut self, h: u32) {
+        let hn = self.next_halfedge_u32(h);
+        let hp = self.prev_halfedge_u32(h);
+        let o = self.opposite_halfedge_u32(h);
+        let on = self.next_halfedge_u32(o);
+        let op = self.prev_halfedge_u32(o);
+        let fh = self.halfedge_face_u32(h);
+        let fo = self.halfedge_face_u32(o);
+        let vh = self.to_vertex_u32(h);
+        let vo = self.to_vertex_u32(o);
+        let incoming: Vec<u32> = self.vih_ccw_iter_u32(vo).collect();
+        for ih in incoming {
+            self.halfedge_mut(ih).vertex = vh;
+        }
+        self.link_halfedges(hp, hn);
+        self.link_halfedges(op, on);
+        if let Some(fh) = fh {
+            self.faces[fh as usize].halfedge = hn;
+        }
+        if let Some(fo) = fo {
+            self.faces[fo as usize].halfedge = on;
+        }
+        if self.vertex_halfedge_u32(vh) == Some(o) {
+            self.vertices[vh as usize].halfedge = Some(hn);
+        }
+        self.adjust_outgoing_halfedge(vh);
+        self.vertices[vo as usize].halfedge = None;
+        self.vertices[vo as usize].deleted = true;
+        self.edges[(h >> 1) as usize].deleted = true;
+    }
+
+    /// Remove a loop of two halfedges left behind by collapsing an edge.
+    fn collapse_loop(&mut self, h0: u32) {
+        let h1 = self.next_halfedge_u32(h0);
+        let o0 = self.opposite_halfedge_u32(h0);
+        let o1 = self.opposite_halfedge_u32(h1);
+        let v0 = self.to_vertex_u32(h0);
+        let v1 = self.to_vertex_u32(h1);
+        let fh = self.halfedge_face_u32(h0);
+        let fo = self.halfedge_face_u32(o0);
+        debug_assert!(self.next_halfedge_u32(h1) == h0 && h1 != o0);
+        let (onext, oprev) = (self.next_halfedge_u32(o0), self.prev_halfedge_u32(o0));
+        self.link_halfedges(h1, onext);
+        self.link_halfedges(oprev, h1);
+        self.halfedge_mut(h1).face = fo;
+        self.vertices[v0 as usize].halfedge = Some(h1);
+        self.adjust_outgoing_halfedge(v0);
+        self.vertices[v1 as usize].halfedge = Some(o1);
+        self.adjust_outgoing_halfedge(v1);
+        if let Some(fo) = fo {
+            if self.face_halfedge_u32(fo) == o0 {
+                self.faces[fo as usize].halfedge = h1;
+            }
+        }
+        if let Some(fh) = fh {
+            self.faces[fh as usize].deleted = true;
+        }
+        self.edges[(h0 >> 1) as usize].deleted = true;
+    }
+
+    /// Mark the face as deleted and turn its halfedges into boundary
+    /// halfedges. Edges that are left without any faces are deleted too, and
+    /// so are the vertices left without any edges, if
+    /// `delete_isolated_vertices` is true. Use `garbage_collection` to get rid
+    /// of the deleted elements. Deleting a face that is already deleted does
+    /// nothing.
+    pub fn delete_face(
+        &mut self,
+        f: FaceHandle,
+        delete_isolated_vertices: bool,
+    ) -> Result<(), Error> {
+        self.delete_face_u32(f.0, delete_isolated_vertices)
+    }
+
+    /// Same as `delete_face`, with plain indices.
+    pub(crate) fn delete_face_u32(
+        &mut self,
+        f: u32,
+        delete_isolated_vertices: bool,
+    ) -> Result<(), Error> {
+        self.check_face(f)?;
+        if self.is_face_deleted_u32(f) {
+            return Ok(());
+        }
+        let halfedges: Vec<u32> = self.fh_ccw_iter_u32(f).collect();
+        self.faces[f as usize].deleted = true;
+        let mut dead_edges = Vec::new();
+        let mut verts = Vec::with_capacity(halfedges.len());
+        for h in halfedges {
+            self.halfedge_mut(h).face = None;
+            if self.is_boundary_halfedge_u32(self.opposite_halfedge_u32(h)) {
+                dead_edges.push(h >> 1);
+            }
+            verts.push(self.to_vertex_u32(h));
+        }
+        for e in dead_edges {
+            let h0 = e << 1;
+            let h1 = h0 | 1;
+            let (v0, next0, prev0) = (
+                self.to_vertex_u32(h0),
+                self.next_halfedge_u32(h0),
+                self.prev_halfedge_u32(h0),
+            );
+            let (v1, next1, prev1) = (
+                self.to_vertex_u32(h1),
+                self.next_halfedge_u32(h1),
+                self.prev_halfedge_u32(h1),
+            );
+            self.link_halfedges(prev0, next1);
+            self.link_halfedges(prev1, next0);
+            self.edges[e as usize].deleted = true;
+            for (v, h, next) in [(v0, h1, next0), (v1, h0, next1)] {
+                if self.vertex_halfedge_u32(v) != Some(h) {
+                    continue;
+                }
+                if next == h {
+                    // Nothing else is connected to the vertex.
+                    self.vertices[v as usize].halfedge = None;
+                    self.vertices[v as usize].deleted |= delete_isolated_vertices;
+                } else {
+                    self.vertices[v as usize].halfedge = Some(next);
+                }
+            }
+        }
+        for v in verts {
+            self.adjust_outgoing_halfedge(v);
+        }
+        Ok(())
+    }
+
+    /// Delete the faces with an area less than `area_eps`, such as slivers
+    /// with collinear vertices, along with the vertices left isolated. The
+    /// mesh is garbage collected afterwards, which invalidates all handles.
+    /// Returns the number of faces removed.
+    pub fn remove_degenerate_faces(&mut self, area_eps: f32) -> Result<usize, Error> {
+        let degenerate: Vec<u32> = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            self.faces_u32()
+                .filter(|f| self.newell_vector(*f, &points).length() * 0.5 < area_eps)
+                .collect()
+        };
+        for f in degenerate.iter() {
+            self.delete_face_u32(*f, true)?;
+        }
+        self.garbage_collection()?;
+        Ok(degenerate.len())
+    }
+
+    /// Delete the faces that have the same set of vertices as an earlier
+    /// face, such as the two sides of a pillow left behind by merging meshes,
+    /// along with the vertices left isolated. If any faces were removed, the
+    /// mesh is garbage collected afterwards, which invalidates all handles;
+    /// otherwise the mesh is left untouched. Returns the number of faces
+    /// removed.
+    pub fn remove_duplicate_faces(&mut self) -> Result<usize, Error> {
+        let mut seen = HashSet::with_capacity(self.num_faces());
+        let duplicates: Vec<u32> = self
+            .faces_u32()
+            .filter(|f| {
+                let mut verts: Vec<u32> = self.fv_ccw_iter_u32(*f).collect();
+                verts.sort();
+                !seen.insert(verts)
+            })
+            .collect();
+        if duplicates.is_empty() {
+            return Ok(0);
+        }
+        for f in duplicates.iter() {
+            self.delete_face_u32(*f, true)?;
+        }
+        self.garbage_collection()?;
+        Ok(duplicates.len())
+    }
+
+    /// Fill the hole bounded by the loop of boundary halfedges starting at
+    /// `h`. Holes with up to six vertices are filled with a single face,
+    /// larger holes are filled with a fan of triangles around a new vertex at
+    /// the centroid of the loop. Returns the first of the new faces; any
+    /// others follow it consecutively.
+    pub fn fill_hole(&mut self, h: HalfedgeHandle) -> Result<FaceHandle, Error> {
+        let h = h.0;
+        self.check_halfedge(h)?;
+        if !self.is_boundary_halfedge_u32(h) {
+            return Err(Error::NotBoundaryHalfedge(h));
+        }
+        let verts: Vec<u32> = self
+            .boundary_loop_iter_u32(h)
+            .map(|h| self.from_vertex_u32(h))
+            .collect();
+        if verts.len() <= MAX_HOLE_FACE_SIZE {
+            return self.add_face_u32(&verts).map(FaceHandle);
+        }
+        let mut centroid = glam::Vec3::zero();
+        for v in verts.iter() {
+            centroid += self.point_u32(*v)?;
+        }
+        let center = self.add_vertex_u32(centroid / verts.len() as f32)?;
+        let first = self.num_faces() as u32;
+        for (i, v) in verts.iter().enumerate() {
+            self.add_tri_face_u32(*v, verts[(i + 1) % verts.len()], center)?;
+        }
+        Ok(FaceHandle(first))
+    }
+
+    /// Merge the boundary edges `e0` and `e1` into a single interior edge,
+    /// closing a seam between them. The faces of the two edges must be
+    /// oriented consistently across the seam, i.e. the edges run in opposite
+    /// directions along their faces. The ends of `e1` are merged into the ends
+    /// of `e0`, keeping the positions of `e0`, and `e1` and its vertices are
+    /// marked as deleted. The halfedge of `e0` that was on the boundary takes
+    /// the properties of the halfedge of `e1` with a face. Stitching that would
+    /// fold a face onto itself, or create duplicate edges, is not allowed.
+    pub fn stitch_edges(&mut self, e0: EdgeHandle, e1: EdgeHandle) -> Result<(), Error> {
+        let e0 = e0.0;
+        let e1 = e1.0;
+        self.check_halfedge(e0 << 1)?;
+        self.check_halfedge(e1 << 1)?;
+        let (h0, b0) = self.seam_halfedges(e0)?;
+        let (h1, b1) = self.seam_halfedges(e1)?;
+        let (a, b) = (self.from_vertex_u32(h0), self.to_vertex_u32(h0));
+        let (c, d) = (self.from_vertex_u32(h1), self.to_vertex_u32(h1));
+        if e0 == e1
+            || a == c
+            || b == d
+            || self.next_halfedge_u32(h0) == h1
+            || self.next_halfedge_u32(h1) == h0
+        {
+            return Err(Error::ComplexEdge(e1));
+        }
+        // After merging, the only shared neighbor allowed is across the seam.
+        let merged = |v: u32| {
+            if v == c {
+                b
+            } else if v == d {
+                a
+            } else {
+                v
+            }
+        };
+        for (keep, gone, across) in [(b, c, a), (a, d, b)] {
+            if keep == gone {
+                continue;
+            }
+            let mut nbs: Vec<u32> = self
+                .vv_ccw_iter_u32(keep)
+                .chain(self.vv_ccw_iter_u32(gone))
+                .map(merged)
+                .collect();
+            if let Some(i) = nbs.iter().position(|v| *v == across) {
+                nbs.swap_remove(i);
+            }
+            nbs.sort_unstable();
+            if nbs.contains(&keep) || nbs.windows(2).any(|w| w[0] == w[1]) {
+                return Err(Error::ComplexVertex(gone));
+            }
+        }
+        let outgoing = |v: u32, keep: u32| -> Vec<u32> {
+            if v == keep {
+                Vec::new()
+            } else {
+                self.voh_ccw_iter_u32(v).collect()
+            }
+        };
+        let (cout, dout) = (outgoing(c, b), outgoing(d, a));
+        // Take both boundary halfedges out of their loops.
+        let (p0, n0) = (self.prev_halfedge_u32(b0), self.next_halfedge_u32(b0));
+        let (p1, n1) = (self.prev_halfedge_u32(b1), self.next_halfedge_u32(b1));
+        if n0 != b1 {
+            self.link_halfedges(p1, n0);
+        }
+        if n1 != b0 {
+            self.link_halfedges(p0, n1);
+        }
+        // The boundary halfedge of e0 replaces the halfedge of e1.
+        let (f1, prev1, next1) = (
+            self.halfedge_face_u32(h1),
+            self.prev_halfedge_u32(h1),
+            self.next_halfedge_u32(h1),
+        );
+        self.halfedge_mut(b0).face = f1;
+        self.link_halfedges(prev1, b0);
+        self.link_halfedges(b0, next1);
+        if let Some(f1) = f1 {
+            if self.faces[f1 as usize].halfedge == h1 {
+                self.faces[f1 as usize].halfedge = b0;
+            }
+        }
+        self.hprops.copy(h1 as usize, b0 as usize)?;
+        for (gone, keep, hs) in [(c, b, cout), (d, a, dout)] {
+            if gone == keep {
+                continue;
+            }
+            for h in hs {
+                let oh = self.opposite_halfedge_u32(h);
+                self.halfedge_mut(oh).vertex = keep;
+            }
+            self.vertices[gone as usize].halfedge = None;
+            self.vertices[gone as usize].deleted = true;
+        }
+        self.edges[e1 as usize].deleted = true;
+        self.vertices[b as usize].halfedge = Some(b0);
+        self.vertices[a as usize].halfedge = Some(h0);
+        self.adjust_outgoing_halfedge(a);
+        self.adjust_outgoing_halfedge(b);
+        Ok(())
+    }
+
+    /// The halfedges of a boundary edge, the one with a face first.
+    fn seam_halfedges(&self, e: u32) -> Result<(u32, u32), Error> {
+        let h = e << 1;
+        match (
+            self.is_boundary_halfedge_u32(h),
+            self.is_boundary_halfedge_u32(h | 1),
+        ) {
+            (false, true) => Ok((h, h | 1)),
+            (true, false) => Ok((h | 1, h)),
+            _ => Err(Error::NotBoundaryEdge(e)),
+        }
+    }
+
+    /// Check whether the edge can be flipped. Boundary edges, edges not
+    /// shared by two triangles, and flips that would duplicate an existing
+    /// edge are not allowed, and neither are edges out of range.
+    pub fn is_flip_ok(&self, e: EdgeHandle) -> bool {
+        self.is_flip_ok_u32(e.0)
+    }
+
+    /// Same as `is_flip_ok`, with plain indices.
+    pub(crate) fn is_flip_ok_u32(&self, e: u32) -> bool {
+        let h = e << 1;
+        let oh = h | 1;
+        if self.check_edge(e).is_err() || self.is_edge_deleted_u32(e) {
+            return false;
+        }
+        match (self.halfedge_face_u32(h), self.halfedge_face_u32(oh)) {
+            (Some(f0), Some(f1)) => {
+                if self.face_valence_u32(f0) != 3 || self.face_valence_u32(f1) != 3 {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+        let a = self.to_vertex_u32(self.next_halfedge_u32(h));
+        let b = self.to_vertex_u32(self.next_halfedge_u32(oh));
+        a != b && !self.vv_ccw_iter_u32(a).any(|v| v == b)
+    }
+
+    /// Flip the edge shared by two triangles, so that it connects the two
+    /// vertices opposite to it. Use `is_flip_ok` to check the flip is valid
+    /// first.
+    pub fn flip_edge(&mut self, e: EdgeHandle) {
+        self.flip_edge_u32(e.0)
+    }
+
+    /// Same as `flip_edge`, with plain indices.
+    pub(crate) fn flip_edge_u32(&mut self, e: u32) {
+        let a0 = e << 1;
+        let b0 = a0 | 1;
+        let a1 = self.next_halfedge_u32(a0);
+        let a2 = self.next_halfedge_u32(a1);
+        let b1 = self.next_halfedge_u32(b0);
+        let b2 = self.next_halfedge_u32(b1);
+        let va0 = self.to_vertex_u32(a0);
+        let va1 = self.to_vertex_u32(a1);
+        let vb0 = self.to_vertex_u32(b0);
+        let vb1 = self.to_vertex_u32(b1);
+        let fa = self.halfedge_face_u32(a0);
+        let fb = self.halfedge_face_u32(b0);
+        self.halfedge_mut(a0).vertex = va1;
+        self.halfedge_mut(b0).vertex = vb1;
+        self.link_halfedges(a0, a2);
+        self.link_halfedges(a2, b1);
+        self.link_halfedges(b1, a0);
+        self.link_halfedges(b0, b2);
+        self.link_halfedges(b2, a1);
+        self.link_halfedges(a1, b0);
+        self.halfedge_mut(a1).face = fb;
+        self.halfedge_mut(b1).face = fa;
+        if let Some(fa) = fa {
+            self.faces[fa as usize].halfedge = a0;
+        }
+        if let Some(fb) = fb {
+            self.faces[fb as usize].halfedge = b0;
+        }
+        if self.vertex_halfedge_u32(va0) == Some(b0) {
+            self.vertices[va0 as usize].halfedge = Some(a1);
+        }
+        if self.vertex_halfedge_u32(vb0) == Some(a0) {
+            self.vertices[vb0 as usize].halfedge = Some(b1);
+        }
+    }
+
+    /// The vertices of the two triangles that flipping the edge would create,
+    /// without flipping it. The triangles are in the same order as the faces
+    /// of the two halfedges of the edge, and their vertices are in the order
+    /// `fv_ccw_iter` would visit them after `flip_edge`. Returns `None` if
+    /// the edge can't be flipped, as checked by `is_flip_ok`.
+    pub fn flip_preview(&self, e: EdgeHandle) -> Option<([VertexHandle; 3], [VertexHandle; 3])> {
+        let e = e.0;
+        if !self.is_flip_ok_u32(e) {
+            return None;
+        }
+        let h = e << 1;
+        let oh = h | 1;
+        let (a, b) = (self.to_vertex_u32(h), self.to_vertex_u32(oh));
+        let c = self.to_vertex_u32(self.next_halfedge_u32(h));
+        let d = self.to_vertex_u32(self.next_halfedge_u32(oh));
+        Some(([c, b, d].map(VertexHandle), [d, a, c].map(VertexHandle)))
+    }
+
+    /// Reverse both halfedges of the edge, swapping their ends and their
+    /// links.
+    fn reverse_edge(&mut self, e: u32) {
+        let [a, b] = &mut self.edges[e as usize].halfedges;
+        std::mem::swap(&mut a.vertex, &mut b.vertex);
+        for he in [a, b] {
+            std::mem::swap(&mut he.next, &mut he.prev);
+        }
+    }
+
+    /// Reverse the winding of every face, so that all the face normals point
+    /// the other way.
+    pub fn flip_face_normals(&mut self) {
+        for e in 0..(self.num_edges() as u32) {
+            if !self.is_edge_deleted_u32(e) {
+                self.reverse_edge(e);
+            }
+        }
+        for v in 0..(self.num_vertices() as u32) {
+            if self.is_vertex_deleted_u32(v) {
+                continue;
+            }
+            // The outgoing halfedge is now incoming.
+            if let Some(h) = self.vertex_halfedge_u32(v) {
+                self.vertices[v as usize].halfedge = Some(self.opposite_halfedge_u32(h));
+                self.adjust_outgoing_halfedge(v);
+            }
+        }
+    }
+
+    /// Make every closed connected component face outwards, by reversing the
+    /// components that enclose a negative signed volume. This does not
+    /// compare the windings of neighboring faces: opposite windings across an
+    /// edge can't be represented by the halfedge structure, so each component
+    /// is already consistent on its own. Conflicting windings in imported
+    /// triangles are resolved by `from_triangle_soup` before the faces are
+    /// added. Components with a boundary have no inside and are left as they
+    /// are. Returns the number of faces flipped.
+    pub fn orient_outwards(&mut self) -> Result<usize, Error> {
+        let labels = self.connected_components();
+        let ncomponents = labels
+            .iter()
+            .filter(|l| **l != u32::MAX)
+            .max()
+            .map_or(0, |l| *l as usize + 1);
+        let mut volumes = vec![0.0f32; ncomponents];
+        let mut closed = vec![true; ncomponents];
+        {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let mut fverts = Vec::new();
+            for f in self.faces_u32() {
+                let c = labels[f as usize] as usize;
+                if self
+                    .fh_ccw_iter_u32(f)
+                    .any(|h| self.is_boundary_halfedge_u32(self.opposite_halfedge_u32(h)))
+                {
+                    closed[c] = false;
+                }
+                fverts.clear();
+                fverts.extend(self.fv_ccw_iter_u32(f).map(|v| points[v as usize]));
+                for i in 1..(fverts.len() - 1) {
+                    volumes[c] += fverts[0].dot(fverts[i].cross(fverts[i + 1]));
+                }
+            }
+        }
+        let flip: Vec<bool> = (0..ncomponents)
+            .map(|c| closed[c] && volumes[c] < 0.0)
+            .collect();
+        // Closed components have faces on both sides of every edge.
+        let flipped = |mesh: &Mesh, h: u32| {
+            mesh.halfedge_face_u32(h)
+                .is_some_and(|f| flip[labels[f as usize] as usize])
+        };
+        let edges: Vec<u32> = self.edges_u32().filter(|e| flipped(self, e << 1)).collect();
+        for e in edges {
+            self.reverse_edge(e);
+        }
+        let verts: Vec<u32> = self
+            .vertices_u32()
+            .filter(|v| {
+                self.vertex_halfedge_u32(*v)
+                    .is_some_and(|h| flipped(self, h))
+            })
+            .collect();
+        for v in verts {
+            if let Some(h) = self.vertex_halfedge_u32(v) {
+                // The outgoing halfedge is now incoming.
+                self.vertices[v as usize].halfedge = Some(self.opposite_halfedge_u32(h));
+            }
+        }
+        Ok(self
+            .faces_u32()
+            .filter(|f| flip[labels[*f as usize] as usize])
+            .count())
+    }
+
+    /// Reverse the winding of a single face. This is only possible when the
+    /// face is not connected to any other face, because its neighbors would
+    /// otherwise end up with inconsistent orientations.
+    pub fn flip_face(&mut self, f: FaceHandle) -> Result<(), Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        let halfedges: Vec<u32> = self.fh_ccw_iter_u32(f).collect();
+        for h in halfedges.iter() {
+            if !self.is_boundary_halfedge_u32(self.opposite_halfedge_u32(*h)) {
+                return Err(Error::ComplexEdge(h >> 1));
+            }
+            let v = self.to_vertex_u32(*h);
+            if self.vertex_valence_u32(v) != 2 {
+                return Err(Error::ComplexVertex(v));
+            }
+        }
+        for h in halfedges.iter() {
+            self.reverse_edge(h >> 1);
+        }
+        for h in halfedges {
+            // The boundary halfedge now starts where the face halfedge used to.
+            let v = self.to_vertex_u32(h);
+            self.vertices[v as usize].halfedge = Some(h ^ 1);
+        }
+        Ok(())
+    }
+
+    /// Remove the vertices, edges and faces marked as deleted, and compact
+    /// the remaining elements along with their properties. This invalidates
+    /// all handles; use `garbage_collection_with_remap` to find out where the
+    /// elements went.
+    pub fn garbage_collection(&mut self) -> Result<(), Error> {
+        self.garbage_collection_with_remap().map(|_| ())
+    }
+
+    /// Same as `garbage_collection`, but returns the new index of every
+    /// element, indexed by its old index.
+    pub fn garbage_collection_with_remap(&mut self) -> Result<GcRemap, Error> {
+        fn remap(deleted: impl Iterator<Item = bool>) -> (Vec<Option<u32>>, Vec<u32>) {
+            let mut map = Vec::new();
+            let mut kept = Vec::new();
+            for (i, deleted) in deleted.enumerate() {
+                if deleted {
+                    map.push(None);
+                } else {
+                    map.push(Some(kept.len() as u32));
+                    kept.push(i as u32);
+                }
+            }
+            (map, kept)
+        }
+        let (vmap, vkept) = remap(self.vertices.iter().map(|v| v.deleted));
+        let (emap, ekept) = remap(self.edges.iter().map(|e| e.deleted));
+        let (fmap, fkept) = remap(self.faces.iter().map(|f| f.deleted));
+        // Live elements only refer to other live elements, so the unwraps
+        // below can't fail on a valid mesh.
+        let hmap = |h: u32| {
+            (emap[(h >> 1) as usize].expect("Halfedge must not be deleted") << 1) | (h & 1)
+        };
+        self.vertices.retain(|v| !v.deleted);
+        self.edges.retain(|e| !e.deleted);
+        self.faces.retain(|f| !f.deleted);
+        for v in self.vertices.iter_mut() {
+            v.halfedge = v.halfedge.map(hmap);
+        }
+        for he in self.edges.iter_mut().flat_map(|e| e.halfedges.iter_mut()) {
+            he.vertex = vmap[he.vertex as usize].expect("Vertex must not be deleted");
+            he.next = hmap(he.next);
+            he.prev = hmap(he.prev);
+            he.face = he
+                .face
+                .map(|f| fmap[f as usize].expect("Face must not be deleted"));
+        }
+        for f in self.faces.iter_mut() {
+            f.halfedge = hmap(f.halfedge);
+        }
+        for (i, v) in vkept.iter().enumerate() {
+            self.vprops.copy(*v as usize, i)?;
+        }
+        self.vprops.resize(vkept.len())?;
+        for (i, f) in fkept.iter().enumerate() {
+            self.fprops.copy(*f as usize, i)?;
+        }
+        self.fprops.resize(fkept.len())?;
+        for (i, e) in ekept.iter().enumerate() {
+            self.eprops.copy(*e as usize, i)?;
+            self.hprops.copy((*e as usize) << 1, i << 1)?;
+            self.hprops.copy(((*e as usize) << 1) | 1, (i << 1) | 1)?;
+        }
+        self.eprops.resize(ekept.len())?;
+        self.hprops.resize(ekept.len() * 2)?;
+        Ok(GcRemap {
+            vertices: vmap.into_iter().map(|v| v.map(VertexHandle)).collect(),
+            edges: emap.into_iter().map(|e| e.map(EdgeHandle)).collect(),
+            faces: fmap.into_iter().map(|f| f.map(FaceHandle)).collect(),
+        })
+    }
+
+    /// Reorder the vertices, so that the `i`-th vertex is the one that was
+    /// at `new_order[i]`, along with its properties. This is useful to
+    /// improve the locality of the vertices in memory after many edits, for
+    /// example with an order given by a space filling curve. `new_order` must
+    /// contain every vertex exactly once, including deleted vertices.
+    pub fn reorder_vertices(&mut self, new_order: &[VertexHandle]) -> Result<(), Error> {
+        let n = self.num_vertices();
+        if new_order.len() != n {
+            return Err(Error::LengthMismatch(n, new_order.len()));
+        }
+        let mut new_index = vec![u32::MAX; n];
+        for (i, v) in new_order.iter().enumerate() {
+            match new_index.get_mut(v.0 as usize) {
+                Some(slot) if *slot == u32::MAX => *slot = i as u32,
+                _ => return Err(Error::InvalidPermutation),
+            }
+        }
+        // Apply the permutation one cycle at a time by swapping.
+        let mut visited = vec![false; n];
+        for start in 0..n {
+            let mut i = start;
+            while !visited[i] {
+                visited[i] = true;
+                let next = new_order[i].0 as usize;
+                if next == start {
+                    break;
+                }
+                self.vertices.swap(i, next);
+                self.vprops.swap(i, next)?;
+                i = next;
+            }
+        }
+        for he in self.edges.iter_mut().flat_map(|e| e.halfedges.iter_mut()) {
+            he.vertex = new_index[he.vertex as usize];
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{
+        test::{quad_box, quad_grid, tri_grid, uv_sphere},
+        EdgeHandle, Error, FaceHandle, HalfedgeHandle, Mesh, VertexHandle,
+    };
+
+    #[test]
+    fn t_split_edge() {
+        let mut mesh = tri_grid(2, 2);
+        let (nv, ne, nf) = (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces());
+        // An interior edge, shared by two triangles.
+        let e = (0..(ne as u32))
+            .find(|e| {
+                !mesh.is_boundary_halfedge_u32(e << 1)
+                    && !mesh.is_boundary_halfedge_u32((e << 1) | 1)
+            })
+            .unwrap();
+        let mid = (mesh.point_u32(mesh.to_vertex_u32(e << 1)).unwrap()
+            + mesh.point_u32(mesh.from_vertex_u32(e << 1)).unwrap())
+            * 0.5;
+        let v = mesh.split_edge_u32(e, mid).unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), nv + 1);
+        assert_eq!(mesh.num_edges(), ne + 3);
+        assert_eq!(mesh.num_faces(), nf + 2);
+        assert_eq!(mesh.vf_ccw_iter_u32(v).count(), 4);
+        // A boundary edge.
+        let e = (0..(mesh.num_edges() as u32))
+            .find(|e| {
+                mesh.is_boundary_halfedge_u32(e << 1) || mesh.is_boundary_halfedge_u32((e << 1) | 1)
+            })
+            .unwrap();
+        let v = mesh.split_edge_u32(e, glam::Vec3::zero()).unwrap();
+        mesh.validate().unwrap();
+        assert!(mesh.is_boundary_vertex_u32(v));
+        assert_eq!(mesh.vf_ccw_iter_u32(v).count(), 2);
+        assert_eq!(mesh.num_faces(), nf + 3);
+        assert!(matches!(
+            mesh.split_edge_u32(mesh.num_edges() as u32, glam::Vec3::zero()),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        // Quads can't be split.
+        let mut mesh = quad_grid(2, 1);
+        let e = mesh.edge_between(VertexHandle(1), VertexHandle(4)).unwrap();
+        assert!(matches!(
+            mesh.split_edge(e, glam::vec3(1.0, 0.5, 0.0)),
+            Err(Error::NotATriangle(_))
+        ));
+        assert_eq!(mesh.num_vertices(), 6);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_split_face() {
+        let mut mesh = quad_box();
+        let mut fprop = mesh.create_face_prop::<u32>();
+        fprop.set(0, 7).unwrap();
+        let corners: Vec<_> = mesh.fv_ccw_iter(FaceHandle(0)).collect();
+        let v = mesh
+            .split_face(FaceHandle(0), glam::vec3(0.5, 0.5, 0.0))
+            .unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(v, VertexHandle(8));
+        assert_eq!(mesh.num_faces(), 6 + 3);
+        assert_eq!(mesh.num_edges(), 12 + 4);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert_eq!(mesh.vertex_valence(v), 4);
+        assert!(!mesh.is_boundary_vertex(v));
+        let fan: Vec<_> = mesh.vf_ccw_iter(v).collect();
+        assert_eq!(fan.len(), 4);
+        for f in fan {
+            assert_eq!(mesh.face_valence(f), 3);
+            assert_eq!(fprop.get(f.into()).unwrap(), 7);
+        }
+        let mut ring: Vec<_> = mesh.vv_ccw_iter(v).collect();
+        ring.sort();
+        let mut corners = corners;
+        corners.sort();
+        assert_eq!(ring, corners);
+        assert!(matches!(
+            mesh.split_face(FaceHandle(100), glam::Vec3::zero()),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_remove_edge() {
+        let mut mesh = quad_box();
+        let mut fprop = mesh.create_face_prop::<u32>();
+        for f in 0..6 {
+            fprop.set(f, f).unwrap();
+        }
+        let positions: Vec<glam::Vec3> = mesh
+            .fv_ccw_iter_u32(0)
+            .map(|v| mesh.point_u32(v).unwrap())
+            .collect();
+        mesh.triangulate().unwrap();
+        assert_eq!(mesh.num_faces(), 12);
+        // The diagonal added to the first face.
+        let diagonal = (12..(mesh.num_edges() as u32))
+            .find(|e| fprop.get(mesh.halfedge_face_u32(e << 1).unwrap()).unwrap() == 0)
+            .unwrap();
+        let f = mesh.remove_edge(EdgeHandle(diagonal)).unwrap();
+        assert_eq!(mesh.face_valence(f), 4);
+        assert_eq!(fprop.get(f.into()).unwrap(), 0);
+        mesh.garbage_collection().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 11);
+        assert_eq!(mesh.num_edges(), 17);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        let quad: Vec<u32> = mesh
+            .faces_u32()
+            .filter(|f| mesh.face_valence_u32(*f) == 4)
+            .collect();
+        assert_eq!(quad.len(), 1);
+        assert_eq!(fprop.get(quad[0]).unwrap(), 0);
+        // The merged quad has the same corners, in the same cyclic order.
+        let merged: Vec<glam::Vec3> = mesh
+            .fv_ccw_iter_u32(quad[0])
+            .map(|v| mesh.point_u32(v).unwrap())
+            .collect();
+        let start = merged.iter().position(|p| *p == positions[0]).unwrap();
+        for (i, p) in positions.iter().enumerate() {
+            assert_eq!(merged[(start + i) % 4], *p);
+        }
+    }
+
+    #[test]
+    fn t_remove_edge_errors() {
+        let mut mesh = quad_grid(2, 1);
+        let boundary = (0..(mesh.num_edges() as u32))
+            .find(|e| {
+                mesh.is_boundary_halfedge_u32(e << 1) || mesh.is_boundary_halfedge_u32((e << 1) | 1)
+            })
+            .unwrap();
+        assert!(matches!(
+            mesh.remove_edge(EdgeHandle(boundary)),
+            Err(Error::BoundaryEdge(e)) if e == boundary
+        ));
+        // Two triangles sharing two edges, around a vertex of valence 2.
+        let mut mesh = Mesh::new();
+        for p in [[0.0, 0.0], [2.0, 0.0], [1.0, 2.0], [1.0, 0.5]] {
+            mesh.add_vertex_u32(glam::vec3(p[0], p[1], 0.0)).unwrap();
+        }
+        mesh.add_face_u32(&[0, 1, 2]).unwrap();
+        mesh.add_face_u32(&[0, 3, 1]).unwrap();
+        mesh.add_face_u32(&[1, 3, 2]).unwrap();
+        mesh.add_face_u32(&[2, 3, 0]).unwrap();
+        mesh.validate().unwrap();
+        let e = mesh
+            .voh_ccw_iter_u32(3)
+            .find(|h| mesh.to_vertex_u32(*h) == 0)
+            .unwrap()
+            >> 1;
+        assert!(mesh.remove_edge(EdgeHandle(e)).is_ok());
+        let e = mesh
+            .voh_ccw_iter_u32(3)
+            .find(|h| mesh.to_vertex_u32(*h) == 1)
+            .unwrap()
+            >> 1;
+        assert!(matches!(
+            mesh.remove_edge(EdgeHandle(e)),
+            Err(Error::ComplexEdge(_))
+        ));
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_flip_edge() {
+        let mut mesh = tri_grid(1, 1);
+        let e = (0..(mesh.num_edges() as u32))
+            .find(|e| mesh.is_flip_ok_u32(*e))
+            .unwrap();
+        let before = [mesh.from_vertex_u32(e << 1), mesh.to_vertex_u32(e << 1)];
+        mesh.flip_edge_u32(e);
+        mesh.validate().unwrap();
+        let after = [mesh.from_vertex_u32(e << 1), mesh.to_vertex_u32(e << 1)];
+        assert!(after.iter().all(|v| !before.contains(v)));
+        // Boundary edges can't be flipped.
+        assert_eq!(
+            (0..(mesh.num_edges() as u32))
+                .filter(|e| mesh.is_flip_ok_u32(*e))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn t_flip_preview() {
+        let mut mesh = tri_grid(2, 2);
+        for e in mesh.edges() {
+            let Some((ta, tb)) = mesh.flip_preview(e) else {
+                assert!(!mesh.is_flip_ok(e));
+                continue;
+            };
+            let mut flipped = tri_grid(2, 2);
+            flipped.flip_edge(e);
+            let (fa, fb) = flipped.edge_faces(e);
+            assert!(flipped.fv_ccw_iter(fa.unwrap()).eq(ta));
+            assert!(flipped.fv_ccw_iter(fb.unwrap()).eq(tb));
+        }
+        // Boundary edges and quads have no preview.
+        let e = mesh.edges().find(|e| mesh.is_boundary_edge(*e)).unwrap();
+        assert_eq!(mesh.flip_preview(e), None);
+        let quads = quad_grid(2, 2);
+        assert!(quads.edges().all(|e| quads.flip_preview(e).is_none()));
+        assert!(!mesh.is_flip_ok_u32(99));
+        assert_eq!(mesh.flip_preview(EdgeHandle(99)), None);
+        // Previewing doesn't change the mesh.
+        let e = mesh.edges().find(|e| mesh.is_flip_ok(*e)).unwrap();
+        let (ta, tb) = mesh.flip_preview(e).unwrap();
+        mesh.validate().unwrap();
+        mesh.flip_edge(e);
+        let (fa, fb) = mesh.edge_faces(e);
+        assert!(mesh.fv_ccw_iter(fa.unwrap()).eq(ta));
+        assert!(mesh.fv_ccw_iter(fb.unwrap()).eq(tb));
+    }
+
+    /// An open cylinder of `n` quads around the z-axis, with a seam where
+    /// the first and the last columns of vertices coincide but aren't shared.
+    fn split_cylinder(n: u32) -> Mesh {
+        use std::f32::consts::TAU;
+        let mut mesh = Mesh::new();
+        for z in [0.0, 1.0] {
+            for i in 0..=n {
+                let t = TAU * (i % n) as f32 / n as f32;
+                mesh.add_vertex_u32(glam::vec3(t.cos(), t.sin(), z))
+                    .unwrap();
+            }
+        }
+        for i in 0..n {
+            let top = n + 1 + i;
+            mesh.add_face_u32(&[i, i + 1, top + 1, top]).unwrap();
+        }
+        mesh
+    }
+
+    /// The edge between the vertices `a` and `b`, which must exist.
+    fn edge(mesh: &Mesh, a: u32, b: u32) -> EdgeHandle {
+        mesh.edge_between(VertexHandle(a), VertexHandle(b))
+            .expect("Edge must exist")
+    }
+
+    #[test]
+    fn t_stitch_edges() {
+        let n = 6;
+        let mut mesh = split_cylinder(n);
+        assert_eq!(mesh.boundary_loop_count(), 1);
+        let e0 = edge(&mesh, 0, n + 1);
+        let e1 = edge(&mesh, n, 2 * n + 1);
+        assert!(mesh.is_boundary_edge(e0) && mesh.is_boundary_edge(e1));
+        mesh.stitch_edges(e0, e1).unwrap();
+        mesh.validate().unwrap();
+        assert!(!mesh.is_boundary_edge(e0));
+        assert!(mesh.is_edge_deleted(e1));
+        assert!(mesh.is_vertex_deleted_u32(n) && mesh.is_vertex_deleted_u32(2 * n + 1));
+        let (f0, f1) = mesh.edge_faces_u32(e0.0);
+        let mut faces = [f0.unwrap(), f1.unwrap()];
+        faces.sort();
+        assert_eq!(faces, [0, n - 1]);
+        assert!(mesh.fv_ccw_iter_u32(n - 1).eq([n - 1, 0, n + 1, 2 * n]));
+        mesh.garbage_collection().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices() as u32, 2 * n);
+        assert_eq!(mesh.num_edges() as u32, 3 * n);
+        assert_eq!(mesh.boundary_loop_count(), 2);
+        assert_eq!(mesh.euler_characteristic(), 0);
+        assert!(mesh.vertices_u32().all(|v| mesh.vertex_valence_u32(v) == 3));
+        assert!(mesh.is_manifold());
+    }
+
+    #[test]
+    fn t_stitch_edges_invalid() {
+        let n = 4;
+        let mut mesh = split_cylinder(n);
+        let seam = edge(&mesh, 0, n + 1);
+        let interior = edge(&mesh, 1, n + 2);
+        assert!(matches!(
+            mesh.stitch_edges(seam, interior),
+            Err(Error::NotBoundaryEdge(e)) if e == interior.0
+        ));
+        assert!(matches!(
+            mesh.stitch_edges(seam, seam),
+            Err(Error::ComplexEdge(_))
+        ));
+        // The next edge along the bottom of the same face would fold it.
+        let bottom = edge(&mesh, 0, 1);
+        assert!(matches!(
+            mesh.stitch_edges(seam, bottom),
+            Err(Error::ComplexEdge(_))
+        ));
+        // Merging the ends of these would duplicate the edges between them.
+        let top = edge(&mesh, n + 2, n + 3);
+        let bottom = edge(&mesh, 2, 3);
+        assert!(matches!(
+            mesh.stitch_edges(top, bottom),
+            Err(Error::ComplexVertex(2))
+        ));
+        mesh.validate().unwrap();
+        assert_eq!(mesh.boundary_loop_count(), 1);
+    }
+
+    #[test]
+    fn t_collapse_halfedge() {
+        let mut mesh = tri_grid(3, 3);
+        let mut fprop = mesh.create_face_prop::<u32>();
+        for f in 0..(mesh.num_faces() as u32) {
+            fprop.set(f, f).unwrap();
+        }
+        // Vertex 5 is interior to the grid. Collapse it into its neighbor.
+        let h = mesh.voh_ccw_iter_u32(5).next().unwrap();
+        assert!(mesh.is_collapse_ok_u32(h));
+        let (nv, ne, nf) = (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces());
+        mesh.collapse_halfedge_u32(h);
+        assert!(mesh.is_vertex_deleted_u32(5));
+        mesh.garbage_collection().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), nv - 1);
+        assert_eq!(mesh.num_edges(), ne - 3);
+        assert_eq!(mesh.num_faces(), nf - 2);
+        assert_eq!(mesh.euler_characteristic(), 1);
+        // The surviving faces kept their properties, in order.
+        let values: Vec<u32> = (0..(mesh.num_faces() as u32))
+            .map(|f| fprop.get(f).unwrap())
+            .collect();
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        // The diagonal of a single quad connects two boundary vertices, so
+        // collapsing it would pinch the mesh.
+        let mesh = tri_grid(1, 1);
+        let h = mesh.find_halfedge(0, 3).unwrap();
+        assert!(!mesh.is_collapse_ok_u32(h));
+        assert!(!mesh.is_collapse_ok_u32(h ^ 1));
+        assert!(mesh.is_collapse_ok_u32(mesh.find_halfedge(0, 1).unwrap()));
+    }
+
+    #[test]
+    fn t_collapse_short_edges() {
+        let mut mesh = tri_grid(3, 3);
+        let (nv, ne, nf) = (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces());
+        let positions = mesh.points_snapshot().unwrap();
+        // An interior edge, split very close to one of its ends.
+        let e = (0..(ne as u32))
+            .find(|e| {
+                !mesh.is_boundary_vertex_u32(mesh.from_vertex_u32(e << 1))
+                    && !mesh.is_boundary_vertex_u32(mesh.to_vertex_u32(e << 1))
+            })
+            .unwrap();
+        let (a, b) = (
+            mesh.point_u32(mesh.from_vertex_u32(e << 1)).unwrap(),
+            mesh.point_u32(mesh.to_vertex_u32(e << 1)).unwrap(),
+        );
+        mesh.split_edge_u32(e, a + (b - a) * 1e-3).unwrap();
+        assert_eq!(mesh.num_vertices(), nv + 1);
+        assert_eq!(mesh.collapse_short_edges(0.01).unwrap(), 1);
+        mesh.validate().unwrap();
+        assert_eq!(
+            (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces()),
+            (nv, ne, nf)
+        );
+        // The original vertices are where they were.
+        let mut after = mesh.points_snapshot().unwrap();
+        let mut before = positions;
+        for pts in [&mut after, &mut before] {
+            pts.sort_by(|p, q| p.x().total_cmp(&q.x()).then(p.y().total_cmp(&q.y())));
+        }
+        assert_eq!(after, before);
+        // Nothing else is short.
+        assert_eq!(mesh.collapse_short_edges(0.5).unwrap(), 0);
+        assert_eq!(mesh.num_vertices(), nv);
+    }
+
+    #[test]
+    fn t_flip_face_normals() {
+        let mut mesh = quad_box();
+        let before: Vec<_> = (0..6)
+            .map(|f| mesh.calc_face_normal_u32(f).unwrap())
+            .collect();
+        let faces: Vec<Vec<u32>> = (0..6).map(|f| mesh.fv_ccw_iter_u32(f).collect()).collect();
+        mesh.flip_face_normals();
+        mesh.validate().unwrap();
+        for f in 0..6 {
+            assert_eq!(mesh.calc_face_normal_u32(f).unwrap(), -before[f as usize]);
+            let mut reversed: Vec<u32> = mesh.fv_ccw_iter_u32(f).collect();
+            reversed.reverse();
+            // Same cycle, other direction.
+            let start = reversed
+                .iter()
+                .position(|v| *v == faces[f as usize][0])
+                .unwrap();
+            reversed.rotate_left(start);
+            assert_eq!(reversed, faces[f as usize]);
+        }
+        assert!(mesh.volume().unwrap() < 0.0);
+        // Flipping back restores the original normals.
+        mesh.flip_face_normals();
+        for f in 0..6 {
+            assert_eq!(mesh.calc_face_normal_u32(f).unwrap(), before[f as usize]);
+        }
+        // An open grid keeps its boundary.
+        let mut mesh = tri_grid(2, 2);
+        mesh.flip_face_normals();
+        mesh.validate().unwrap();
+        let nboundary = (0..(mesh.num_vertices() as u32))
+            .filter(|v| mesh.is_boundary_vertex_u32(*v))
+            .count();
+        assert_eq!(nboundary, 8);
+        for f in 0..(mesh.num_faces() as u32) {
+            assert!(mesh.calc_face_normal_u32(f).unwrap().z() < 0.0);
+        }
+    }
+
+    #[test]
+    fn t_orient_outwards() {
+        let mut mesh = quad_box();
+        let mut inside_out = quad_box();
+        inside_out.flip_face_normals();
+        inside_out
+            .transform(glam::Mat4::from_translation(glam::vec3(3.0, 0.0, 0.0)))
+            .unwrap();
+        mesh.append(&inside_out).unwrap();
+        let mut grid = quad_grid(2, 2);
+        grid.flip_face_normals();
+        mesh.append(&grid).unwrap();
+        assert!(mesh.volume().unwrap().abs() < 1e-6);
+        assert_eq!(mesh.orient_outwards().unwrap(), 6);
+        mesh.validate().unwrap();
+        assert!((mesh.volume().unwrap() - 2.0).abs() < 1e-6);
+        // The open grid still faces down.
+        let points = mesh.points_snapshot().unwrap();
+        assert_eq!(mesh.face_normal_from(12, &points), -glam::Vec3::unit_z());
+        assert_eq!(mesh.orient_outwards().unwrap(), 0);
+    }
+
+    #[test]
+    fn t_orient_outwards_reversed_faces() {
+        let (positions, mut triangles, _) = quad_box().to_triangle_buffers().unwrap();
+        let center = glam::Vec3::splat(0.5);
+        let outwards = |mesh: &Mesh| {
+            mesh.faces_u32().all(|f| {
+                let v = mesh.fv_ccw_iter_u32(f).next().unwrap();
+                let normal = mesh.calc_face_normal_u32(f).unwrap();
+                normal.dot(mesh.point_u32(v).unwrap() - center) > 0.0
+            })
+        };
+        // One reversed triangle is fixed while importing the soup.
+        triangles[5].reverse();
+        let (mut mesh, _) = Mesh::from_triangle_soup(&positions, &triangles, None).unwrap();
+        assert_eq!(mesh.orient_outwards().unwrap(), 0);
+        assert!(outwards(&mesh));
+        // When most of the triangles are reversed, the soup is imported
+        // inside out, and the whole box is flipped.
+        triangles[5].reverse();
+        for tri in triangles.iter_mut().take(7) {
+            tri.reverse();
+        }
+        let (mut mesh, _) = Mesh::from_triangle_soup(&positions, &triangles, None).unwrap();
+        assert!(!outwards(&mesh));
+        assert_eq!(mesh.orient_outwards().unwrap(), 12);
+        mesh.validate().unwrap();
+        assert!(outwards(&mesh));
+    }
+
+    #[test]
+    fn t_flip_face() {
+        let mut mesh = Mesh::new();
+        for p in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            mesh.add_vertex_u32(glam::Vec3::from(p)).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        mesh.flip_face(FaceHandle(0)).unwrap();
+        mesh.validate().unwrap();
+        assert!(mesh.fv_ccw_iter_u32(0).eq([2, 1, 0]));
+        assert_eq!(mesh.calc_face_normal_u32(0).unwrap(), -glam::Vec3::unit_z());
+        assert!((0..3).all(|v| mesh.is_boundary_vertex_u32(v)));
+        // Faces with neighbors can't be flipped on their own.
+        let mut mesh = quad_box();
+        assert!(matches!(
+            mesh.flip_face(FaceHandle(0)),
+            Err(Error::ComplexEdge(_))
+        ));
+        assert!(matches!(
+            mesh.flip_face(FaceHandle(99)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_delete_face_and_fill_hole() {
+        let mut mesh = quad_box();
+        mesh.delete_face_u32(5, true).unwrap();
+        // Deleting it again does nothing.
+        mesh.delete_face_u32(5, true).unwrap();
+        assert!(matches!(
+            mesh.delete_face_u32(99, true),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        mesh.garbage_collection().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 5);
+        assert_eq!(mesh.num_edges(), 12);
+        let h = (0..(mesh.num_halfedges() as u32))
+            .find(|h| mesh.is_boundary_halfedge_u32(*h))
+            .unwrap();
+        assert_eq!(mesh.boundary_loop_iter_u32(h).count(), 4);
+        let f = mesh.fill_hole(HalfedgeHandle(h)).unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.face_valence(f), 4);
+        assert_eq!(mesh.num_faces(), 6);
+        assert!((0..(mesh.num_halfedges() as u32)).all(|h| !mesh.is_boundary_halfedge_u32(h)));
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!((mesh.volume().unwrap() - 1.0).abs() < 1e-6);
+        // Interior halfedges are rejected.
+        assert!(matches!(
+            mesh.fill_hole(HalfedgeHandle(0)),
+            Err(Error::NotBoundaryHalfedge(0))
+        ));
+    }
+
+    #[test]
+    fn t_remove_degenerate_faces() {
+        let mut mesh = quad_grid(2, 1);
+        let area = mesh.surface_area().unwrap();
+        // A sliver hanging off the bottom edge, with its apex on the edge.
+        let v = mesh.add_vertex_u32(glam::vec3(0.5, 0.0, 0.0)).unwrap();
+        mesh.add_tri_face_u32(0, v, 1).unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 3);
+        assert_eq!(mesh.remove_degenerate_faces(1e-6).unwrap(), 1);
+        mesh.validate().unwrap();
+        assert_eq!(
+            (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces()),
+            (6, 7, 2)
+        );
+        assert_eq!(mesh.surface_area().unwrap(), area);
+        assert_eq!(mesh.remove_degenerate_faces(1e-6).unwrap(), 0);
+    }
+
+    #[test]
+    fn t_remove_duplicate_faces() {
+        let mut mesh = Mesh::new();
+        let mut flipped = Mesh::new();
+        for p in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            mesh.add_vertex_u32(glam::Vec3::from(p)).unwrap();
+            flipped.add_vertex_u32(glam::Vec3::from(p)).unwrap();
+        }
+        mesh.add_tri_face_u32(0, 1, 2).unwrap();
+        flipped.add_tri_face_u32(0, 2, 1).unwrap();
+        mesh.append(&flipped).unwrap();
+        assert_eq!(mesh.weld_vertices(1e-6).unwrap(), 3);
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 2);
+        assert_eq!(mesh.remove_duplicate_faces().unwrap(), 1);
+        mesh.validate().unwrap();
+        assert_eq!(
+            (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces()),
+            (3, 3, 1)
+        );
+        assert_eq!(mesh.fv_ccw_iter_u32(0).collect::<Vec<_>>(), [0, 1, 2]);
+        // Nothing to remove, so the deleted face is not garbage collected.
+        let mut mesh = quad_box();
+        mesh.delete_face_u32(0, false).unwrap();
+        assert_eq!(mesh.remove_duplicate_faces().unwrap(), 0);
+        assert_eq!(mesh.num_faces(), 6);
+        assert!(mesh.is_face_deleted_u32(0));
+    }
+
+    #[test]
+    fn t_fill_large_hole() {
+        let mut mesh = uv_sphere(1.0, 16, 8);
+        // The fan around the north pole.
+        for f in 0..16 {
+            mesh.delete_face_u32(f, true).unwrap();
+        }
+        assert!(mesh.is_vertex_deleted_u32(0));
+        mesh.garbage_collection().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.euler_characteristic(), 1);
+        let h = (0..(mesh.num_halfedges() as u32))
+            .find(|h| mesh.is_boundary_halfedge_u32(*h))
+            .unwrap();
+        let nfaces = mesh.num_faces();
+        let first = mesh.fill_hole(HalfedgeHandle(h)).unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(first.index(), nfaces);
+        assert_eq!(mesh.num_faces(), nfaces + 16);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        let center = mesh.point_u32(mesh.num_vertices() as u32 - 1).unwrap();
+        assert!(center.x().abs() < 1e-6 && center.y().abs() < 1e-6);
+    }
+
+    #[test]
+    fn t_delete_face_edges() {
+        let mut mesh = tri_grid(1, 1);
+        mesh.delete_face_u32(0, true).unwrap();
+        mesh.validate().unwrap();
+        // Two boundary edges and the corner vertex between them go away.
+        assert_eq!((0..5).filter(|e| mesh.is_edge_deleted_u32(*e)).count(), 2);
+        assert_eq!((0..4).filter(|v| mesh.is_vertex_deleted_u32(*v)).count(), 1);
+        mesh.garbage_collection().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(
+            (mesh.num_vertices(), mesh.num_edges(), mesh.num_faces()),
+            (3, 3, 1)
+        );
+    }
+
+    #[test]
+    fn t_garbage_collection_remap() {
+        let mut mesh = quad_grid(3, 1);
+        let mut fprop = mesh.create_face_prop::<u32>();
+        fprop.from_slice(&[10, 11, 12]).unwrap();
+        let fv: Vec<Vec<u32>> = (0..3).map(|f| mesh.fv_ccw_iter_u32(f).collect()).collect();
+        let positions = mesh.points.to_vec().unwrap();
+        mesh.delete_face_u32(0, true).unwrap();
+        mesh.delete_face_u32(1, true).unwrap();
+        let remap = mesh.garbage_collection_with_remap().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(remap.faces, vec![None, None, Some(FaceHandle(0))]);
+        assert_eq!(remap.vertices.iter().flatten().count(), 4);
+        assert_eq!(remap.edges.iter().flatten().count(), 4);
+        assert_eq!(remap.vertices.len(), 8);
+        // The old handle of the surviving face still finds the same face.
+        let f = remap.faces[2].unwrap();
+        assert_eq!(fprop.get(f.into()).unwrap(), 12);
+        let expected: Vec<_> = fv[2]
+            .iter()
+            .map(|v| remap.vertices[*v as usize].unwrap())
+            .collect();
+        assert!(mesh.fv_ccw_iter(f).eq(expected));
+        for (old, new) in remap.vertices.iter().enumerate() {
+            if let Some(new) = new {
+                assert_eq!(mesh.point(*new).unwrap(), positions[old]);
+            }
+        }
+    }
+
+    #[test]
+    fn t_reorder_vertices() {
+        let mut mesh = quad_box();
+        let points = mesh.points_snapshot().unwrap();
+        let mut tags = mesh.create_vertex_prop::<u32>();
+        for v in 0..8 {
+            tags.set(v, 100 + v).unwrap();
+        }
+        let fverts: Vec<Vec<u32>> = mesh
+            .faces_u32()
+            .map(|f| mesh.fv_ccw_iter_u32(f).collect())
+            .collect();
+        let order: Vec<_> = (0..8).rev().map(VertexHandle).collect();
+        mesh.reorder_vertices(&order).unwrap();
+        mesh.validate().unwrap();
+        for v in 0..8u32 {
+            assert_eq!(mesh.point_u32(v).unwrap(), points[(7 - v) as usize]);
+            assert_eq!(tags.get(v).unwrap(), 107 - v);
+        }
+        for (f, verts) in mesh.faces_u32().zip(fverts) {
+            assert!(mesh.fv_ccw_iter_u32(f).eq(verts.iter().map(|v| 7 - v)));
+        }
+        // A cycle longer than two.
+        mesh.reorder_vertices(&[1, 2, 3, 0, 4, 5, 6, 7].map(VertexHandle))
+            .unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.point_u32(0).unwrap(), points[6]);
+        assert_eq!(mesh.point_u32(3).unwrap(), points[7]);
+        assert_eq!(tags.get(3).unwrap(), 107);
+        assert!(matches!(
+            mesh.reorder_vertices(&[0, 1, 2].map(VertexHandle)),
+            Err(Error::LengthMismatch(8, 3))
+        ));
+        assert!(matches!(
+            mesh.reorder_vertices(&[0, 1, 2, 3, 4, 5, 6, 6].map(VertexHandle)),
+            Err(Error::InvalidPermutation)
+        ));
+        assert!(matches!(
+            mesh.reorder_vertices(&[0, 1, 2, 3, 4, 5, 6, 8].map(VertexHandle)),
+            Err(Error::InvalidPermutation)
+        ));
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn t_halfedge_props_follow_edits() {
+        let mut mesh = tri_grid(3, 1);
+        let mut hprop = mesh.create_halfedge_prop::<u32>();
+        let ends = |mesh: &Mesh, h: u32| (mesh.from_vertex_u32(h), mesh.to_vertex_u32(h));
+        let tag = |(a, b): (u32, u32)| a * 100 + b;
+        let v = mesh.split_edge_u32(0, glam::vec3(0.5, 0.0, 0.0)).unwrap();
+        assert_eq!(hprop.to_vec().unwrap().len(), mesh.num_halfedges());
+        // Tag every halfedge with its end points, to compare after the
+        // garbage collection.
+        for h in 0..(mesh.num_halfedges() as u32) {
+            hprop.set(h, tag(ends(&mesh, h))).unwrap();
+        }
+        mesh.delete_face_u32(0, true).unwrap();
+        let remap = mesh.garbage_collection_with_remap().unwrap();
+        assert!(remap.vertices[v as usize].is_some());
+        assert!(remap.edges.iter().any(|e| e.is_none()));
+        assert_eq!(hprop.to_vec().unwrap().len(), mesh.num_halfedges());
+        for h in 0..(mesh.num_halfedges() as u32) {
+            let (a, b) = ends(&mesh, h);
+            let old = |v: u32| {
+                remap
+                    .vertices
+                    .iter()
+                    .position(|n| *n == Some(VertexHandle(v)))
+                    .unwrap() as u32
+            };
+            assert_eq!(hprop.get(h).unwrap(), tag((old(a), old(b))));
+        }
+    }
+}
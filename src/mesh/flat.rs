@@ -0,0 +1,121 @@
+use super::{Error, Mesh};
+
+/// Connectivity and positions of a mesh laid out in flat buffers, e.g. for
+/// uploading to the GPU. All arrays are indexed by the handles of the mesh,
+/// and missing elements, such as the face of a boundary halfedge, are encoded
+/// as `u32::MAX`. Deleted elements are exported as they are, so call
+/// `garbage_collection` first if they should not be present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatMesh {
+    /// Three coordinates per vertex.
+    pub positions: Vec<f32>,
+    pub vertex_halfedge: Vec<u32>,
+    pub halfedge_next: Vec<u32>,
+    pub halfedge_prev: Vec<u32>,
+    /// The vertex the halfedge points to.
+    pub halfedge_vertex: Vec<u32>,
+    pub halfedge_opposite: Vec<u32>,
+    pub halfedge_face: Vec<u32>,
+    pub face_halfedge: Vec<u32>,
+}
+
+impl Mesh {
+    /// Export the positions and the halfedge connectivity of the mesh as flat
+    /// buffers. See `FlatMesh`.
+    pub fn export_flat(&self) -> Result<FlatMesh, Error> {
+        let positions = self
+            .points_snapshot()?
+            .iter()
+            .flat_map(|p| [p.x(), p.y(), p.z()])
+            .collect();
+        let nh = self.num_halfedges() as u32;
+        Ok(FlatMesh {
+            positions,
+            vertex_halfedge: (0..(self.num_vertices() as u32))
+                .map(|v| self.vertex_halfedge_u32(v).unwrap_or(u32::MAX))
+                .collect(),
+            halfedge_next: (0..nh).map(|h| self.next_halfedge_u32(h)).collect(),
+            halfedge_prev: (0..nh).map(|h| self.prev_halfedge_u32(h)).collect(),
+            halfedge_vertex: (0..nh).map(|h| self.to_vertex_u32(h)).collect(),
+            halfedge_opposite: (0..nh).map(|h| self.opposite_halfedge_u32(h)).collect(),
+            halfedge_face: (0..nh)
+                .map(|h| self.halfedge_face_u32(h).unwrap_or(u32::MAX))
+                .collect(),
+            face_halfedge: (0..(self.num_faces() as u32))
+                .map(|f| self.face_halfedge_u32(f))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::test::{quad_box, quad_grid};
+
+    #[test]
+    fn t_box_export_flat() {
+        let mesh = quad_box();
+        let flat = mesh.export_flat().unwrap();
+        assert_eq!(flat.positions.len(), 3 * mesh.num_vertices());
+        assert_eq!(flat.halfedge_next.len(), mesh.num_halfedges());
+        assert_eq!(flat.face_halfedge.len(), mesh.num_faces());
+        for v in 0..(mesh.num_vertices() as u32) {
+            let p = mesh.point_u32(v).unwrap();
+            let i = 3 * v as usize;
+            assert_eq!(
+                glam::vec3(
+                    flat.positions[i],
+                    flat.positions[i + 1],
+                    flat.positions[i + 2]
+                ),
+                p
+            );
+        }
+        // Walk the connectivity using only the flat buffers.
+        for h in 0..(mesh.num_halfedges() as u32) {
+            let hi = h as usize;
+            let next = flat.halfedge_next[hi];
+            let opp = flat.halfedge_opposite[hi];
+            assert_eq!(next, mesh.next_halfedge_u32(h));
+            assert_eq!(opp, mesh.opposite_halfedge_u32(h));
+            assert_eq!(flat.halfedge_prev[next as usize], h);
+            assert_eq!(flat.halfedge_opposite[opp as usize], h);
+            // The next halfedge starts where this one ends.
+            assert_eq!(
+                flat.halfedge_vertex[flat.halfedge_opposite[next as usize] as usize],
+                flat.halfedge_vertex[hi]
+            );
+            assert_eq!(flat.halfedge_face[hi], flat.halfedge_face[next as usize]);
+            assert_ne!(flat.halfedge_face[hi], u32::MAX);
+        }
+        for f in 0..(mesh.num_faces() as u32) {
+            let mut verts = Vec::new();
+            let start = flat.face_halfedge[f as usize];
+            let mut h = start;
+            loop {
+                verts.push(flat.halfedge_vertex[h as usize]);
+                h = flat.halfedge_next[h as usize];
+                if h == start {
+                    break;
+                }
+            }
+            // Same order as walking the face on the mesh.
+            assert_eq!(verts, mesh.fv_ccw_iter_u32(f).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn t_grid_export_flat_boundary() {
+        let mesh = quad_grid(2, 2);
+        let flat = mesh.export_flat().unwrap();
+        let nboundary = flat
+            .halfedge_face
+            .iter()
+            .filter(|f| **f == u32::MAX)
+            .count();
+        assert_eq!(nboundary, 8);
+        for h in mesh.boundary_halfedges_u32() {
+            assert_eq!(flat.halfedge_face[h as usize], u32::MAX);
+        }
+    }
+}
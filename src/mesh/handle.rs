@@ -0,0 +1,120 @@
+/// Define a typed handle wrapping a `u32` index, so that handles of different
+/// kinds of elements can't be mixed up by accident. Only the mesh creates
+/// handles; the index can be read with `index` or by converting the handle
+/// into a `u32`.
+macro_rules! handle {
+    ($(#[$attr:meta])* $name:ident, $what:literal) => {
+        #[doc = concat!("Handle to a ", $what, " of a mesh.")]
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub(crate) u32);
+
+        impl $name {
+            pub fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+
+        impl From<$name> for u32 {
+            fn from(h: $name) -> Self {
+                h.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl<T> std::ops::Index<$name> for [T] {
+            type Output = T;
+
+            fn index(&self, h: $name) -> &T {
+                &self[h.index()]
+            }
+        }
+
+        impl<T> std::ops::IndexMut<$name> for [T] {
+            fn index_mut(&mut self, h: $name) -> &mut T {
+                &mut self[h.index()]
+            }
+        }
+
+        impl<T> std::ops::Index<$name> for Vec<T> {
+            type Output = T;
+
+            fn index(&self, h: $name) -> &T {
+                &self[h.index()]
+            }
+        }
+
+        impl<T> std::ops::IndexMut<$name> for Vec<T> {
+            fn index_mut(&mut self, h: $name) -> &mut T {
+                &mut self[h.index()]
+            }
+        }
+    };
+}
+
+handle!(
+    ///
+    /// Handles of other elements are not accepted where a vertex handle is
+    /// expected:
+    ///
+    /// ```compile_fail
+    /// use alum::mesh::Mesh;
+    ///
+    /// let mut mesh = Mesh::new();
+    /// let a = mesh.add_vertex(glam::vec3(0.0, 0.0, 0.0)).unwrap();
+    /// let b = mesh.add_vertex(glam::vec3(1.0, 0.0, 0.0)).unwrap();
+    /// let c = mesh.add_vertex(glam::vec3(0.0, 1.0, 0.0)).unwrap();
+    /// let f = mesh.add_tri_face(a, b, c).unwrap();
+    /// let _ = mesh.point(f);
+    /// ```
+    VertexHandle,
+    "vertex"
+);
+handle!(HalfedgeHandle, "halfedge");
+handle!(EdgeHandle, "edge");
+handle!(FaceHandle, "face");
+
+impl HalfedgeHandle {
+    /// The edge the halfedge belongs to.
+    pub fn edge(self) -> EdgeHandle {
+        EdgeHandle(self.0 >> 1)
+    }
+
+    /// The other halfedge of the same edge.
+    pub fn opposite(self) -> HalfedgeHandle {
+        HalfedgeHandle(self.0 ^ 1)
+    }
+}
+
+impl EdgeHandle {
+    /// One of the two halfedges of the edge, `side` being 0 or 1.
+    pub fn halfedge(self, side: u32) -> HalfedgeHandle {
+        HalfedgeHandle((self.0 << 1) | (side & 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EdgeHandle, FaceHandle, HalfedgeHandle, VertexHandle};
+
+    #[test]
+    fn t_handle_conversions() {
+        let v = VertexHandle(3);
+        assert_eq!(u32::from(v), 3);
+        assert_eq!(v.to_string(), "3");
+        let h = HalfedgeHandle(5);
+        assert_eq!(h.edge(), EdgeHandle(2));
+        assert_eq!(h.opposite(), HalfedgeHandle(4));
+        assert_eq!(EdgeHandle(2).halfedge(1), h);
+        let mut values: Vec<f32> = (1..=4).map(|i| i as f32).collect();
+        values[FaceHandle(2)] = 5.0;
+        assert_eq!(values[FaceHandle(2)], 5.0);
+        assert_eq!(values[v], 4.0);
+        assert_eq!(values[..][EdgeHandle(1)], 2.0);
+    }
+}
@@ -0,0 +1,177 @@
+use super::{Error, FaceHandle, Mesh};
+
+/// Summary of the shape of the elements of a mesh, for diagnostics. Deleted
+/// elements are ignored. The fields for edges or faces are zero if the mesh
+/// has none.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct QualityReport {
+    pub min_edge_length: f32,
+    pub max_edge_length: f32,
+    pub mean_edge_length: f32,
+    pub min_face_area: f32,
+    pub max_face_area: f32,
+    pub mean_face_area: f32,
+    /// Smallest angle at a corner of any face, in radians.
+    pub min_angle: f32,
+    /// Largest angle at a corner of any face, in radians.
+    pub max_angle: f32,
+    /// Number of vertices of each valence, indexed by the valence.
+    pub valence_histogram: Vec<usize>,
+}
+
+impl Mesh {
+    /// Measure the edge lengths, face areas, corner angles and vertex
+    /// valences of the mesh in one pass. See `QualityReport`.
+    pub fn quality_report(&self) -> Result<QualityReport, Error> {
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?;
+        let mut report = QualityReport {
+            min_edge_length: f32::INFINITY,
+            max_edge_length: 0.0,
+            min_face_area: f32::INFINITY,
+            max_face_area: 0.0,
+            min_angle: f32::INFINITY,
+            max_angle: 0.0,
+            ..Default::default()
+        };
+        let mut nedges = 0usize;
+        for e in self.edges_u32() {
+            let h = e << 1;
+            let len = (points[self.to_vertex_u32(h) as usize]
+                - points[self.from_vertex_u32(h) as usize])
+                .length();
+            report.min_edge_length = report.min_edge_length.min(len);
+            report.max_edge_length = report.max_edge_length.max(len);
+            report.mean_edge_length += len;
+            nedges += 1;
+        }
+        let mut nfaces = 0usize;
+        for f in self.faces_u32() {
+            let area = self.newell_vector(f, &points).length() * 0.5;
+            report.min_face_area = report.min_face_area.min(area);
+            report.max_face_area = report.max_face_area.max(area);
+            report.mean_face_area += area;
+            nfaces += 1;
+            for h in self.fh_ccw_iter_u32(f) {
+                // Angle at the corner where `h` ends.
+                let v = points[self.to_vertex_u32(h) as usize];
+                let a = points[self.from_vertex_u32(h) as usize] - v;
+                let b = points[self.to_vertex_u32(self.next_halfedge_u32(h)) as usize] - v;
+                let angle = a.cross(b).length().atan2(a.dot(b));
+                report.min_angle = report.min_angle.min(angle);
+                report.max_angle = report.max_angle.max(angle);
+            }
+        }
+        if nedges > 0 {
+            report.mean_edge_length /= nedges as f32;
+        } else {
+            report.min_edge_length = 0.0;
+        }
+        if nfaces > 0 {
+            report.mean_face_area /= nfaces as f32;
+        } else {
+            report.min_face_area = 0.0;
+            report.min_angle = 0.0;
+        }
+        for v in self.vertices_u32() {
+            let valence = self.vertex_valence_u32(v);
+            if report.valence_histogram.len() <= valence {
+                report.valence_histogram.resize(valence + 1, 0);
+            }
+            report.valence_histogram[valence] += 1;
+        }
+        Ok(report)
+    }
+
+    /// Radius ratio of the triangle, i.e. twice the inradius over the
+    /// circumradius. This is 1.0 for an equilateral triangle and approaches
+    /// 0.0 as the triangle degenerates. `None` is returned if the face is not
+    /// a triangle.
+    pub fn triangle_quality(&self, f: FaceHandle) -> Result<Option<f32>, Error> {
+        self.check_face(f.0)?;
+        if self.face_valence(f) != 3 {
+            return Ok(None);
+        }
+        let mut points = self.fv_points_ccw_iter(f)?;
+        let (Some(p0), Some(p1), Some(p2)) = (points.next(), points.next(), points.next()) else {
+            return Ok(None);
+        };
+        let a = (p1 - p0).length();
+        let b = (p2 - p1).length();
+        let c = (p0 - p2).length();
+        let denom = a * b * c;
+        if denom == 0.0 {
+            return Ok(Some(0.0));
+        }
+        Ok(Some(
+            ((b + c - a) * (c + a - b) * (a + b - c) / denom).max(0.0),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{
+        test::{quad_box, tri_grid},
+        Error, FaceHandle, Mesh,
+    };
+
+    #[test]
+    fn t_box_quality_report() {
+        let mesh = quad_box();
+        let report = mesh.quality_report().unwrap();
+        assert_eq!(report.min_edge_length, 1.0);
+        assert_eq!(report.max_edge_length, 1.0);
+        assert_eq!(report.mean_edge_length, 1.0);
+        assert_eq!(report.min_face_area, 1.0);
+        assert_eq!(report.max_face_area, 1.0);
+        assert_eq!(report.mean_face_area, 1.0);
+        assert!((report.min_angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!((report.max_angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(report.valence_histogram, [0, 0, 0, 8]);
+    }
+
+    #[test]
+    fn t_triangle_quality() {
+        let mut mesh = Mesh::new();
+        let [a, b, c, d] = [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.5, 0.75f32.sqrt(), 0.0),
+            glam::vec3(0.5, -0.001, 0.0),
+        ]
+        .map(|p| mesh.add_vertex(p).unwrap());
+        let equilateral = mesh.add_tri_face(a, b, c).unwrap();
+        let sliver = mesh.add_tri_face(a, d, b).unwrap();
+        assert!((mesh.triangle_quality(equilateral).unwrap().unwrap() - 1.0).abs() < 1e-5);
+        assert!(mesh.triangle_quality(sliver).unwrap().unwrap() < 1e-2);
+        let right = tri_grid(1, 1)
+            .triangle_quality(FaceHandle(0))
+            .unwrap()
+            .unwrap();
+        assert!(right > 0.0 && right < 1.0);
+        assert_eq!(quad_box().triangle_quality(FaceHandle(0)).unwrap(), None);
+        assert!(matches!(
+            mesh.triangle_quality(FaceHandle(99)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_tri_grid_quality_report() {
+        let mesh = tri_grid(2, 2);
+        let report = mesh.quality_report().unwrap();
+        assert!((report.min_angle - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        assert!((report.max_angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(
+            report.valence_histogram.iter().sum::<usize>(),
+            mesh.num_vertices()
+        );
+        let report = Mesh::new().quality_report().unwrap();
+        assert_eq!(report.min_edge_length, 0.0);
+        assert!(report.valence_histogram.is_empty());
+    }
+}
@@ -0,0 +1,277 @@
+use super::{Error, Mesh, Status};
+
+impl Mesh {
+    /// Remesh the surface so that all edges are close to
+    /// `target_edge_length`, following Botsch and Kobbelt, "A Remeshing
+    /// Approach to Multiresolution Modeling". Each iteration splits edges
+    /// longer than 4/3 of the target, collapses edges shorter than 4/5 of the
+    /// target, flips edges to bring the valences closer to 6 (4 on the
+    /// boundary), and finally relaxes the vertices in their tangent planes.
+    /// Boundary vertices are never moved or removed. Faces with more than
+    /// three vertices are triangulated first. The target length must be
+    /// positive and finite, otherwise `Error::InvalidLength` is returned and
+    /// the mesh is left untouched.
+    pub fn isotropic_remesh(
+        &mut self,
+        target_edge_length: f32,
+        iterations: usize,
+    ) -> Result<(), Error> {
+        if !(target_edge_length.is_finite() && target_edge_length > 0.0) {
+            return Err(Error::InvalidLength(target_edge_length));
+        }
+        let high = target_edge_length * 4.0 / 3.0;
+        let low = target_edge_length * 4.0 / 5.0;
+        self.triangulate()?;
+        for _ in 0..iterations {
+            self.split_long_edges(high)?;
+            self.collapse_short_edges_bounded(low, high)?;
+            self.equalize_valences();
+            self.tangential_relaxation()?;
+        }
+        Ok(())
+    }
+
+    fn split_long_edges(&mut self, high: f32) -> Result<(), Error> {
+        // Edges created by the splits are appended, so they get visited too.
+        let mut e = 0u32;
+        while (e as usize) < self.num_edges() {
+            if !self.is_edge_deleted_u32(e) {
+                let h = e << 1;
+                let a = self.point_u32(self.from_vertex_u32(h))?;
+                let b = self.point_u32(self.to_vertex_u32(h))?;
+                if (b - a).length() > high {
+                    self.split_edge_u32(e, (a + b) * 0.5)?;
+                    continue;
+                }
+            }
+            e += 1;
+        }
+        Ok(())
+    }
+
+    fn collapse_short_edges_bounded(&mut self, low: f32, high: f32) -> Result<(), Error> {
+        // Collapses don't move any vertices, so the points can be read once.
+        let points = self
+            .points
+            .data
+            .read()
+            .map_err(|_| Error::ReadPropertyFailed)?
+            .clone();
+        for e in 0..(self.num_edges() as u32) {
+            if self.is_edge_deleted_u32(e) {
+                continue;
+            }
+            let h = e << 1;
+            let length = (points[self.to_vertex_u32(h) as usize]
+                - points[self.from_vertex_u32(h) as usize])
+                .length();
+            if length >= low {
+                continue;
+            }
+            for h in [h, self.opposite_halfedge_u32(h)] {
+                let from = self.from_vertex_u32(h);
+                let to = points[self.to_vertex_u32(h) as usize];
+                if self.is_boundary_vertex_u32(from)
+                    || !self.is_collapse_ok_u32(h)
+                    // Don't create edges that would need splitting again.
+                    || self
+                        .vv_ccw_iter_u32(from)
+                        .any(|v| (points[v as usize] - to).length() > high)
+                {
+                    continue;
+                }
+                self.collapse_halfedge_u32(h);
+                break;
+            }
+        }
+        self.garbage_collection()
+    }
+
+    /// Flip edges of a triangle mesh to bring the valences of the vertices
+    /// closer to 6, or 4 on the boundary, which is what a regular
+    /// triangulation has. An edge is flipped if that reduces the total
+    /// deviation of the valences of its two vertices and the two vertices
+    /// opposite to it. This is repeated over all edges up to `iterations`
+    /// times, stopping early once no more flips help. Edges that are not
+    /// shared by two triangles are left alone. Returns the number of flips.
+    pub fn optimize_valence(&mut self, iterations: usize) -> Result<usize, Error> {
+        let mut total = 0usize;
+        for _ in 0..iterations {
+            let flips = self.equalize_valences();
+            if flips == 0 {
+                break;
+            }
+            total += flips;
+        }
+        Ok(total)
+    }
+
+    /// How far the valence of the vertex is from the ideal for its position.
+    fn valence_deviation(&self, v: u32, valence: usize) -> i64 {
+        let target = if self.is_boundary_vertex_u32(v) { 4 } else { 6 };
+        (valence as i64 - target).abs()
+    }
+
+    /// Flip every edge that reduces the valence deviation, and return the
+    /// number of edges flipped.
+    fn equalize_valences(&mut self) -> usize {
+        let mut flips = 0usize;
+        for e in 0..(self.num_edges() as u32) {
+            if !self.is_flip_ok_u32(e) {
+                continue;
+            }
+            let h = e << 1;
+            let oh = self.opposite_halfedge_u32(h);
+            let (a, b) = (self.to_vertex_u32(h), self.to_vertex_u32(oh));
+            let c = self.to_vertex_u32(self.next_halfedge_u32(h));
+            let d = self.to_vertex_u32(self.next_halfedge_u32(oh));
+            let [va, vb, vc, vd] = [a, b, c, d].map(|v| self.vertex_valence_u32(v));
+            let before = self.valence_deviation(a, va)
+                + self.valence_deviation(b, vb)
+                + self.valence_deviation(c, vc)
+                + self.valence_deviation(d, vd);
+            let after = self.valence_deviation(a, va - 1)
+                + self.valence_deviation(b, vb - 1)
+                + self.valence_deviation(c, vc + 1)
+                + self.valence_deviation(d, vd + 1);
+            if after < before {
+                self.flip_edge_u32(e);
+                flips += 1;
+            }
+        }
+        flips
+    }
+
+    /// Move every interior vertex towards the centroid of its neighbors,
+    /// within the tangent plane of the vertex. Locked vertices stay in place.
+    fn tangential_relaxation(&mut self) -> Result<(), Error> {
+        let locked = self.vertex_status_mask(Status::LOCKED)?;
+        let relaxed = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let fnormals: Vec<_> = (0..(self.num_faces() as u32))
+                .map(|f| self.face_normal_from(f, &points))
+                .collect();
+            let mut relaxed = points.clone();
+            for v in 0..(self.num_vertices() as u32) {
+                if locked[v as usize] || self.is_boundary_vertex_u32(v) {
+                    continue;
+                }
+                let (sum, count) = self
+                    .vv_ccw_iter_u32(v)
+                    .fold((glam::Vec3::zero(), 0usize), |(sum, count), n| {
+                        (sum + points[n as usize], count + 1)
+                    });
+                let p = points[v as usize];
+                let d = sum / count as f32 - p;
+                let normal = self.vertex_normal_from(v, &fnormals);
+                relaxed[v as usize] = p + d - normal * normal.dot(d);
+            }
+            relaxed
+        };
+        *self
+            .points
+            .data
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)? = relaxed;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{test::tri_grid, Error, Mesh, VertexHandle};
+
+    /// Mean and standard deviation of the edge lengths.
+    fn edge_length_stats(mesh: &Mesh) -> (f32, f32) {
+        let lengths: Vec<f32> = (0..(mesh.num_edges() as u32))
+            .map(|e| mesh.calc_edge_length_u32(e).unwrap())
+            .collect();
+        let n = lengths.len() as f32;
+        let mean = lengths.iter().sum::<f32>() / n;
+        let var = lengths.iter().map(|l| (l - mean) * (l - mean)).sum::<f32>() / n;
+        (mean, var.sqrt())
+    }
+
+    fn total_valence_deviation(mesh: &Mesh) -> i64 {
+        mesh.vertices_u32()
+            .map(|v| mesh.valence_deviation(v, mesh.vertex_valence_u32(v)))
+            .sum()
+    }
+
+    #[test]
+    fn t_optimize_valence() {
+        let mut mesh = tri_grid(6, 6);
+        let regular = total_valence_deviation(&mesh);
+        // Scramble the triangulation with some flips.
+        for e in (0..(mesh.num_edges() as u32)).step_by(3) {
+            if mesh.is_flip_ok_u32(e) {
+                mesh.flip_edge_u32(e);
+            }
+        }
+        mesh.validate().unwrap();
+        let before = total_valence_deviation(&mesh);
+        assert!(before > regular);
+        let flips = mesh.optimize_valence(10).unwrap();
+        mesh.validate().unwrap();
+        assert!(flips > 0);
+        let after = total_valence_deviation(&mesh);
+        assert!(after < before, "{after} is not less than {before}");
+        // Nothing left to improve.
+        assert_eq!(mesh.optimize_valence(10).unwrap(), 0);
+        assert_eq!(mesh.euler_characteristic(), 1);
+    }
+
+    #[test]
+    fn t_isotropic_remesh_noisy_grid() {
+        const TARGET: f32 = 1.0;
+        let mut mesh = tri_grid(12, 12);
+        // Jitter the interior vertices with a deterministic pseudo-random
+        // offset.
+        let mut seed = 12345u32;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) & 0x7fff) as f32 / 32767.0 - 0.5
+        };
+        for v in (0..mesh.num_vertices() as u32).map(VertexHandle) {
+            if !mesh.is_boundary_vertex(v) {
+                let p = mesh.point(v).unwrap();
+                mesh.set_point(v, p + glam::vec3(rand(), rand(), 0.0) * 0.7)
+                    .unwrap();
+            }
+        }
+        let (_, before) = edge_length_stats(&mesh);
+        mesh.isotropic_remesh(TARGET, 5).unwrap();
+        mesh.validate().unwrap();
+        let (mean, after) = edge_length_stats(&mesh);
+        assert!(
+            after < before * 0.6,
+            "{after} is not much less than {before}"
+        );
+        assert!(
+            (mean - TARGET).abs() < 0.15 * TARGET,
+            "mean edge length {mean}"
+        );
+        // Still a single flat disk.
+        assert_eq!(mesh.euler_characteristic(), 1);
+        for v in 0..(mesh.num_vertices() as u32) {
+            assert_eq!(mesh.point_u32(v).unwrap().z(), 0.0);
+        }
+    }
+
+    #[test]
+    fn t_isotropic_remesh_invalid_length() {
+        for target in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let mut mesh = tri_grid(2, 2);
+            assert!(matches!(
+                mesh.isotropic_remesh(target, 1),
+                Err(Error::InvalidLength(_))
+            ));
+            assert_eq!(mesh.num_faces(), 8);
+            assert_eq!(mesh.num_edges(), 16);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use super::{EdgeHandle, Error, FaceHandle, Mesh, Property, TPropData, VertexHandle};
+
+/// Set of flags describing the state of an element, used by editing tools.
+/// Flags are combined with `|`, e.g. `Status::SELECTED | Status::LOCKED`.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Status(u8);
+
+impl Status {
+    pub const SELECTED: Status = Status(1);
+    /// Locked vertices are not moved by smoothing.
+    pub const LOCKED: Status = Status(1 << 1);
+    /// Mirrors the deleted state of the element, which can only be changed
+    /// by editing the mesh.
+    pub const DELETED: Status = Status(1 << 2);
+    pub const HIDDEN: Status = Status(1 << 3);
+
+    pub const fn empty() -> Self {
+        Status(0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether all the flags in `other` are set.
+    pub fn contains(self, other: Status) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Set or unset all the flags in `other`.
+    pub fn set(&mut self, other: Status, value: bool) {
+        if value {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl std::ops::BitOr for Status {
+    type Output = Status;
+
+    fn bitor(self, rhs: Status) -> Status {
+        Status(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Status {
+    fn bitor_assign(&mut self, rhs: Status) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for Status {
+    type Output = Status;
+
+    fn bitand(self, rhs: Status) -> Status {
+        Status(self.0 & rhs.0)
+    }
+}
+
+impl TPropData for Status {}
+
+/// Read the status of an element from the property, if it exists, and add
+/// the deleted flag.
+fn read_status(prop: &Option<Property<Status>>, i: u32, deleted: bool) -> Result<Status, Error> {
+    let mut status = match prop {
+        Some(prop) => prop.get(i)?,
+        None => Status::empty(),
+    };
+    status.set(Status::DELETED, deleted);
+    Ok(status)
+}
+
+impl Mesh {
+    /// Status flags of the vertex. Vertices start with no flags set.
+    pub fn vertex_status(&self, v: VertexHandle) -> Result<Status, Error> {
+        let v = v.0;
+        self.check_vertex(v)?;
+        read_status(&self.vstatus, v, self.is_vertex_deleted_u32(v))
+    }
+
+    /// Replace the status flags of the vertex. `Status::DELETED` is ignored,
+    /// use the editing functions to delete vertices.
+    pub fn set_vertex_status(&mut self, v: VertexHandle, mut status: Status) -> Result<(), Error> {
+        let v = v.0;
+        self.check_vertex(v)?;
+        status.set(Status::DELETED, false);
+        if self.vstatus.is_none() {
+            self.vstatus = Some(self.create_vertex_prop());
+        }
+        self.vstatus
+            .as_mut()
+            .expect("Vertex status must exist here")
+            .set(v, status)
+    }
+
+    /// Status flags of the edge. Edges start with no flags set.
+    pub fn edge_status(&self, e: EdgeHandle) -> Result<Status, Error> {
+        let e = e.0;
+        self.check_halfedge(e << 1)?;
+        read_status(&self.estatus, e, self.is_edge_deleted_u32(e))
+    }
+
+    /// Replace the status flags of the edge. `Status::DELETED` is ignored,
+    /// use the editing functions to delete edges.
+    pub fn set_edge_status(&mut self, e: EdgeHandle, mut status: Status) -> Result<(), Error> {
+        let e = e.0;
+        self.check_halfedge(e << 1)?;
+        status.set(Status::DELETED, false);
+        if self.estatus.is_none() {
+            self.estatus = Some(self.create_edge_prop());
+        }
+        self.estatus
+            .as_mut()
+            .expect("Edge status must exist here")
+            .set(e, status)
+    }
+
+    /// Status flags of the face. Faces start with no flags set.
+    pub fn face_status(&self, f: FaceHandle) -> Result<Status, Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        read_status(&self.fstatus, f, self.is_face_deleted_u32(f))
+    }
+
+    /// Replace the status flags of the face. `Status::DELETED` is ignored,
+    /// use the editing functions to delete faces.
+    pub fn set_face_status(&mut self, f: FaceHandle, mut status: Status) -> Result<(), Error> {
+        let f = f.0;
+        self.check_face(f)?;
+        status.set(Status::DELETED, false);
+        if self.fstatus.is_none() {
+            self.fstatus = Some(self.create_face_prop());
+        }
+        self.fstatus
+            .as_mut()
+            .expect("Face status must exist here")
+            .set(f, status)
+    }
+
+    pub fn is_vertex_selected(&self, v: VertexHandle) -> Result<bool, Error> {
+        Ok(self.vertex_status(v)?.contains(Status::SELECTED))
+    }
+
+    pub fn set_vertex_selected(&mut self, v: VertexHandle, selected: bool) -> Result<(), Error> {
+        let mut status = self.vertex_status(v)?;
+        status.set(Status::SELECTED, selected);
+        self.set_vertex_status(v, status)
+    }
+
+    pub fn is_vertex_locked(&self, v: VertexHandle) -> Result<bool, Error> {
+        Ok(self.vertex_status(v)?.contains(Status::LOCKED))
+    }
+
+    pub fn set_vertex_locked(&mut self, v: VertexHandle, locked: bool) -> Result<(), Error> {
+        let mut status = self.vertex_status(v)?;
+        status.set(Status::LOCKED, locked);
+        self.set_vertex_status(v, status)
+    }
+
+    pub fn is_edge_selected(&self, e: EdgeHandle) -> Result<bool, Error> {
+        Ok(self.edge_status(e)?.contains(Status::SELECTED))
+    }
+
+    pub fn set_edge_selected(&mut self, e: EdgeHandle, selected: bool) -> Result<(), Error> {
+        let mut status = self.edge_status(e)?;
+        status.set(Status::SELECTED, selected);
+        self.set_edge_status(e, status)
+    }
+
+    pub fn is_face_selected(&self, f: FaceHandle) -> Result<bool, Error> {
+        Ok(self.face_status(f)?.contains(Status::SELECTED))
+    }
+
+    pub fn set_face_selected(&mut self, f: FaceHandle, selected: bool) -> Result<(), Error> {
+        let mut status = self.face_status(f)?;
+        status.set(Status::SELECTED, selected);
+        self.set_face_status(f, status)
+    }
+
+    /// For every vertex, whether any of the `flags` are set.
+    pub(crate) fn vertex_status_mask(&self, flags: Status) -> Result<Vec<bool>, Error> {
+        match &self.vstatus {
+            Some(prop) => Ok(prop
+                .to_vec()?
+                .into_iter()
+                .map(|s| !(s & flags).is_empty())
+                .collect()),
+            None => Ok(vec![false; self.num_vertices()]),
+        }
+    }
+
+    /// Move every vertex towards the centroid of its neighbors by the factor
+    /// `lambda`, `iterations` times. Boundary vertices and vertices with the
+    /// `Status::LOCKED` flag stay in place.
+    pub fn laplacian_smooth(&mut self, iterations: usize, lambda: f32) -> Result<(), Error> {
+        let movable: Vec<bool> = self
+            .vertex_status_mask(Status::LOCKED)?
+            .into_iter()
+            .map(|locked| !locked)
+            .collect();
+        self.smooth_vertices(&movable, iterations, lambda)
+    }
+
+    /// Same as `laplacian_smooth`, but only the vertices with the
+    /// `Status::SELECTED` flag are moved. Their neighbors are used for the
+    /// centroids whether they are selected or not, so the selection relaxes
+    /// towards the surrounding surface.
+    pub fn smooth_selection(&mut self, iterations: usize, lambda: f32) -> Result<(), Error> {
+        let locked = self.vertex_status_mask(Status::LOCKED)?;
+        let movable: Vec<bool> = self
+            .vertex_status_mask(Status::SELECTED)?
+            .into_iter()
+            .zip(locked)
+            .map(|(selected, locked)| selected && !locked)
+            .collect();
+        self.smooth_vertices(&movable, iterations, lambda)
+    }
+
+    fn smooth_vertices(
+        &mut self,
+        movable: &[bool],
+        iterations: usize,
+        lambda: f32,
+    ) -> Result<(), Error> {
+        let mut points = self.points.to_vec()?;
+        let mut smoothed = points.clone();
+        for _ in 0..iterations {
+            for v in self.vertices_u32() {
+                if !movable[v as usize] || self.is_boundary_vertex_u32(v) {
+                    continue;
+                }
+                let (sum, count) = self
+                    .vv_ccw_iter_u32(v)
+                    .fold((glam::Vec3::zero(), 0usize), |(sum, count), n| {
+                        (sum + points[n as usize], count + 1)
+                    });
+                let p = points[v as usize];
+                smoothed[v as usize] = p + (sum / count as f32 - p) * lambda;
+            }
+            std::mem::swap(&mut points, &mut smoothed);
+        }
+        self.points.from_slice(&points)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Status;
+    use crate::mesh::{
+        test::{quad_box, quad_grid},
+        EdgeHandle, Error, FaceHandle, VertexHandle,
+    };
+
+    #[test]
+    fn t_status_flags() {
+        let mut status = Status::SELECTED | Status::HIDDEN;
+        assert!(status.contains(Status::SELECTED));
+        assert!(!status.contains(Status::SELECTED | Status::LOCKED));
+        status.set(Status::SELECTED, false);
+        status |= Status::LOCKED;
+        assert_eq!(status, Status::LOCKED | Status::HIDDEN);
+        assert!((status & Status::SELECTED).is_empty());
+        assert!(Status::default().is_empty());
+    }
+
+    #[test]
+    fn t_element_status() {
+        let mut mesh = quad_grid(2, 2);
+        assert_eq!(
+            mesh.vertex_status(VertexHandle(4)).unwrap(),
+            Status::empty()
+        );
+        mesh.set_vertex_selected(VertexHandle(4), true).unwrap();
+        mesh.set_vertex_locked(VertexHandle(4), true).unwrap();
+        mesh.set_vertex_locked(VertexHandle(5), true).unwrap();
+        mesh.set_vertex_selected(VertexHandle(5), false).unwrap();
+        assert_eq!(
+            mesh.vertex_status(VertexHandle(4)).unwrap(),
+            Status::SELECTED | Status::LOCKED
+        );
+        assert!(!mesh.is_vertex_selected(VertexHandle(5)).unwrap());
+        assert!(mesh.is_vertex_locked(VertexHandle(5)).unwrap());
+        assert!(!mesh.is_vertex_locked(VertexHandle(0)).unwrap());
+        mesh.set_edge_selected(EdgeHandle(3), true).unwrap();
+        assert!(mesh.is_edge_selected(EdgeHandle(3)).unwrap());
+        assert!(!mesh.is_edge_selected(EdgeHandle(2)).unwrap());
+        mesh.set_face_status(FaceHandle(1), Status::HIDDEN | Status::DELETED)
+            .unwrap();
+        assert_eq!(mesh.face_status(FaceHandle(1)).unwrap(), Status::HIDDEN);
+        mesh.set_face_selected(FaceHandle(2), true).unwrap();
+        // The deleted flag follows the topology.
+        mesh.delete_face(FaceHandle(0), true).unwrap();
+        assert!(mesh
+            .vertex_status(VertexHandle(0))
+            .unwrap()
+            .contains(Status::DELETED));
+        assert_eq!(mesh.face_status(FaceHandle(0)).unwrap(), Status::DELETED);
+        // The flags follow the elements through garbage collection.
+        mesh.garbage_collection().unwrap();
+        assert_eq!(mesh.face_status(FaceHandle(0)).unwrap(), Status::HIDDEN);
+        assert!(mesh.is_face_selected(FaceHandle(1)).unwrap());
+        assert!(matches!(
+            mesh.vertex_status(VertexHandle(100)),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            mesh.set_face_selected(FaceHandle(100), true),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
+    #[test]
+    fn t_laplacian_smooth_locked() {
+        let mut mesh = quad_grid(4, 4);
+        // Pull up the interior vertices, and lock some of them.
+        let interior: Vec<_> = mesh
+            .vertices()
+            .filter(|v| !mesh.is_boundary_vertex(*v))
+            .collect();
+        for v in interior {
+            let p = mesh.point(v).unwrap();
+            mesh.set_point(v, p + glam::vec3(0.0, 0.0, 1.0)).unwrap();
+        }
+        let locked = [6, 12, 18].map(VertexHandle);
+        for v in locked {
+            mesh.set_vertex_locked(v, true).unwrap();
+        }
+        // Selecting a vertex doesn't lock it.
+        mesh.set_vertex_selected(VertexHandle(7), true).unwrap();
+        let before = mesh.points_snapshot().unwrap();
+        mesh.laplacian_smooth(10, 0.5).unwrap();
+        assert_ne!(mesh.point(VertexHandle(7)).unwrap(), before[7]);
+        for v in mesh.vertices() {
+            let moved = mesh.point(v).unwrap() != before[v];
+            let fixed = locked.contains(&v) || mesh.is_boundary_vertex(v);
+            assert_eq!(moved, !fixed);
+        }
+        assert!(mesh.point(VertexHandle(8)).unwrap().z() < 1.0);
+        // Without any status, everything in the interior moves.
+        let mut mesh = quad_box();
+        mesh.set_point(VertexHandle(0), glam::vec3(-1.0, -1.0, -1.0))
+            .unwrap();
+        let before = mesh.points_snapshot().unwrap();
+        mesh.laplacian_smooth(1, 1.0).unwrap();
+        assert!(mesh.vertices().all(|v| mesh.point(v).unwrap() != before[v]));
+    }
+
+    #[test]
+    fn t_smooth_selection() {
+        let mut mesh = quad_grid(6, 6);
+        // Deterministic noise in z on all the interior vertices.
+        let mut seed = 12345u32;
+        for v in (0..mesh.num_vertices() as u32).map(VertexHandle) {
+            if mesh.is_boundary_vertex(v) {
+                continue;
+            }
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let z = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+            let p = mesh.point(v).unwrap();
+            mesh.set_point(v, p + glam::vec3(0.0, 0.0, z)).unwrap();
+        }
+        // Select the 3 x 3 patch of vertices in the middle, with one locked.
+        let patch: Vec<_> = (2..5)
+            .flat_map(|y| (2..5).map(move |x| VertexHandle(y * 7 + x)))
+            .collect();
+        for v in &patch {
+            mesh.set_vertex_selected(*v, true).unwrap();
+        }
+        mesh.set_vertex_locked(patch[0], true).unwrap();
+        let before = mesh.points_snapshot().unwrap();
+        let roughness = |mesh: &crate::mesh::Mesh| -> f32 {
+            patch[1..]
+                .iter()
+                .map(|v| {
+                    let p = mesh.point(*v).unwrap();
+                    let n = mesh.vertex_valence(*v) as f32;
+                    let c = mesh
+                        .vv_ccw_iter(*v)
+                        .fold(glam::Vec3::zero(), |c, nb| c + mesh.point(nb).unwrap());
+                    (p - c / n).length()
+                })
+                .sum()
+        };
+        let rough_before = roughness(&mesh);
+        mesh.smooth_selection(5, 0.5).unwrap();
+        for v in mesh.vertices() {
+            let moved = mesh.point(v).unwrap() != before[v];
+            assert_eq!(moved, patch[1..].contains(&v), "vertex {v}");
+        }
+        assert!(roughness(&mesh) < rough_before);
+        // Nothing moves without a selection.
+        let mut mesh = quad_box();
+        let before = mesh.points_snapshot().unwrap();
+        mesh.smooth_selection(3, 0.5).unwrap();
+        assert_eq!(mesh.points_snapshot().unwrap(), before);
+    }
+}
@@ -0,0 +1,336 @@
+use super::{EdgeHandle, Error, Mesh};
+
+impl Mesh {
+    /// Crease weight of the edge, as set by `set_crease`. Edges without a
+    /// crease have a weight of zero.
+    pub fn crease(&self, e: EdgeHandle) -> Result<f32, Error> {
+        let e = e.0;
+        self.check_halfedge(e << 1)?;
+        match &self.creases {
+            Some(creases) => creases.get(e),
+            None => Ok(0.0),
+        }
+    }
+
+    /// Set the crease weight of the edge, used by subdivision. A weight of 1
+    /// or more keeps the edge sharp, i.e. the surface is not smoothed across
+    /// it. Fractional weights blend between the sharp and the smooth rules.
+    /// Negative weights are treated as zero.
+    pub fn set_crease(&mut self, e: EdgeHandle, weight: f32) -> Result<(), Error> {
+        let e = e.0;
+        self.check_halfedge(e << 1)?;
+        if self.creases.is_none() {
+            self.creases = Some(self.create_edge_prop());
+        }
+        self.creases
+            .as_mut()
+            .expect("Creases must exist here")
+            .set(e, weight.max(0.0))
+    }
+
+    /// Apply one level of Catmull-Clark subdivision. Every face with `n`
+    /// vertices is split into `n` quads, connecting its centroid to the
+    /// midpoints of its edges. Creased edges follow the rules of DeRose et
+    /// al., "Subdivision Surfaces in Character Animation", and boundary edges
+    /// are always sharp. The crease weights of the edges are passed on to the
+    /// two halves of the edge, reduced by one, so that fractional and
+    /// semi-sharp creases soften over successive levels.
+    ///
+    /// Deleted elements are garbage collected first. The original vertices
+    /// keep their handles, followed by one new vertex per edge and one per
+    /// face. The new faces inherit the properties of the face they were split
+    /// from, and the halves of an edge inherit the properties of the edge.
+    /// Halfedge properties are reset to their default values.
+    pub fn subdivide_catmull_clark(&mut self) -> Result<(), Error> {
+        self.garbage_collection()?;
+        let (nverts, nedges, nfaces) = (self.num_vertices(), self.num_edges(), self.num_faces());
+        let positions = {
+            let points = self
+                .points
+                .data
+                .read()
+                .map_err(|_| Error::ReadPropertyFailed)?;
+            let sharpness: Vec<f32> = match &self.creases {
+                Some(creases) => creases.to_vec()?,
+                None => vec![0.0; nedges],
+            }
+            .into_iter()
+            .enumerate()
+            .map(|(e, w)| {
+                if self.is_boundary_edge_u32(e as u32) {
+                    1.0
+                } else {
+                    w.min(1.0)
+                }
+            })
+            .collect();
+            let fpoints: Vec<glam::Vec3> = (0..(nfaces as u32))
+                .map(|f| {
+                    let (sum, count) = self
+                        .fv_ccw_iter_u32(f)
+                        .fold((glam::Vec3::zero(), 0usize), |(sum, count), v| {
+                            (sum + points[v as usize], count + 1)
+                        });
+                    sum / count as f32
+                })
+                .collect();
+            let mut positions = Vec::with_capacity(nverts + nedges + nfaces);
+            for v in 0..(nverts as u32) {
+                positions.push(self.subdivided_vertex(v, &points, &fpoints, &sharpness));
+            }
+            for (e, s) in sharpness.iter().enumerate() {
+                let h = (e as u32) << 1;
+                let a = points[self.from_vertex_u32(h) as usize];
+                let b = points[self.to_vertex_u32(h) as usize];
+                let mid = (a + b) * 0.5;
+                if *s >= 1.0 {
+                    positions.push(mid);
+                } else {
+                    let fa = fpoints[self.face_index(h)];
+                    let fb = fpoints[self.face_index(h | 1)];
+                    let smooth = (a + b + fa + fb) * 0.25;
+                    positions.push(smooth + (mid - smooth) * *s);
+                }
+            }
+            positions.extend(fpoints);
+            positions
+        };
+        // Build the new topology.
+        let mut topol = Mesh::with_capacity(positions.len(), nedges * 4, nfaces * 4);
+        for _ in 0..positions.len() {
+            topol.add_vertex_u32(glam::Vec3::zero())?;
+        }
+        let mut parents: Vec<u32> = Vec::with_capacity(nfaces * 4);
+        for f in 0..(nfaces as u32) {
+            let fpoint = (nverts + nedges) as u32 + f;
+            for h in self.fh_ccw_iter_u32(f) {
+                let next = self.next_halfedge_u32(h);
+                topol.add_face_u32(&[
+                    nverts as u32 + (h >> 1),
+                    self.to_vertex_u32(h),
+                    nverts as u32 + (next >> 1),
+                    fpoint,
+                ])?;
+                parents.push(f);
+            }
+        }
+        // Each new face has a parent with the same or a lower index, so the
+        // properties can be copied in place from the back.
+        self.fprops.resize(parents.len())?;
+        for (i, f) in parents.iter().enumerate().rev() {
+            self.fprops.copy(*f as usize, i)?;
+        }
+        // The halves of the edges are scattered among the new edges, so copy
+        // their properties past the old edges, and then shift them down.
+        self.eprops.resize(nedges + topol.num_edges())?;
+        for e in 0..(nedges as u32) {
+            let h = e << 1;
+            let mid = nverts as u32 + e;
+            for (from, to) in [(self.from_vertex_u32(h), mid), (mid, self.to_vertex_u32(h))] {
+                let child = topol
+                    .find_halfedge(from, to)
+                    .expect("Every half of an edge must exist after subdivision")
+                    >> 1;
+                self.eprops.copy(e as usize, nedges + child as usize)?;
+            }
+        }
+        for e in 0..topol.num_edges() {
+            self.eprops.copy(nedges + e, e)?;
+        }
+        self.eprops.resize(topol.num_edges())?;
+        if let Some(creases) = self.creases.as_mut() {
+            creases.map_in_place(|w| *w = (*w - 1.0).max(0.0))?;
+        }
+        self.hprops.clear()?;
+        self.hprops.resize(topol.num_halfedges())?;
+        self.vprops.resize(positions.len())?;
+        *self
+            .points
+            .data
+            .write()
+            .map_err(|_| Error::WriteToPropertyFailed)? = positions;
+        self.vertices = std::mem::take(&mut topol.vertices);
+        self.edges = std::mem::take(&mut topol.edges);
+        self.faces = std::mem::take(&mut topol.faces);
+        Ok(())
+    }
+
+    /// Index of the face of the halfedge. Only for interior halfedges.
+    fn face_index(&self, h: u32) -> usize {
+        self.halfedge_face_u32(h)
+            .expect("Halfedge must not be on the boundary") as usize
+    }
+
+    /// New position of an existing vertex after a level of Catmull-Clark
+    /// subdivision. Vertices with fewer than two sharp edges are smooth,
+    /// vertices with exactly two lie on a crease, and vertices with more are
+    /// corners that don't move. The sharpness of a vertex is the mean
+    /// sharpness of its sharp edges, and blends between the smooth and the
+    /// sharp rules when fractional.
+    fn subdivided_vertex(
+        &self,
+        v: u32,
+        points: &[glam::Vec3],
+        fpoints: &[glam::Vec3],
+        sharpness: &[f32],
+    ) -> glam::Vec3 {
+        let p = points[v as usize];
+        let sharp: Vec<u32> = self
+            .voh_ccw_iter_u32(v)
+            .filter(|h| sharpness[(h >> 1) as usize] > 0.0)
+            .collect();
+        let sharp_pos = match sharp.len() {
+            0 | 1 => None,
+            2 => {
+                let a = points[self.to_vertex_u32(sharp[0]) as usize];
+                let b = points[self.to_vertex_u32(sharp[1]) as usize];
+                Some((a + b + p * 6.0) * 0.125)
+            }
+            _ => Some(p),
+        };
+        let weight = sharp
+            .iter()
+            .map(|h| sharpness[(h >> 1) as usize])
+            .sum::<f32>()
+            / sharp.len().max(1) as f32;
+        match sharp_pos {
+            // Boundary vertices have no smooth position to blend with.
+            Some(sharp_pos) if weight >= 1.0 || self.is_boundary_vertex_u32(v) => sharp_pos,
+            _ => {
+                let (fsum, esum, n) = self.voh_ccw_iter_u32(v).fold(
+                    (glam::Vec3::zero(), glam::Vec3::zero(), 0usize),
+                    |(fsum, esum, n), h| {
+                        (
+                            fsum + fpoints[self.face_index(h)],
+                            esum + (p + points[self.to_vertex_u32(h) as usize]) * 0.5,
+                            n + 1,
+                        )
+                    },
+                );
+                if n == 0 {
+                    return p; // Isolated vertex.
+                }
+                let nf = n as f32;
+                let smooth = (fsum / nf + esum * 2.0 / nf + p * (nf - 3.0)) / nf;
+                match sharp_pos {
+                    Some(sharp_pos) => smooth + (sharp_pos - smooth) * weight,
+                    None => smooth,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{
+        test::{quad_box, quad_grid},
+        FaceHandle,
+    };
+
+    /// Largest distance of the points from the plane through `origin` with
+    /// the normal `normal`.
+    fn max_plane_distance(points: &[glam::Vec3], origin: glam::Vec3, normal: glam::Vec3) -> f32 {
+        points
+            .iter()
+            .map(|p| (*p - origin).dot(normal).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn t_catmull_clark_box() {
+        let mut mesh = quad_box();
+        mesh.subdivide_catmull_clark().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_vertices(), 8 + 12 + 6);
+        assert_eq!(mesh.num_edges(), 48);
+        assert_eq!(mesh.num_faces(), 24);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        // The corners of a cube move towards the center, by a known amount.
+        let p = mesh.point_u32(0).unwrap();
+        assert!((p - glam::Vec3::splat(2.0 / 9.0)).length() < 1e-6, "{p:?}");
+        // The new faces inherit the properties of their parent faces.
+        let mut mesh = quad_box();
+        let mut fprop = mesh.create_face_prop::<u32>();
+        for f in 0..6 {
+            fprop.set(f, f + 10).unwrap();
+        }
+        mesh.subdivide_catmull_clark().unwrap();
+        for f in 0..(mesh.num_faces() as u32) {
+            assert_eq!(fprop.get(f).unwrap(), f / 4 + 10);
+        }
+    }
+
+    #[test]
+    fn t_catmull_clark_crease() {
+        let mut mesh = quad_box();
+        // Crease the edges of the bottom face, in the plane z = 0.
+        let bottom: Vec<_> = mesh.fe_ccw_iter(FaceHandle(0)).collect();
+        for e in bottom {
+            mesh.set_crease(e, 1.0).unwrap();
+        }
+        mesh.subdivide_catmull_clark().unwrap();
+        mesh.validate().unwrap();
+        // The children of the bottom face stay in its plane.
+        let flat: Vec<glam::Vec3> = (0..4)
+            .flat_map(|f| mesh.fv_ccw_iter_u32(f).collect::<Vec<_>>())
+            .map(|v| mesh.point_u32(v).unwrap())
+            .collect();
+        assert!(max_plane_distance(&flat, glam::Vec3::zero(), glam::Vec3::unit_z()) < 1e-6);
+        // The bottom corners slide along the creases.
+        assert!((mesh.point_u32(0).unwrap() - glam::vec3(0.125, 0.125, 0.0)).length() < 1e-6);
+        // The top face rounds off.
+        let top: Vec<glam::Vec3> = (20..24)
+            .flat_map(|f| mesh.fv_ccw_iter_u32(f).collect::<Vec<_>>())
+            .map(|v| mesh.point_u32(v).unwrap())
+            .collect();
+        let center = mesh.point_u32(8 + 12 + 5).unwrap();
+        assert!(max_plane_distance(&top, center, glam::Vec3::unit_z()) > 0.05);
+        // The weights drop by one per level, so a weight of 1 only lasts for
+        // one level and the halves of the creased edges are smooth.
+        let creased = mesh
+            .edges()
+            .filter(|e| mesh.crease(*e).unwrap() > 0.0)
+            .count();
+        assert_eq!(creased, 0);
+    }
+
+    #[test]
+    fn t_catmull_clark_fractional_crease() {
+        let heights = [0.0f32, 0.5, 2.0].map(|w| {
+            let mut mesh = quad_box();
+            let bottom: Vec<_> = mesh.fe_ccw_iter(FaceHandle(0)).collect();
+            for e in bottom {
+                mesh.set_crease(e, w).unwrap();
+            }
+            mesh.subdivide_catmull_clark().unwrap();
+            // The sharpness carries over to the halves of the edges.
+            let halves = mesh
+                .edges()
+                .filter(|e| mesh.crease(*e).unwrap() > 0.0)
+                .count();
+            assert_eq!(halves, if w > 1.0 { 8 } else { 0 });
+            mesh.point_u32(0).unwrap().z()
+        });
+        // The corner lifts off the plane less as the crease gets sharper.
+        assert!(heights[0] > heights[1] && heights[1] > heights[2]);
+        assert_eq!(heights[2], 0.0);
+    }
+
+    #[test]
+    fn t_catmull_clark_boundary() {
+        let mut mesh = quad_grid(2, 2);
+        mesh.subdivide_catmull_clark().unwrap();
+        mesh.validate().unwrap();
+        assert_eq!(mesh.num_faces(), 16);
+        assert_eq!(mesh.euler_characteristic(), 1);
+        // A flat grid stays flat. The boundary is a crease, so the corners
+        // are cut and the rest of the boundary stays on the sides.
+        for v in 0..(mesh.num_vertices() as u32) {
+            assert_eq!(mesh.point_u32(v).unwrap().z(), 0.0);
+        }
+        assert_eq!(mesh.point_u32(0).unwrap(), glam::vec3(0.125, 0.125, 0.0));
+        assert_eq!(mesh.point_u32(1).unwrap(), glam::vec3(1.0, 0.0, 0.0));
+        assert_eq!(mesh.point_u32(4).unwrap(), glam::vec3(1.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::mesh::Mesh;
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Compact representation of a mesh used for serialization. Only the vertex
+/// positions and the vertices of each face are stored, the halfedge
+/// connectivity is rebuilt when deserializing. Deleted elements are skipped.
+#[derive(Serialize, Deserialize)]
+struct MeshData {
+    points: Vec<[f32; 3]>,
+    faces: Vec<Vec<u32>>,
+}
+
+impl Serialize for Mesh {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (indices, _) = self.compact_vertex_indices();
+        let points = self
+            .vertices_u32()
+            .map(|v| {
+                self.point_u32(v)
+                    .map(|p| [p.x(), p.y(), p.z()])
+                    .map_err(S::Error::custom)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let faces = self
+            .faces_u32()
+            .map(|f| {
+                self.fv_ccw_iter_u32(f)
+                    .map(|v| indices[v as usize])
+                    .collect()
+            })
+            .collect();
+        MeshData { points, faces }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mesh {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = MeshData::deserialize(deserializer)?;
+        let nverts = data.points.len();
+        let mut mesh = Mesh::with_capacity(nverts, nverts + data.faces.len(), data.faces.len());
+        for [x, y, z] in data.points {
+            mesh.add_vertex_u32(glam::vec3(x, y, z))
+                .map_err(D::Error::custom)?;
+        }
+        for (i, face) in data.faces.iter().enumerate() {
+            if let Some(v) = face.iter().find(|v| **v as usize >= nverts) {
+                return Err(D::Error::custom(format!(
+                    "face {i} refers to vertex {v}, which does not exist"
+                )));
+            }
+            mesh.add_face_u32(face)
+                .map_err(|e| D::Error::custom(format!("unable to add face {i}: {e}")))?;
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{test::quad_box, Mesh};
+
+    #[test]
+    fn t_box_json_roundtrip() {
+        let mesh = quad_box();
+        let json = serde_json::to_string(&mesh).expect("Unable to serialize");
+        let copy: Mesh = serde_json::from_str(&json).expect("Unable to deserialize");
+        assert_eq!(copy.num_vertices(), mesh.num_vertices());
+        assert_eq!(copy.num_edges(), mesh.num_edges());
+        assert_eq!(copy.num_faces(), mesh.num_faces());
+        copy.validate().unwrap();
+        for v in 0..(mesh.num_vertices() as u32) {
+            assert_eq!(copy.point_u32(v).unwrap(), mesh.point_u32(v).unwrap());
+        }
+        for f in 0..(mesh.num_faces() as u32) {
+            assert!(copy.fv_ccw_iter_u32(f).eq(mesh.fv_ccw_iter_u32(f)));
+        }
+    }
+
+    #[test]
+    fn t_deserialize_complex_edge() {
+        // The second face repeats an edge of the first face in the same
+        // direction, which can't be represented.
+        let json = r#"{
+            "points": [[0,0,0],[1,0,0],[0,1,0],[1,1,0]],
+            "faces": [[0,1,2],[0,1,3]]
+        }"#;
+        let err = serde_json::from_str::<Mesh>(json).err().unwrap();
+        assert!(err.to_string().contains("face 1"));
+    }
+
+    #[test]
+    fn t_deserialize_missing_vertex() {
+        let json = r#"{"points": [[0,0,0],[1,0,0]], "faces": [[0,1,2]]}"#;
+        assert!(serde_json::from_str::<Mesh>(json).is_err());
+    }
+}